@@ -1,24 +1,37 @@
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint128, WasmMsg,
+    entry_point, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 
 use crate::error::ContractError;
-use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg};
+use crate::msg::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolExecuteMsg, PoolInfoResponse,
+    PoolQueryMsg, TokenSelect,
+};
 use crate::query::query_all_unbonding_info;
 use crate::state::{
     staker_info_key, staker_info_storage, unbonding_info_key, unbonding_info_storage,
-    user_earned_info_key, user_earned_info_storage, Config, Denom, StakerInfo, State,
-    UnbondingInfo, UserEarnedInfo, CONFIG, STATE,
+    user_earned_info_key, user_earned_info_storage, CompoundState, Config, Denom, LegacyMigration,
+    LockTier, RewardToken, StakerInfo, State, UnbondingInfo, UserEarnedInfo, COMPOUND_STATE,
+    CONFIG, LEGACY_CONFIG, LEGACY_MIGRATION, LEGACY_STAKER_INFO, LEGACY_STATE,
+    LEGACY_UNBONDING_INFO, LEGACY_USER_EARNED_INFO, OPERATORS, STATE,
 };
+use cw_storage_plus::Bound;
 
 use cw2::{get_contract_version, set_contract_version};
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
 
 use std::collections::BTreeMap;
 
 const CONTRACT_NAME: &str = "hopers_staking";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const LEGACY_CONTRACT_VERSION: &str = "0.1.0";
+
+const DEFAULT_MIGRATE_LIMIT: u32 = 30;
+const MAX_MIGRATE_LIMIT: u32 = 100;
+
+const COMPOUND_SWAP_REPLY_ID: u64 = 1;
+const COMPOUND_ADD_LIQUIDITY_REPLY_ID: u64 = 2;
 
 #[entry_point]
 pub fn instantiate(
@@ -29,27 +42,44 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    deps.api.addr_validate(&msg.lp_token_contract)?;
+    validate_staking_token(deps.as_ref(), &msg.staking_token)?;
+    assert_lock_tiers(&msg.lock_tiers)?;
+    assert_penalty(msg.instant_unbond_penalty)?;
+    if let Some(penalty_treasury) = &msg.penalty_treasury {
+        deps.api.addr_validate(penalty_treasury)?;
+    }
+    if let Some(pool_contract) = &msg.pool_contract {
+        validate_pool_contract(deps.as_ref(), &msg.staking_token, pool_contract)?;
+    }
 
-    CONFIG.save(
-        deps.storage,
-        &Config {
-            lp_token_contract: msg.lp_token_contract,
-            reward_token: msg.reward_token,
-            distribution_schedule: msg.distribution_schedule,
-            admin: info.sender.to_string(),
-            lock_duration: msg.lock_duration,
-        },
-    )?;
+    let config = Config {
+        staking_token: msg.staking_token,
+        reward_tokens: msg.reward_tokens.clone(),
+        admin: info.sender.to_string(),
+        lock_duration: msg.lock_duration,
+        lock_tiers: msg.lock_tiers,
+        instant_unbond_penalty: msg.instant_unbond_penalty,
+        penalty_treasury: msg.penalty_treasury,
+        pool_contract: msg.pool_contract,
+    };
+    CONFIG.save(deps.storage, &config)?;
 
-    STATE.save(
-        deps.storage,
-        &State {
-            last_distributed: env.block.time.seconds(),
-            total_bond_amount: Uint128::zero(),
-            global_reward_index: Decimal::zero(),
-        },
-    )?;
+    let mut state = State {
+        last_distributed: env.block.time.seconds(),
+        total_bond_amount: Uint128::zero(),
+        total_weight: Uint128::zero(),
+        global_reward_indexes: vec![Decimal::zero(); msg.reward_tokens.len()],
+        funded_rewards: vec![Uint128::zero(); msg.reward_tokens.len()],
+        distributed_rewards: vec![Uint128::zero(); msg.reward_tokens.len()],
+        penalty_reward_index: Decimal::zero(),
+    };
+    add_native_funds(&config, &mut state, &info.funds)?;
+    for (reward_token, funded_amount) in
+        config.reward_tokens.iter().zip(state.funded_rewards.iter())
+    {
+        assert_funded_schedule(&reward_token.distribution_schedule, *funded_amount)?;
+    }
+    STATE.save(deps.storage, &state)?;
 
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
@@ -61,25 +91,60 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // entries of the 0.1.0 layout can't be loaded until they are converted
+    if LEGACY_MIGRATION.may_load(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::MigrateLegacy { .. })
+    {
+        return Err(ContractError::LegacyMigrationPending {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::MigrateLegacy { limit } => migrate_legacy(deps, limit),
+        ExecuteMsg::BondNative { lock_duration } => bond_native(deps, env, info, lock_duration),
         ExecuteMsg::Unbond { amount } => unbond(deps, env, info, amount),
-        ExecuteMsg::Redeem {} => redeem(deps, env, info),
-        ExecuteMsg::Withdraw {} => withdraw(deps, env, info),
+        ExecuteMsg::Rebond { time, amount } => rebond(deps, env, info, time, amount),
+        ExecuteMsg::InstantUnbond { amount } => instant_unbond(deps, env, info, amount),
+        ExecuteMsg::Redeem { owner, recipient } => redeem(deps, env, info, owner, recipient),
+        ExecuteMsg::Withdraw { owner, recipient } => withdraw(deps, env, info, owner, recipient),
+        ExecuteMsg::Compound {
+            owner,
+            min_output,
+            min_lp_out,
+        } => compound(deps, env, info, owner, min_output, min_lp_out),
+        ExecuteMsg::ApproveOperator { operator, expires } => {
+            approve_operator(deps, env, info, operator, expires)
+        }
+        ExecuteMsg::RevokeOperator { operator } => revoke_operator(deps, info, operator),
         ExecuteMsg::UpdateConfig {
+            reward_token,
+            distribution_schedule,
+        } => update_config(deps, env, info, reward_token, distribution_schedule),
+        ExecuteMsg::FundRewards {} => fund_native_rewards(deps, info),
+        ExecuteMsg::AddRewardToken {
+            reward_token,
             distribution_schedule,
-        } => update_config(deps, env, info, distribution_schedule),
+        } => add_reward_token(deps, env, info, reward_token, distribution_schedule),
         ExecuteMsg::MigrateStaking {
             new_staking_contract,
         } => migrate_staking(deps, env, info, new_staking_contract),
         ExecuteMsg::UpdateAdmin { admin } => update_admin(deps, info, admin),
-        ExecuteMsg::UpdateTokenContract {
-            lp_token_contract,
-            reward_token,
-        } => update_token_contract(deps, info, lp_token_contract, reward_token),
+        ExecuteMsg::UpdateStakingToken { staking_token } => {
+            update_staking_token(deps, info, staking_token)
+        }
+        ExecuteMsg::UpdateTokenContract { lp_token_contract } => {
+            update_staking_token(deps, info, Denom::Cw20(Addr::unchecked(lp_token_contract)))
+        }
         ExecuteMsg::UpdateLockDuration { lock_duration } => {
             update_lock_duration(deps, info, lock_duration)
         }
+        ExecuteMsg::UpdatePoolContract { pool_contract } => {
+            update_pool_contract(deps, info, pool_contract)
+        }
+        ExecuteMsg::UpdateLockTiers { lock_tiers } => update_lock_tiers(deps, info, lock_tiers),
+        ExecuteMsg::UpdateInstantUnbondConfig { penalty, treasury } => {
+            update_instant_unbond_config(deps, info, penalty, treasury)
+        }
     }
 }
 
@@ -90,27 +155,48 @@ pub fn receive_cw20(
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let token_contract = info.sender.to_string();
 
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::Bond {}) => {
+        Ok(Cw20HookMsg::Bond { lock_duration }) => {
             // only staking token contract can execute this message
-            if config.lp_token_contract != token_contract {
+            if config.staking_token != Denom::Cw20(info.sender) {
                 return Err(ContractError::WrongContractError {});
             }
 
             let cw20_sender = cw20_msg.sender;
-            bond(deps, env, cw20_sender, cw20_msg.amount)
+            bond(deps, env, cw20_sender, cw20_msg.amount, lock_duration)
+        }
+        Ok(Cw20HookMsg::FundRewards {}) => {
+            fund_cw20_rewards(deps, Denom::Cw20(info.sender), cw20_msg.amount)
         }
         Err(_) => return Err(ContractError::DataShouldBeGiven {}),
     }
 }
 
+pub fn bond_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let amount = match (&config.staking_token, info.funds.as_slice()) {
+        (Denom::Native(denom), [coin]) if coin.denom == *denom && !coin.amount.is_zero() => {
+            coin.amount
+        }
+        _ => return Err(ContractError::InvalidFunds {}),
+    };
+
+    bond(deps, env, info.sender.to_string(), amount, lock_duration)
+}
+
 pub fn bond(
     deps: DepsMut,
     env: Env,
     sender_addr: String,
     amount: Uint128,
+    lock_duration: Option<u64>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
@@ -121,9 +207,13 @@ pub fn bond(
         Some(some_staker_info) => staker_info = some_staker_info,
         None => {
             staker_info = StakerInfo {
-                reward_index: Decimal::zero(),
+                reward_indexes: vec![],
                 bond_amount: Uint128::zero(),
-                pending_reward: Uint128::zero(),
+                weight: Uint128::zero(),
+                lock_tier: None,
+                pending_rewards: vec![],
+                penalty_reward_index: Decimal::zero(),
+                pending_penalty_reward: Uint128::zero(),
                 address: sender_addr.clone(),
             }
         }
@@ -133,6 +223,21 @@ pub fn bond(
     compute_reward(&config, &mut state, env.block.time.seconds());
     compute_staker_reward(&state, &mut staker_info)?;
 
+    // The tier applies to the whole bond, so it can only get longer
+    if let Some(lock_duration) = lock_duration {
+        let lock_tier = config
+            .lock_tiers
+            .iter()
+            .find(|tier| tier.duration == lock_duration)
+            .ok_or(ContractError::LockTierNotFound {})?;
+        if let Some(current_tier) = &staker_info.lock_tier {
+            if current_tier.duration > lock_tier.duration {
+                return Err(ContractError::LockTierDowngrade {});
+            }
+        }
+        staker_info.lock_tier = Some(lock_tier.clone());
+    }
+
     // Increase bond_amount
     increase_bond_amount(&mut state, &mut staker_info, amount);
 
@@ -175,10 +280,20 @@ pub fn unbond(
 
     // Decrease bond_amount
     decrease_bond_amount(&mut state, &mut staker_info, amount)?;
+    let lock_duration = staker_info
+        .lock_tier
+        .as_ref()
+        .map_or(config.lock_duration, |tier| tier.duration);
 
     // Store or remove updated rewards info
     // depends on the left pending reward and bond amount
-    if staker_info.pending_reward.is_zero() && staker_info.bond_amount.is_zero() {
+    if staker_info
+        .pending_rewards
+        .iter()
+        .all(|reward| reward.is_zero())
+        && staker_info.pending_penalty_reward.is_zero()
+        && staker_info.bond_amount.is_zero()
+    {
         staker_info_storage().remove(deps.storage, staker_info_key)?;
     } else {
         staker_info_storage().save(deps.storage, staker_info_key, &staker_info)?;
@@ -195,6 +310,7 @@ pub fn unbond(
             address: sender_addr.clone(),
             amount,
             time,
+            lock_duration,
         },
     )?;
 
@@ -205,21 +321,197 @@ pub fn unbond(
     ]))
 }
 
-pub fn redeem(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn rebond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    time: Option<u64>,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
     let sender_addr = info.sender.to_string();
+
+    // Take the amount out of the unbonding entries, latest first
+    let mut remaining = amount;
+    let mut rebonded_infos = vec![];
+    let unbonding_infos =
+        query_all_unbonding_info(deps.as_ref(), env.clone(), sender_addr.clone())?;
+    for mut unbonding_info in unbonding_infos.into_iter().rev() {
+        if remaining.is_zero() {
+            break;
+        }
+        if matches!(time, Some(time) if time != unbonding_info.time) {
+            continue;
+        }
+        let rebond_amount = std::cmp::min(remaining, unbonding_info.amount);
+        remaining -= rebond_amount;
+        unbonding_info.amount -= rebond_amount;
+        rebonded_infos.push(unbonding_info);
+    }
+    if !remaining.is_zero() {
+        return Err(ContractError::ExceedUnbondingAmount {});
+    }
+    for unbonding_info in rebonded_infos {
+        let unbonding_info_key = unbonding_info_key(&sender_addr, unbonding_info.time);
+        if unbonding_info.amount.is_zero() {
+            unbonding_info_storage().remove(deps.storage, unbonding_info_key)?;
+        } else {
+            unbonding_info_storage().save(deps.storage, unbonding_info_key, &unbonding_info)?;
+        }
+    }
+
+    let staker_info_key = staker_info_key(&sender_addr);
+    let mut staker_info = staker_info_storage()
+        .may_load(deps.storage, staker_info_key.clone())?
+        .unwrap_or(StakerInfo {
+            reward_indexes: vec![],
+            bond_amount: Uint128::zero(),
+            weight: Uint128::zero(),
+            lock_tier: None,
+            pending_rewards: vec![],
+            penalty_reward_index: Decimal::zero(),
+            pending_penalty_reward: Uint128::zero(),
+            address: sender_addr.clone(),
+        });
+
+    // Compute global reward & staker reward
+    compute_reward(&config, &mut state, env.block.time.seconds());
+    compute_staker_reward(&state, &mut staker_info)?;
+
+    // Increase bond_amount
+    increase_bond_amount(&mut state, &mut staker_info, amount);
+
+    // Store updated state with staker's staker_info
+    staker_info_storage().save(deps.storage, staker_info_key, &staker_info)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "rebond"),
+        ("owner", sender_addr.as_str()),
+        ("amount", amount.to_string().as_str()),
+    ]))
+}
+
+pub fn instant_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let sender_addr = info.sender.to_string();
+
+    let staker_info_key = staker_info_key(&sender_addr);
+    let mut staker_info = staker_info_storage()
+        .may_load(deps.storage, staker_info_key.clone())?
+        .ok_or(ContractError::NotStaked {})?;
+
+    if staker_info.bond_amount < amount {
+        return Err(ContractError::ExceedBondAmount {});
+    }
+
+    // Compute global reward & staker reward
+    compute_reward(&config, &mut state, env.block.time.seconds());
+    compute_staker_reward(&state, &mut staker_info)?;
+
+    // Decrease bond_amount
+    decrease_bond_amount(&mut state, &mut staker_info, amount)?;
+
+    let penalty = get_instant_unbond_penalty(&config, staker_info.lock_tier.as_ref(), amount);
+    let mut messages = vec![get_transfer_msg(
+        &config.staking_token,
+        &sender_addr,
+        amount - penalty,
+    )?];
+    if !penalty.is_zero() {
+        match &config.penalty_treasury {
+            Some(penalty_treasury) => messages.push(get_transfer_msg(
+                &config.staking_token,
+                penalty_treasury,
+                penalty,
+            )?),
+            None if !state.total_weight.is_zero() => {
+                state.penalty_reward_index =
+                    state.penalty_reward_index + Decimal::from_ratio(penalty, state.total_weight);
+            }
+            None => messages.push(get_transfer_msg(
+                &config.staking_token,
+                &config.admin,
+                penalty,
+            )?),
+        }
+    }
+
+    // Store or remove updated rewards info
+    // depends on the left pending reward and bond amount
+    if staker_info
+        .pending_rewards
+        .iter()
+        .all(|reward| reward.is_zero())
+        && staker_info.pending_penalty_reward.is_zero()
+        && staker_info.bond_amount.is_zero()
+    {
+        staker_info_storage().remove(deps.storage, staker_info_key)?;
+    } else {
+        staker_info_storage().save(deps.storage, staker_info_key, &staker_info)?;
+    }
+
+    // Store updated state
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "instant_unbond"),
+        ("owner", sender_addr.as_str()),
+        ("amount", amount.to_string().as_str()),
+        ("penalty", penalty.to_string().as_str()),
+    ]))
+}
+
+// the penalty grows with the lock that is skipped, a tier locked for twice the
+// default lock duration pays twice the penalty, up to the whole amount
+pub fn get_instant_unbond_penalty(
+    config: &Config,
+    lock_tier: Option<&LockTier>,
+    amount: Uint128,
+) -> Uint128 {
+    let penalty = amount * config.instant_unbond_penalty;
+    match lock_tier {
+        Some(tier) if tier.duration > config.lock_duration => std::cmp::min(
+            amount,
+            penalty.multiply_ratio(tier.duration, std::cmp::max(config.lock_duration, 1)),
+        ),
+        _ => penalty,
+    }
+}
+
+pub fn redeem(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Option<String>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let owner_addr = get_owner(deps.as_ref(), &env, &info, owner)?;
+    let recipient = get_recipient(deps.as_ref(), recipient, &owner_addr)?;
+    // operators can't move the principal out of the owner's account
+    if info.sender != owner_addr && recipient != owner_addr {
+        return Err(ContractError::OperatorRecipient {});
+    }
     let crr_time = env.block.time.seconds();
 
     let config = CONFIG.load(deps.storage)?;
 
     let mut amount = Uint128::zero();
 
-    let unbonding_infos = query_all_unbonding_info(deps.as_ref(), env, sender_addr.clone())?;
+    let unbonding_infos = query_all_unbonding_info(deps.as_ref(), env, owner_addr.clone())?;
     for unbonding_info in unbonding_infos {
-        if unbonding_info.time + config.lock_duration > crr_time {
-            break;
+        if unbonding_info.time + unbonding_info.lock_duration > crr_time {
+            continue;
         } else {
             amount += unbonding_info.amount;
-            let unbonding_info_key = unbonding_info_key(&sender_addr, unbonding_info.time);
+            let unbonding_info_key = unbonding_info_key(&owner_addr, unbonding_info.time);
             unbonding_info_storage().remove(deps.storage, unbonding_info_key.clone())?;
         }
     }
@@ -229,29 +521,34 @@ pub fn redeem(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Co
     }
 
     Ok(Response::new()
-        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.lp_token_contract,
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount,
-            })?,
-            funds: vec![],
-        })])
+        .add_message(get_transfer_msg(&config.staking_token, &recipient, amount)?)
         .add_attributes(vec![
             ("action", "redeem"),
-            ("owner", info.sender.as_str()),
+            ("owner", owner_addr.as_str()),
+            ("recipient", recipient.as_str()),
             ("amount", amount.to_string().as_str()),
         ]))
 }
 
-// withdraw rewards to executor
-pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    let sender_addr = info.sender.to_string();
+// withdraw rewards to the recipient, the owner by default
+pub fn withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Option<String>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let owner_addr = get_owner(deps.as_ref(), &env, &info, owner)?;
+    let recipient = get_recipient(deps.as_ref(), recipient, &owner_addr)?;
+    // operators can't move the rewards out of the owner's account
+    if info.sender != owner_addr && recipient != owner_addr {
+        return Err(ContractError::OperatorRecipient {});
+    }
 
     let config = CONFIG.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
 
-    let staker_info_key = staker_info_key(&sender_addr);
+    let staker_info_key = staker_info_key(&owner_addr);
     let mut staker_info: StakerInfo;
     match staker_info_storage().may_load(deps.storage, staker_info_key.clone())? {
         Some(some_staker_info) => staker_info = some_staker_info,
@@ -262,30 +559,17 @@ pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response,
     compute_reward(&config, &mut state, env.block.time.seconds());
     compute_staker_reward(&state, &mut staker_info)?;
 
-    let user_earned_info_key = user_earned_info_key(&sender_addr);
-    match user_earned_info_storage().may_load(deps.storage, user_earned_info_key.clone())? {
-        Some(mut user_earned_info) => {
-            user_earned_info.total_earned += staker_info.pending_reward;
-            user_earned_info_storage().save(
-                deps.storage,
-                user_earned_info_key,
-                &user_earned_info,
-            )?;
-        }
-        None => {
-            user_earned_info_storage().save(
-                deps.storage,
-                user_earned_info_key,
-                &UserEarnedInfo {
-                    address: sender_addr,
-                    total_earned: staker_info.pending_reward,
-                },
-            )?;
-        }
-    }
+    add_total_earned(
+        deps.storage,
+        owner_addr.clone(),
+        &staker_info.pending_rewards,
+    )?;
 
-    let amount = staker_info.pending_reward;
-    staker_info.pending_reward = Uint128::zero();
+    let amounts = std::mem::replace(
+        &mut staker_info.pending_rewards,
+        vec![Uint128::zero(); state.global_reward_indexes.len()],
+    );
+    let penalty_reward = std::mem::take(&mut staker_info.pending_penalty_reward);
 
     // Store or remove updated rewards info
     // depends on the left pending reward and bond amount
@@ -298,32 +582,382 @@ pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response,
     // Store updated state
     STATE.save(deps.storage, &state)?;
 
-    let reward_msg: CosmosMsg;
+    let mut reward_msgs: Vec<CosmosMsg> = vec![];
+    for (reward_token, amount) in config.reward_tokens.iter().zip(amounts.iter()) {
+        if !amount.is_zero() {
+            reward_msgs.push(get_transfer_msg(&reward_token.denom, &recipient, *amount)?);
+        }
+    }
+    if !penalty_reward.is_zero() {
+        reward_msgs.push(get_transfer_msg(
+            &config.staking_token,
+            &recipient,
+            penalty_reward,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_messages(reward_msgs)
+        .add_attributes(vec![
+            ("action", "withdraw"),
+            ("owner", owner_addr.as_str()),
+            ("recipient", recipient.as_str()),
+            ("amount", join_amounts(&amounts).as_str()),
+            ("penalty_reward", penalty_reward.to_string().as_str()),
+        ]))
+}
 
-    match config.reward_token {
-        Denom::Native(denom) => {
-            reward_msg = CosmosMsg::Bank(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin { denom, amount }],
-            })
+// staker the sender acts for, acting for another owner needs its operator approval
+fn get_owner(
+    deps: Deps,
+    env: &Env,
+    info: &MessageInfo,
+    owner: Option<String>,
+) -> Result<String, ContractError> {
+    let owner = match owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => return Ok(info.sender.to_string()),
+    };
+    if owner != info.sender {
+        match OPERATORS.may_load(deps.storage, (&owner, &info.sender))? {
+            Some(expires) if !expires.is_expired(&env.block) => {}
+            _ => return Err(ContractError::Unauthorized {}),
         }
-        Denom::Cw20(address) => {
-            reward_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+    }
+    Ok(owner.into_string())
+}
+
+fn get_recipient(deps: Deps, recipient: Option<String>, owner_addr: &str) -> StdResult<String> {
+    match recipient {
+        Some(recipient) => Ok(deps.api.addr_validate(&recipient)?.into_string()),
+        None => Ok(owner_addr.to_string()),
+    }
+}
+
+pub fn approve_operator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    OPERATORS.save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "approve_operator"),
+        ("owner", info.sender.as_str()),
+        ("operator", operator.as_str()),
+    ]))
+}
+
+pub fn revoke_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    OPERATORS.remove(deps.storage, (&info.sender, &operator_addr));
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "revoke_operator"),
+        ("owner", info.sender.as_str()),
+        ("operator", operator.as_str()),
+    ]))
+}
+
+fn add_total_earned(
+    storage: &mut dyn Storage,
+    address: String,
+    amounts: &[Uint128],
+) -> StdResult<()> {
+    let user_earned_info_key = user_earned_info_key(&address);
+    let mut user_earned_info = user_earned_info_storage()
+        .may_load(storage, user_earned_info_key.clone())?
+        .unwrap_or(UserEarnedInfo {
+            address,
+            total_earned: vec![],
+        });
+    user_earned_info
+        .total_earned
+        .resize(amounts.len(), Uint128::zero());
+    for (total_earned, amount) in user_earned_info.total_earned.iter_mut().zip(amounts.iter()) {
+        *total_earned += *amount;
+    }
+    user_earned_info_storage().save(storage, user_earned_info_key, &user_earned_info)
+}
+
+// swap half of the pending pool reward, the rest happens in the replies
+pub fn compound(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Option<String>,
+    min_output: Uint128,
+    min_lp_out: Uint128,
+) -> Result<Response, ContractError> {
+    let owner_addr = get_owner(deps.as_ref(), &env, &info, owner)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let pool_contract = config
+        .pool_contract
+        .clone()
+        .ok_or(ContractError::PoolNotConfigured {})?;
+    let pool_info: PoolInfoResponse = deps
+        .querier
+        .query_wasm_smart(&pool_contract, &PoolQueryMsg::Info {})?;
+
+    let staker_info_key = staker_info_key(&owner_addr);
+    let mut staker_info = staker_info_storage()
+        .may_load(deps.storage, staker_info_key.clone())?
+        .ok_or(ContractError::NotStaked {})?;
+
+    // Compute global reward & staker reward
+    compute_reward(&config, &mut state, env.block.time.seconds());
+    compute_staker_reward(&state, &mut staker_info)?;
+
+    let reward_index = config
+        .reward_tokens
+        .iter()
+        .position(|token| {
+            token.denom == pool_info.token1_denom || token.denom == pool_info.token2_denom
+        })
+        .ok_or(ContractError::RewardNotInPool {})?;
+    let reward_denom = config.reward_tokens[reward_index].denom.clone();
+    let amount = staker_info.pending_rewards[reward_index];
+    if amount.is_zero() {
+        return Err(ContractError::NothingToCompound {});
+    }
+
+    let mut amounts = vec![Uint128::zero(); staker_info.pending_rewards.len()];
+    amounts[reward_index] = amount;
+    add_total_earned(deps.storage, owner_addr.clone(), &amounts)?;
+    staker_info.pending_rewards[reward_index] = Uint128::zero();
+
+    staker_info_storage().save(deps.storage, staker_info_key, &staker_info)?;
+    STATE.save(deps.storage, &state)?;
+
+    let swap_amount = amount.multiply_ratio(1u128, 2u128);
+    let reward_is_token1 = reward_denom == pool_info.token1_denom;
+    COMPOUND_STATE.save(
+        deps.storage,
+        &CompoundState {
+            staker: owner_addr.clone(),
+            reward_is_token1,
+            remaining_amount: amount - swap_amount,
+            min_lp_out,
+        },
+    )?;
+
+    let (mut messages, funds) = get_pool_input_msgs(&reward_denom, &pool_contract, swap_amount)?;
+    messages.push(SubMsg::reply_on_success(
+        WasmMsg::Execute {
+            contract_addr: pool_contract,
+            msg: to_binary(&PoolExecuteMsg::Swap {
+                input_token: if reward_is_token1 {
+                    TokenSelect::Token1
+                } else {
+                    TokenSelect::Token2
+                },
+                input_amount: swap_amount,
+                min_output,
+                expiration: None,
+            })?,
+            funds,
+        },
+        COMPOUND_SWAP_REPLY_ID,
+    ));
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            ("action", "compound"),
+            ("owner", owner_addr.as_str()),
+            ("amount", amount.to_string().as_str()),
+        ]))
+}
+
+// allowance message for a cw20 pool input or the funds of a native one
+fn get_pool_input_msgs(
+    denom: &Denom,
+    pool_contract: &str,
+    amount: Uint128,
+) -> StdResult<(Vec<SubMsg>, Vec<Coin>)> {
+    if amount.is_zero() {
+        return Ok((vec![], vec![]));
+    }
+    Ok(match denom {
+        Denom::Native(denom) => (
+            vec![],
+            vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        ),
+        Denom::Cw20(address) => (
+            vec![SubMsg::new(WasmMsg::Execute {
                 contract_addr: address.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: info.sender.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: pool_contract.to_string(),
                     amount,
+                    expires: None,
                 })?,
                 funds: vec![],
-            });
+            })],
+            vec![],
+        ),
+    })
+}
+
+fn get_reply_amount(msg: Reply, event_type: &str, key: &str) -> Result<Uint128, ContractError> {
+    let response = msg
+        .result
+        .into_result()
+        .map_err(|_| ContractError::InvalidPoolReply {
+            key: key.to_string(),
+        })?;
+    response
+        .events
+        .iter()
+        .filter(|event| event.ty == event_type)
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == key)
+        .and_then(|attr| attr.value.parse::<u128>().ok())
+        .map(Uint128::from)
+        .ok_or(ContractError::InvalidPoolReply {
+            key: key.to_string(),
+        })
+}
+
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        COMPOUND_SWAP_REPLY_ID => {
+            let output_amount = get_reply_amount(msg, "wasm-hopers_swap", "output_amount")?;
+            add_compound_liquidity(deps, output_amount)
+        }
+        COMPOUND_ADD_LIQUIDITY_REPLY_ID => {
+            let liquidity_amount =
+                get_reply_amount(msg, "wasm-hopers_add_liquidity", "liquidity_amount")?;
+            let compound_state = COMPOUND_STATE.load(deps.storage)?;
+            COMPOUND_STATE.remove(deps.storage);
+            bond(deps, env, compound_state.staker, liquidity_amount, None)
         }
+        id => Err(ContractError::UnknownReplyId { id }),
     }
+}
 
-    Ok(Response::new().add_message(reward_msg).add_attributes(vec![
-        ("action", "withdraw"),
-        ("owner", info.sender.as_str()),
-        ("amount", amount.to_string().as_str()),
-    ]))
+// add liquidity with the swapped and the remaining reward at the new pool price,
+// amounts the pool can not take are sent back to the staker
+fn add_compound_liquidity(
+    deps: DepsMut,
+    output_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let compound_state = COMPOUND_STATE.load(deps.storage)?;
+    let pool_contract = config
+        .pool_contract
+        .ok_or(ContractError::PoolNotConfigured {})?;
+    let pool_info: PoolInfoResponse = deps
+        .querier
+        .query_wasm_smart(&pool_contract, &PoolQueryMsg::Info {})?;
+
+    let (token1_available, token2_available) = if compound_state.reward_is_token1 {
+        (compound_state.remaining_amount, output_amount)
+    } else {
+        (output_amount, compound_state.remaining_amount)
+    };
+
+    // the pool requires token1_amount * token2_reserve / token1_reserve + 1 of token2
+    let token1_amount = std::cmp::min(
+        token1_available,
+        token2_available
+            .saturating_sub(Uint128::new(1))
+            .multiply_ratio(pool_info.token1_reserve, pool_info.token2_reserve),
+    );
+    if token1_amount.is_zero() {
+        return Err(ContractError::NothingToCompound {});
+    }
+    let token2_amount = token1_amount
+        .multiply_ratio(pool_info.token2_reserve, pool_info.token1_reserve)
+        + Uint128::new(1);
+
+    let (mut messages, mut funds) =
+        get_pool_input_msgs(&pool_info.token1_denom, &pool_contract, token1_amount)?;
+    let (token2_messages, token2_funds) =
+        get_pool_input_msgs(&pool_info.token2_denom, &pool_contract, token2_amount)?;
+    messages.extend(token2_messages);
+    funds.extend(token2_funds);
+    messages.push(SubMsg::reply_on_success(
+        WasmMsg::Execute {
+            contract_addr: pool_contract,
+            msg: to_binary(&PoolExecuteMsg::AddLiquidity {
+                token1_amount,
+                min_liquidity: compound_state.min_lp_out,
+                max_token2: token2_amount,
+                expiration: None,
+            })?,
+            funds,
+        },
+        COMPOUND_ADD_LIQUIDITY_REPLY_ID,
+    ));
+
+    for (denom, amount) in [
+        (&pool_info.token1_denom, token1_available - token1_amount),
+        (&pool_info.token2_denom, token2_available - token2_amount),
+    ] {
+        if !amount.is_zero() {
+            messages.push(SubMsg::new(get_transfer_msg(
+                denom,
+                &compound_state.staker,
+                amount,
+            )?));
+        }
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            ("action", "compound_add_liquidity"),
+            ("owner", compound_state.staker.as_str()),
+            ("token1_amount", token1_amount.to_string().as_str()),
+            ("token2_amount", token2_amount.to_string().as_str()),
+        ]))
+}
+
+fn get_transfer_msg(denom: &Denom, recipient: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match denom {
+        Denom::Native(denom) => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        }),
+        Denom::Cw20(address) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }),
+    })
+}
+
+// amounts of the reward tokens in config order, e.g. "100,0,25"
+fn join_amounts(amounts: &[Uint128]) -> String {
+    amounts
+        .iter()
+        .map(|amount| amount.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 pub fn migrate_staking(
@@ -344,34 +978,55 @@ pub fn migrate_staking(
     // compute global reward, sets last_distributed_seconds to env.block.time.seconds
     compute_reward(&config, &mut state, env.block.time.seconds());
 
-    let total_distribution_amount: Uint128 =
-        config.distribution_schedule.iter().map(|item| item.2).sum();
-
     let block_time = env.block.time.seconds();
-    // eliminate distribution slots that have not started
-    config
-        .distribution_schedule
-        .retain(|slot| slot.0 < block_time);
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut distributed_amounts = vec![];
+    let mut remaining_amounts = vec![];
+    for (reward_token, funded_amount) in config
+        .reward_tokens
+        .iter_mut()
+        .zip(state.funded_rewards.iter_mut())
+    {
+        // eliminate distribution slots that have not started
+        reward_token
+            .distribution_schedule
+            .retain(|slot| slot.0 < block_time);
+
+        let mut distributed_amount = Uint128::zero();
+        for s in reward_token.distribution_schedule.iter_mut() {
+            if s.1 < block_time {
+                // all distributed
+                distributed_amount += s.2;
+            } else {
+                // partially distributed slot
+                let whole_time = s.1 - s.0;
+                let distribution_amount_per_second: Decimal = Decimal::from_ratio(s.2, whole_time);
+
+                let passed_time = block_time - s.0;
+                let distributed_amount_on_slot =
+                    distribution_amount_per_second * Uint128::from(passed_time as u128);
+                distributed_amount += distributed_amount_on_slot;
+
+                // modify distribution slot
+                s.1 = block_time;
+                s.2 = distributed_amount_on_slot;
+            }
+        }
 
-    let mut distributed_amount = Uint128::zero();
-    for s in config.distribution_schedule.iter_mut() {
-        if s.1 < block_time {
-            // all distributed
-            distributed_amount += s.2;
-        } else {
-            // partially distributed slot
-            let whole_time = s.1 - s.0;
-            let distribution_amount_per_second: Decimal = Decimal::from_ratio(s.2, whole_time);
-
-            let passed_time = block_time - s.0;
-            let distributed_amount_on_slot =
-                distribution_amount_per_second * Uint128::from(passed_time as u128);
-            distributed_amount += distributed_amount_on_slot;
-
-            // modify distribution slot
-            s.1 = block_time;
-            s.2 = distributed_amount_on_slot;
+        // return the funds that will not be distributed
+        let remaining_amount = funded_amount
+            .checked_sub(distributed_amount)
+            .unwrap_or_default();
+        *funded_amount = distributed_amount;
+        if !remaining_amount.is_zero() {
+            messages.push(get_transfer_msg(
+                &reward_token.denom,
+                &new_staking_contract,
+                remaining_amount,
+            )?);
         }
+        distributed_amounts.push(distributed_amount);
+        remaining_amounts.push(remaining_amount);
     }
 
     // update config
@@ -379,49 +1034,158 @@ pub fn migrate_staking(
     // update state
     STATE.save(deps.storage, &state)?;
 
-    let remaining_lp = total_distribution_amount
-        .checked_sub(distributed_amount)
-        .unwrap_or_default();
-
-    Ok(Response::new()
-        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.lp_token_contract,
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: new_staking_contract,
-                amount: remaining_lp,
-            })?,
-            funds: vec![],
-        })])
-        .add_attributes(vec![
-            ("action", "migrate_staking"),
-            ("distributed_amount", &distributed_amount.to_string()),
-            ("remaining_amount", &remaining_lp.to_string()),
-        ]))
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "migrate_staking"),
+        ("distributed_amount", &join_amounts(&distributed_amounts)),
+        ("remaining_amount", &join_amounts(&remaining_amounts)),
+    ]))
 }
 
 pub fn update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
+    reward_token: Denom,
     distribution_schedule: Vec<(u64, u64, Uint128)>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
     let state = STATE.load(deps.storage)?;
 
     authcheck(deps.as_ref(), &info)?;
 
-    assert_new_schedules(&config, &state, distribution_schedule.clone())?;
+    let reward_index = config
+        .reward_tokens
+        .iter()
+        .position(|token| token.denom == reward_token)
+        .ok_or(ContractError::RewardTokenNotFound {})?;
+    let reward_token = &mut config.reward_tokens[reward_index];
+    assert_new_schedules(
+        &reward_token.distribution_schedule,
+        &state,
+        distribution_schedule.clone(),
+    )?;
+    assert_funded_schedule(&distribution_schedule, state.funded_rewards[reward_index])?;
+    reward_token.distribution_schedule = distribution_schedule;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+}
+
+pub fn add_reward_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_token: Denom,
+    distribution_schedule: Vec<(u64, u64, Uint128)>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    authcheck(deps.as_ref(), &info)?;
+    if config
+        .reward_tokens
+        .iter()
+        .any(|token| token.denom == reward_token)
+    {
+        return Err(ContractError::DuplicateRewardToken {});
+    }
+
+    // Distribute the current tokens up to now, the new one starts from zero
+    compute_reward(&config, &mut state, env.block.time.seconds());
+    assert_new_schedules(&[], &state, distribution_schedule.clone())?;
 
-    let new_config = Config {
-        admin: config.admin,
-        lp_token_contract: config.lp_token_contract,
-        reward_token: config.reward_token,
+    config.reward_tokens.push(RewardToken {
+        denom: reward_token,
         distribution_schedule,
-        lock_duration: config.lock_duration,
-    };
-    CONFIG.save(deps.storage, &new_config)?;
+    });
+    state.global_reward_indexes.push(Decimal::zero());
+    state.funded_rewards.push(Uint128::zero());
+    state.distributed_rewards.push(Uint128::zero());
+    add_native_funds(&config, &mut state, &info.funds)?;
+    if let (Some(reward_token), Some(funded_amount)) =
+        (config.reward_tokens.last(), state.funded_rewards.last())
+    {
+        assert_funded_schedule(&reward_token.distribution_schedule, *funded_amount)?;
+    }
+    CONFIG.save(deps.storage, &config)?;
+    STATE.save(deps.storage, &state)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+    Ok(Response::new().add_attributes(vec![("action", "add_reward_token")]))
+}
+
+pub fn fund_native_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    if info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+    add_native_funds(&config, &mut state, &info.funds)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "fund_rewards"),
+        ("funder", info.sender.as_str()),
+    ]))
+}
+
+pub fn fund_cw20_rewards(
+    deps: DepsMut,
+    denom: Denom,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    add_reward_funds(&config, &mut state, &denom, amount)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "fund_rewards"),
+        ("amount", amount.to_string().as_str()),
+    ]))
+}
+
+fn add_native_funds(
+    config: &Config,
+    state: &mut State,
+    funds: &[Coin],
+) -> Result<(), ContractError> {
+    for coin in funds {
+        add_reward_funds(
+            config,
+            state,
+            &Denom::Native(coin.denom.clone()),
+            coin.amount,
+        )?;
+    }
+    Ok(())
+}
+
+fn add_reward_funds(
+    config: &Config,
+    state: &mut State,
+    denom: &Denom,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let reward_index = config
+        .reward_tokens
+        .iter()
+        .position(|token| token.denom == *denom)
+        .ok_or(ContractError::RewardTokenNotFound {})?;
+    state.funded_rewards[reward_index] += amount;
+    Ok(())
+}
+
+fn assert_funded_schedule(
+    distribution_schedule: &[(u64, u64, Uint128)],
+    funded_amount: Uint128,
+) -> Result<(), ContractError> {
+    let total_distribution_amount: Uint128 = distribution_schedule.iter().map(|item| item.2).sum();
+    if total_distribution_amount > funded_amount {
+        return Err(ContractError::InsufficientRewardFunds {});
+    }
+    Ok(())
 }
 
 pub fn update_admin(
@@ -440,23 +1204,130 @@ pub fn update_admin(
     Ok(Response::new().add_attributes(vec![("action", "update_admin")]))
 }
 
-pub fn update_token_contract(
+pub fn update_staking_token(
     deps: DepsMut,
     info: MessageInfo,
-    lp_contract: String,
-    reward_token: Denom,
+    staking_token: Denom,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
-    deps.api.addr_validate(&lp_contract)?;
+    validate_staking_token(deps.as_ref(), &staking_token)?;
 
     authcheck(deps.as_ref(), &info)?;
-    config.reward_token = reward_token;
-    config.lp_token_contract = lp_contract;
+
+    // bonded and unbonding tokens are paid back in the staking token
+    let state = STATE.load(deps.storage)?;
+    let is_unbonding = unbonding_info_storage()
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if !state.total_bond_amount.is_zero() || is_unbonding {
+        return Err(ContractError::StakingTokenInUse {});
+    }
+
+    config.staking_token = staking_token;
 
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_admin")]))
+    Ok(Response::new().add_attributes(vec![("action", "update_staking_token")]))
+}
+
+fn validate_staking_token(deps: Deps, staking_token: &Denom) -> StdResult<()> {
+    if let Denom::Cw20(address) = staking_token {
+        deps.api.addr_validate(address.as_str())?;
+    }
+    Ok(())
+}
+
+pub fn update_pool_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    authcheck(deps.as_ref(), &info)?;
+    if let Some(pool_contract) = &pool_contract {
+        validate_pool_contract(deps.as_ref(), &config.staking_token, pool_contract)?;
+    }
+    config.pool_contract = pool_contract;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_pool_contract")]))
+}
+
+// the pool has to mint the staked LP token for compounded liquidity to be bonded
+fn validate_pool_contract(
+    deps: Deps,
+    staking_token: &Denom,
+    pool_contract: &str,
+) -> Result<(), ContractError> {
+    deps.api.addr_validate(pool_contract)?;
+    let pool_info: PoolInfoResponse = deps
+        .querier
+        .query_wasm_smart(pool_contract, &PoolQueryMsg::Info {})?;
+    match staking_token {
+        Denom::Cw20(address) if address.as_str() == pool_info.lp_token_address => Ok(()),
+        _ => Err(ContractError::PoolLpTokenMismatch {}),
+    }
+}
+
+pub fn update_lock_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    lock_tiers: Vec<LockTier>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    authcheck(deps.as_ref(), &info)?;
+    assert_lock_tiers(&lock_tiers)?;
+    config.lock_tiers = lock_tiers;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_lock_tiers")]))
+}
+
+pub fn update_instant_unbond_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    penalty: Decimal,
+    treasury: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    authcheck(deps.as_ref(), &info)?;
+    assert_penalty(penalty)?;
+    if let Some(treasury) = &treasury {
+        deps.api.addr_validate(treasury)?;
+    }
+    config.instant_unbond_penalty = penalty;
+    config.penalty_treasury = treasury;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_instant_unbond_config")]))
+}
+
+fn assert_penalty(penalty: Decimal) -> Result<(), ContractError> {
+    if penalty > Decimal::one() {
+        return Err(ContractError::InvalidPenalty {});
+    }
+    Ok(())
+}
+
+fn assert_lock_tiers(lock_tiers: &[LockTier]) -> Result<(), ContractError> {
+    for (i, tier) in lock_tiers.iter().enumerate() {
+        if tier.multiplier < Decimal::one()
+            || lock_tiers[..i]
+                .iter()
+                .any(|other| other.duration == tier.duration)
+        {
+            return Err(ContractError::InvalidLockTiers {});
+        }
+    }
+    Ok(())
 }
 
 pub fn update_lock_duration(
@@ -483,16 +1354,16 @@ fn authcheck(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
 }
 
 pub fn assert_new_schedules(
-    config: &Config,
+    existing_schedule: &[(u64, u64, Uint128)],
     state: &State,
     distribution_schedule: Vec<(u64, u64, Uint128)>,
 ) -> Result<(), ContractError> {
-    if distribution_schedule.len() < config.distribution_schedule.len() {
+    if distribution_schedule.len() < existing_schedule.len() {
         return Err(ContractError::NotIncludeAllDistributionSchedule {});
     }
 
     let mut existing_counts: BTreeMap<(u64, u64, Uint128), u32> = BTreeMap::new();
-    for schedule in config.distribution_schedule.clone() {
+    for schedule in existing_schedule.iter().cloned() {
         let counter = existing_counts.entry(schedule).or_insert(0);
         *counter += 1;
     }
@@ -522,46 +1393,90 @@ pub fn assert_new_schedules(
     Ok(())
 }
 
-// compute distributed rewards and update global reward index
+// compute distributed rewards and update global reward indexes
 pub fn compute_reward(config: &Config, state: &mut State, block_time: u64) {
-    if state.total_bond_amount.is_zero() {
+    state
+        .global_reward_indexes
+        .resize(config.reward_tokens.len(), Decimal::zero());
+    state
+        .distributed_rewards
+        .resize(config.reward_tokens.len(), Uint128::zero());
+    if state.total_weight.is_zero() {
         state.last_distributed = block_time;
         return;
     }
 
+    for ((reward_token, global_reward_index), distributed_reward) in config
+        .reward_tokens
+        .iter()
+        .zip(state.global_reward_indexes.iter_mut())
+        .zip(state.distributed_rewards.iter_mut())
+    {
+        let distributed_amount = get_distributed_amount(
+            &reward_token.distribution_schedule,
+            state.last_distributed,
+            block_time,
+        );
+        *global_reward_index =
+            *global_reward_index + Decimal::from_ratio(distributed_amount, state.total_weight);
+        *distributed_reward += distributed_amount;
+    }
+    state.last_distributed = block_time;
+}
+
+// amount of a schedule distributed between last_distributed and block_time
+fn get_distributed_amount(
+    distribution_schedule: &[(u64, u64, Uint128)],
+    last_distributed: u64,
+    block_time: u64,
+) -> Uint128 {
     let mut distributed_amount: Uint128 = Uint128::zero();
-    for s in config.distribution_schedule.iter() {
-        if s.0 > block_time || s.1 < state.last_distributed {
+    for s in distribution_schedule.iter() {
+        if s.0 > block_time || s.1 < last_distributed {
             continue;
         }
 
         // min(s.1, block_time) - max(s.0, last_distributed)
-        let passed_time =
-            std::cmp::min(s.1, block_time) - std::cmp::max(s.0, state.last_distributed);
+        let passed_time = std::cmp::min(s.1, block_time) - std::cmp::max(s.0, last_distributed);
 
         let time = s.1 - s.0;
         let distribution_amount_per_second: Decimal = Decimal::from_ratio(s.2, time);
         distributed_amount += distribution_amount_per_second * Uint128::from(passed_time as u128);
     }
-
-    state.last_distributed = block_time;
-    state.global_reward_index = state.global_reward_index
-        + Decimal::from_ratio(distributed_amount, state.total_bond_amount);
+    distributed_amount
 }
 
-// withdraw reward to pending reward
+// withdraw rewards to pending rewards, reward tokens added since the staker's
+// last update start from a zero index
 pub fn compute_staker_reward(state: &State, staker_info: &mut StakerInfo) -> StdResult<()> {
-    let pending_reward = (staker_info.bond_amount * state.global_reward_index)
-        .checked_sub(staker_info.bond_amount * staker_info.reward_index)?;
-
-    staker_info.reward_index = state.global_reward_index;
-    staker_info.pending_reward += pending_reward;
+    let reward_count = state.global_reward_indexes.len();
+    staker_info
+        .reward_indexes
+        .resize(reward_count, Decimal::zero());
+    staker_info
+        .pending_rewards
+        .resize(reward_count, Uint128::zero());
+
+    let weight = staker_info.weight;
+    for ((global_reward_index, reward_index), pending_reward) in state
+        .global_reward_indexes
+        .iter()
+        .zip(staker_info.reward_indexes.iter_mut())
+        .zip(staker_info.pending_rewards.iter_mut())
+    {
+        *pending_reward += (weight * *global_reward_index).checked_sub(weight * *reward_index)?;
+        *reward_index = *global_reward_index;
+    }
+    staker_info.pending_penalty_reward += (weight * state.penalty_reward_index)
+        .checked_sub(weight * staker_info.penalty_reward_index)?;
+    staker_info.penalty_reward_index = state.penalty_reward_index;
     Ok(())
 }
 
 fn increase_bond_amount(state: &mut State, staker_info: &mut StakerInfo, amount: Uint128) {
     state.total_bond_amount += amount;
     staker_info.bond_amount += amount;
+    update_weight(state, staker_info);
 }
 
 fn decrease_bond_amount(
@@ -571,9 +1486,21 @@ fn decrease_bond_amount(
 ) -> StdResult<()> {
     state.total_bond_amount = state.total_bond_amount.checked_sub(amount)?;
     staker_info.bond_amount = staker_info.bond_amount.checked_sub(amount)?;
+    update_weight(state, staker_info);
     Ok(())
 }
 
+// recompute the staker weight after a bond or lock tier change
+fn update_weight(state: &mut State, staker_info: &mut StakerInfo) {
+    let multiplier = staker_info
+        .lock_tier
+        .as_ref()
+        .map_or(Decimal::one(), |tier| tier.multiplier);
+    let weight = staker_info.bond_amount * multiplier;
+    state.total_weight = state.total_weight - staker_info.weight + weight;
+    staker_info.weight = weight;
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let version = get_contract_version(deps.storage)?;
@@ -582,10 +1509,194 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
             previous_contract: version.contract,
         });
     }
-    if version.version != CONTRACT_VERSION {
+    if version.version == LEGACY_CONTRACT_VERSION {
+        migrate_legacy_state(deps.storage)?;
+    } else if version.version != CONTRACT_VERSION {
         return Err(ContractError::CannotMigrate {
             previous_contract: version.contract,
         });
     }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::default())
 }
+
+// converts the single reward config and state of 0.1.0, the entries are converted
+// in batches by MigrateLegacy
+fn migrate_legacy_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config = LEGACY_CONFIG.load(storage)?;
+    let lock_duration = legacy_config.lock_duration;
+    // the existing schedule was funded up front
+    let funded_amount = legacy_config
+        .distribution_schedule
+        .iter()
+        .map(|item| item.2)
+        .sum();
+    CONFIG.save(
+        storage,
+        &Config {
+            staking_token: Denom::Cw20(Addr::unchecked(legacy_config.lp_token_contract)),
+            reward_tokens: vec![RewardToken {
+                denom: legacy_config.reward_token,
+                distribution_schedule: legacy_config.distribution_schedule,
+            }],
+            admin: legacy_config.admin,
+            lock_duration,
+            lock_tiers: vec![],
+            // stakers couldn't skip the lock duration before, the admin can lower it
+            instant_unbond_penalty: Decimal::one(),
+            penalty_treasury: None,
+            pool_contract: None,
+        },
+    )?;
+
+    let legacy_state = LEGACY_STATE.load(storage)?;
+    STATE.save(
+        storage,
+        &State {
+            last_distributed: legacy_state.last_distributed,
+            total_bond_amount: legacy_state.total_bond_amount,
+            total_weight: legacy_state.total_bond_amount,
+            global_reward_indexes: vec![legacy_state.global_reward_index],
+            funded_rewards: vec![funded_amount],
+            // the weight was the bond, this is the amount accrued at the current bond
+            distributed_rewards: vec![
+                legacy_state.global_reward_index * legacy_state.total_bond_amount,
+            ],
+            penalty_reward_index: Decimal::zero(),
+        },
+    )?;
+
+    LEGACY_MIGRATION.save(storage, &LegacyMigration::StakerInfo { last_key: None })?;
+    Ok(())
+}
+
+pub fn migrate_legacy(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let mut migration = LEGACY_MIGRATION
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NothingToMigrate {})?;
+    let limit = limit
+        .unwrap_or(DEFAULT_MIGRATE_LIMIT)
+        .min(MAX_MIGRATE_LIMIT) as usize;
+    let lock_duration = CONFIG.load(deps.storage)?.lock_duration;
+
+    let mut migrated = 0;
+    let mut done = false;
+    while migrated < limit && !done {
+        let remaining = limit - migrated;
+        // the legacy entries are removed first so the indexed maps don't load them
+        let (count, next) = match migration.clone() {
+            LegacyMigration::StakerInfo { last_key } => {
+                let stakers = LEGACY_STAKER_INFO
+                    .range(
+                        deps.storage,
+                        last_key.map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(remaining)
+                    .collect::<StdResult<Vec<_>>>()?;
+                let last_key = stakers.last().map(|(key, _)| key.clone());
+                for (key, staker_info) in &stakers {
+                    LEGACY_STAKER_INFO.remove(deps.storage, key.clone());
+                    staker_info_storage().save(
+                        deps.storage,
+                        key.clone(),
+                        &StakerInfo {
+                            address: staker_info.address.clone(),
+                            reward_indexes: vec![staker_info.reward_index],
+                            bond_amount: staker_info.bond_amount,
+                            weight: staker_info.bond_amount,
+                            lock_tier: None,
+                            pending_rewards: vec![staker_info.pending_reward],
+                            penalty_reward_index: Decimal::zero(),
+                            pending_penalty_reward: Uint128::zero(),
+                        },
+                    )?;
+                }
+                let next = if stakers.len() < remaining {
+                    Some(LegacyMigration::UserEarnedInfo { last_key: None })
+                } else {
+                    Some(LegacyMigration::StakerInfo { last_key })
+                };
+                (stakers.len(), next)
+            }
+            LegacyMigration::UserEarnedInfo { last_key } => {
+                let user_earned_infos = LEGACY_USER_EARNED_INFO
+                    .range(
+                        deps.storage,
+                        last_key.map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(remaining)
+                    .collect::<StdResult<Vec<_>>>()?;
+                let last_key = user_earned_infos.last().map(|(key, _)| key.clone());
+                for (key, user_earned_info) in &user_earned_infos {
+                    LEGACY_USER_EARNED_INFO.remove(deps.storage, key.clone());
+                    user_earned_info_storage().save(
+                        deps.storage,
+                        key.clone(),
+                        &UserEarnedInfo {
+                            address: user_earned_info.address.clone(),
+                            total_earned: vec![user_earned_info.total_earned],
+                        },
+                    )?;
+                }
+                let next = if user_earned_infos.len() < remaining {
+                    Some(LegacyMigration::UnbondingInfo { last_key: None })
+                } else {
+                    Some(LegacyMigration::UserEarnedInfo { last_key })
+                };
+                (user_earned_infos.len(), next)
+            }
+            LegacyMigration::UnbondingInfo { last_key } => {
+                let unbonding_infos = LEGACY_UNBONDING_INFO
+                    .range(
+                        deps.storage,
+                        last_key.map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(remaining)
+                    .collect::<StdResult<Vec<_>>>()?;
+                let last_key = unbonding_infos.last().map(|(key, _)| key.clone());
+                for (key, unbonding_info) in &unbonding_infos {
+                    LEGACY_UNBONDING_INFO.remove(deps.storage, key.clone());
+                    unbonding_info_storage().save(
+                        deps.storage,
+                        key.clone(),
+                        &UnbondingInfo {
+                            address: unbonding_info.address.clone(),
+                            time: unbonding_info.time,
+                            amount: unbonding_info.amount,
+                            lock_duration,
+                        },
+                    )?;
+                }
+                let next = if unbonding_infos.len() < remaining {
+                    None
+                } else {
+                    Some(LegacyMigration::UnbondingInfo { last_key })
+                };
+                (unbonding_infos.len(), next)
+            }
+        };
+        migrated += count;
+        match next {
+            Some(next) => migration = next,
+            None => done = true,
+        }
+    }
+
+    if done {
+        LEGACY_MIGRATION.remove(deps.storage);
+    } else {
+        LEGACY_MIGRATION.save(deps.storage, &migration)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate_legacy".to_string()),
+        ("migrated", migrated.to_string()),
+        ("done", done.to_string()),
+    ]))
+}
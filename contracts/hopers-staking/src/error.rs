@@ -36,6 +36,66 @@ pub enum ContractError {
     #[error("Cannot unbond more than bond amount")]
     ExceedBondAmount {},
 
+    #[error("Cannot rebond more than unbonding amount")]
+    ExceedUnbondingAmount {},
+
+    #[error("Distribution schedule exceeds the funded rewards")]
+    InsufficientRewardFunds {},
+
+    #[error("Reward token not found")]
+    RewardTokenNotFound {},
+
+    #[error("Reward token is already distributed")]
+    DuplicateRewardToken {},
+
+    #[error("Penalty can not be more than 100%")]
+    InvalidPenalty {},
+
+    #[error("Approval expiration is already expired")]
+    InvalidExpiration {},
+
+    #[error("Send only the native staking denom")]
+    InvalidFunds {},
+
+    #[error("Lock tier not found")]
+    LockTierNotFound {},
+
+    #[error("Cannot move to a shorter lock tier")]
+    LockTierDowngrade {},
+
+    #[error("Lock tiers need distinct durations and multipliers of at least 1")]
+    InvalidLockTiers {},
+
+    #[error("Pool contract is not configured")]
+    PoolNotConfigured {},
+
+    #[error("Pool does not trade the staked LP token")]
+    PoolLpTokenMismatch {},
+
+    #[error("No reward token is traded by the pool")]
+    RewardNotInPool {},
+
+    #[error("There is no reward to compound")]
+    NothingToCompound {},
+
+    #[error("Missing {key} in the pool reply")]
+    InvalidPoolReply { key: String },
+
+    #[error("Cannot change the staking token while tokens are bonded or unbonding")]
+    StakingTokenInUse {},
+
+    #[error("Operators can only send funds to the staker")]
+    OperatorRecipient {},
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("The 0.1.0 state is still being migrated")]
+    LegacyMigrationPending {},
+
+    #[error("There is no 0.1.0 state to migrate")]
+    NothingToMigrate {},
+
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 }
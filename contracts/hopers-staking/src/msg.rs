@@ -1,51 +1,135 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_schema::QueryResponses;
 
-use cosmwasm_std::{Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw20::{Cw20ReceiveMsg, Expiration};
+use serde::{Deserialize, Deserializer};
 
-use crate::state::{Denom, StakerInfo, UnbondingInfo};
+use crate::state::{Denom, LockTier, RewardToken, StakerInfo, UnbondingInfo};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub lp_token_contract: String,
-    pub reward_token: Denom,
-    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    /// Also accepts the cw20 address under the former `lp_token_contract` name
+    #[serde(alias = "lp_token_contract", deserialize_with = "deserialize_staking_token")]
+    pub staking_token: Denom,
+    pub reward_tokens: Vec<RewardToken>,
     pub lock_duration: u64,
+    pub lock_tiers: Vec<LockTier>,
+    pub instant_unbond_penalty: Decimal,
+    pub penalty_treasury: Option<String>,
+    pub pool_contract: Option<String>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
+    /// Bonds the funds sent when the staking token is a native denom
+    BondNative {
+        lock_duration: Option<u64>,
+    },
     Unbond {
         amount: Uint128,
     },
-    /// Withdraw pending rewards
-    Withdraw {},
-    Redeem {},
+    /// Moves `amount` of the unbonding entry created at `time` back into the bond,
+    /// the latest entries are used first when `time` is not given
+    Rebond {
+        time: Option<u64>,
+        amount: Uint128,
+    },
+    /// Unbonds without waiting for the lock duration, `instant_unbond_penalty` of the
+    /// amount goes to the penalty treasury or is paid to the remaining stakers as a
+    /// reward in the staking token. Without either it goes to the admin. The penalty
+    /// of a lock tier is scaled by its duration over the default lock duration
+    InstantUnbond {
+        amount: Uint128,
+    },
+    /// Withdraw pending rewards of every reward token and the share of instant unbond
+    /// penalties. `owner` is the staker an approved operator withdraws for, rewards
+    /// go to `recipient` or the staker. An operator can only withdraw to the staker
+    Withdraw {
+        owner: Option<String>,
+        recipient: Option<String>,
+    },
+    /// Swaps half of the pending reward traded by the pool, adds liquidity with
+    /// both halves and bonds the minted LP tokens. The swap must return at least
+    /// `min_output` and the liquidity at least `min_lp_out`
+    Compound {
+        owner: Option<String>,
+        min_output: Uint128,
+        min_lp_out: Uint128,
+    },
+    /// Redeems the unlocked unbondings of `owner`, an operator can only redeem to the
+    /// owner itself
+    Redeem {
+        owner: Option<String>,
+        recipient: Option<String>,
+    },
+    /// Allows `operator` to withdraw, compound and redeem for the sender
+    ApproveOperator {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    RevokeOperator {
+        operator: String,
+    },
+    /// Converts up to `limit` stakers, earnings and unbondings of the 0.1.0 layout,
+    /// other messages are rejected until every entry is converted
+    MigrateLegacy {
+        limit: Option<u32>,
+    },
     /// Owner operation to stop distribution on current staking contract
     /// and send remaining tokens to the new contract
     MigrateStaking {
         new_staking_contract: String,
     },
+    /// Replaces the distribution schedule of a reward token
     UpdateConfig {
+        reward_token: Denom,
+        distribution_schedule: Vec<(u64, u64, Uint128)>,
+    },
+    /// Funds the distribution schedules of native reward tokens with the sent funds
+    FundRewards {},
+    /// Starts distributing another reward token, native funds sent fund its schedule
+    AddRewardToken {
+        reward_token: Denom,
         distribution_schedule: Vec<(u64, u64, Uint128)>,
     },
     UpdateAdmin {
         admin: String,
     },
+    /// Only allowed while nothing is bonded or unbonding
+    UpdateStakingToken {
+        staking_token: Denom,
+    },
+    /// Former name of `UpdateStakingToken` for a cw20 staking token
     UpdateTokenContract {
         lp_token_contract: String,
-        reward_token: Denom,
     },
     UpdateLockDuration {
         lock_duration: u64,
     },
+    UpdatePoolContract {
+        pool_contract: Option<String>,
+    },
+    /// Replaces the lock tiers, stakers keep the tier they bonded with
+    UpdateLockTiers {
+        lock_tiers: Vec<LockTier>,
+    },
+    UpdateInstantUnbondConfig {
+        penalty: Decimal,
+        treasury: Option<String>,
+    },
 }
 
 #[cw_serde]
 pub enum Cw20HookMsg {
-    Bond {},
+    /// `lock_duration` selects a lock tier for the whole bond of the staker,
+    /// the current tier is kept when it is not given
+    Bond {
+        lock_duration: Option<u64>,
+    },
+    /// Funds the distribution schedule of the cw20 reward token
+    FundRewards {},
 }
 
 /// migrate struct for distribution schedule
@@ -77,16 +161,36 @@ pub enum QueryMsg {
         start_after: Option<u64>,
         limit: Option<u32>,
     },
+    /// Funded, scheduled and distributed amounts of every reward token
+    #[returns(RewardBalanceResponse)]
+    RewardBalance {},
+    /// Penalty and the amount received on an instant unbond of `amount` by `staker`
+    #[returns(InstantUnbondPenaltyResponse)]
+    InstantUnbondPenalty {
+        staker: String,
+        amount: Uint128,
+    },
+    /// Operators of the staker whose approval has not expired
+    #[returns(OperatorsResponse)]
+    Operators {
+        staker: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct ConfigResponse {
-    pub lp_token_contract: String,
-    pub reward_token: Denom,
-    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    #[serde(alias = "lp_token_contract", deserialize_with = "deserialize_staking_token")]
+    pub staking_token: Denom,
+    pub reward_tokens: Vec<RewardToken>,
     pub admin: String,
     pub lock_duration: u64,
+    pub lock_tiers: Vec<LockTier>,
+    pub instant_unbond_penalty: Decimal,
+    pub penalty_treasury: Option<String>,
+    pub pool_contract: Option<String>,
 }
 
 // We define a custom struct for each query response
@@ -94,17 +198,24 @@ pub struct ConfigResponse {
 pub struct StateResponse {
     pub last_distributed: u64,
     pub total_bond_amount: Uint128,
-    pub global_reward_index: Decimal,
+    pub total_weight: Uint128,
+    /// In the order of the reward tokens of the config
+    pub global_reward_indexes: Vec<Decimal>,
+    pub penalty_reward_index: Decimal,
 }
 
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct StakerInfoResponse {
     pub staker: String,
-    pub reward_index: Decimal,
+    pub reward_indexes: Vec<Decimal>,
     pub bond_amount: Uint128,
-    pub pending_reward: Uint128,
-    pub total_earned: Uint128,
+    pub weight: Uint128,
+    pub lock_tier: Option<LockTier>,
+    pub pending_rewards: Vec<Uint128>,
+    /// Share of instant unbond penalties, in the staking token
+    pub pending_penalty_reward: Uint128,
+    pub total_earned: Vec<Uint128>,
 }
 
 #[cw_serde]
@@ -117,3 +228,93 @@ pub struct UnbondingInfoResponse {
     pub unbonding_info: Vec<UnbondingInfo>,
     pub crr_time: u64,
 }
+
+#[cw_serde]
+pub struct RewardBalance {
+    pub denom: Denom,
+    pub funded_amount: Uint128,
+    /// Total amount of the distribution schedule
+    pub scheduled_amount: Uint128,
+    /// Amount accrued to the stakers so far, periods without any bond are left out
+    pub distributed_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct RewardBalanceResponse {
+    pub reward_balances: Vec<RewardBalance>,
+}
+
+#[cw_serde]
+pub struct InstantUnbondPenaltyResponse {
+    pub penalty: Uint128,
+    pub amount_after_penalty: Uint128,
+}
+
+#[cw_serde]
+pub struct OperatorInfo {
+    pub operator: String,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct OperatorsResponse {
+    pub operators: Vec<OperatorInfo>,
+}
+
+#[cw_serde]
+pub enum TokenSelect {
+    Token1,
+    Token2,
+}
+
+/// Messages of the hopers-swap pool used for compounding, only the fields every
+/// pool accepts are sent
+#[cw_serde]
+pub enum PoolExecuteMsg {
+    AddLiquidity {
+        token1_amount: Uint128,
+        min_liquidity: Uint128,
+        max_token2: Uint128,
+        expiration: Option<Expiration>,
+    },
+    Swap {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        min_output: Uint128,
+        expiration: Option<Expiration>,
+    },
+}
+
+#[cw_serde]
+pub enum PoolQueryMsg {
+    Info {},
+}
+
+#[cw_serde]
+pub struct PoolInfoResponse {
+    pub token1_reserve: Uint128,
+    pub token1_denom: Denom,
+    pub token2_reserve: Uint128,
+    pub token2_denom: Denom,
+    pub lp_token_supply: Uint128,
+    pub lp_token_address: String,
+}
+
+/// Reads the staking token as a `Denom` or as the cw20 address the former
+/// `lp_token_contract` field held
+fn deserialize_staking_token<'de, D>(deserializer: D) -> Result<Denom, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StakingToken {
+        Denom(Denom),
+        LpTokenContract(String),
+    }
+
+    Ok(match StakingToken::deserialize(deserializer)? {
+        StakingToken::Denom(denom) => denom,
+        StakingToken::LpTokenContract(address) => Denom::Cw20(Addr::unchecked(address)),
+    })
+}
@@ -1,11 +1,12 @@
-use crate::contract::{compute_reward, compute_staker_reward};
+use crate::contract::{compute_reward, compute_staker_reward, get_instant_unbond_penalty};
 use crate::msg::{
-    ConfigResponse, QueryMsg, StakerInfoResponse, StakersListResponse, StateResponse,
+    ConfigResponse, InstantUnbondPenaltyResponse, OperatorInfo, OperatorsResponse, QueryMsg,
+    RewardBalance, RewardBalanceResponse, StakerInfoResponse, StakersListResponse, StateResponse,
     UnbondingInfoResponse,
 };
 use crate::state::{
     staker_info_key, staker_info_storage, unbonding_info_storage, user_earned_info_key,
-    user_earned_info_storage, UnbondingInfo, CONFIG, STATE,
+    user_earned_info_storage, UnbondingInfo, CONFIG, OPERATORS, STATE,
 };
 use cosmwasm_std::{entry_point, to_binary, Binary, Decimal, Deps, Env, Order, StdResult, Uint128};
 use cw_storage_plus::Bound;
@@ -34,17 +35,29 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::RewardBalance {} => to_binary(&query_reward_balance(deps, env)?),
+        QueryMsg::InstantUnbondPenalty { staker, amount } => {
+            to_binary(&query_instant_unbond_penalty(deps, staker, amount)?)
+        }
+        QueryMsg::Operators {
+            staker,
+            start_after,
+            limit,
+        } => to_binary(&query_operators(deps, env, staker, start_after, limit)?),
     }
 }
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse {
-        lp_token_contract: config.lp_token_contract,
-        reward_token: config.reward_token,
-        distribution_schedule: config.distribution_schedule,
+        staking_token: config.staking_token,
+        reward_tokens: config.reward_tokens,
         admin: config.admin,
         lock_duration: config.lock_duration,
+        lock_tiers: config.lock_tiers,
+        instant_unbond_penalty: config.instant_unbond_penalty,
+        penalty_treasury: config.penalty_treasury,
+        pool_contract: config.pool_contract,
     })
 }
 
@@ -58,12 +71,15 @@ pub fn query_state(deps: Deps, block_time: Option<u64>) -> StdResult<StateRespon
     Ok(StateResponse {
         last_distributed: state.last_distributed,
         total_bond_amount: state.total_bond_amount,
-        global_reward_index: state.global_reward_index,
+        total_weight: state.total_weight,
+        global_reward_indexes: state.global_reward_indexes,
+        penalty_reward_index: state.penalty_reward_index,
     })
 }
 
 pub fn query_staker_info(deps: Deps, env: Env, staker: String) -> StdResult<StakerInfoResponse> {
     let block_time = Some(env.block.time.seconds());
+    let reward_count = CONFIG.load(deps.storage)?.reward_tokens.len();
     let staker_info_key = staker_info_key(&staker);
     match staker_info_storage().may_load(deps.storage, staker_info_key)? {
         Some(some_staker_info) => {
@@ -76,7 +92,7 @@ pub fn query_staker_info(deps: Deps, env: Env, staker: String) -> StdResult<Stak
                 compute_staker_reward(&state, &mut staker_info)?;
             }
 
-            let total_earned: Uint128;
+            let mut total_earned: Vec<Uint128>;
 
             let user_earned_info_key = user_earned_info_key(&staker);
             match user_earned_info_storage().may_load(deps.storage, user_earned_info_key)? {
@@ -84,24 +100,31 @@ pub fn query_staker_info(deps: Deps, env: Env, staker: String) -> StdResult<Stak
                     total_earned = user_earned_info.total_earned;
                 }
                 None => {
-                    total_earned = Uint128::zero();
+                    total_earned = vec![];
                 }
             }
+            total_earned.resize(reward_count, Uint128::zero());
 
             Ok(StakerInfoResponse {
                 staker,
-                reward_index: staker_info.reward_index,
+                reward_indexes: staker_info.reward_indexes,
                 bond_amount: staker_info.bond_amount,
-                pending_reward: staker_info.pending_reward,
+                weight: staker_info.weight,
+                lock_tier: staker_info.lock_tier,
+                pending_rewards: staker_info.pending_rewards,
+                pending_penalty_reward: staker_info.pending_penalty_reward,
                 total_earned,
             })
         }
         None => Ok(StakerInfoResponse {
             staker,
-            reward_index: Decimal::zero(),
+            reward_indexes: vec![Decimal::zero(); reward_count],
             bond_amount: Uint128::zero(),
-            pending_reward: Uint128::zero(),
-            total_earned: Uint128::zero(),
+            weight: Uint128::zero(),
+            lock_tier: None,
+            pending_rewards: vec![Uint128::zero(); reward_count],
+            pending_penalty_reward: Uint128::zero(),
+            total_earned: vec![Uint128::zero(); reward_count],
         }),
     }
 }
@@ -168,3 +191,81 @@ pub fn query_all_unbonding_info(
 
     Ok(unbonding_info)
 }
+
+pub fn query_reward_balance(deps: Deps, env: Env) -> StdResult<RewardBalanceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    compute_reward(&config, &mut state, env.block.time.seconds());
+
+    let reward_balances = config
+        .reward_tokens
+        .into_iter()
+        .zip(state.funded_rewards)
+        .zip(state.distributed_rewards)
+        .map(
+            |((reward_token, funded_amount), distributed_amount)| RewardBalance {
+                scheduled_amount: reward_token
+                    .distribution_schedule
+                    .iter()
+                    .map(|item| item.2)
+                    .sum(),
+                denom: reward_token.denom,
+                funded_amount,
+                distributed_amount,
+            },
+        )
+        .collect();
+    Ok(RewardBalanceResponse { reward_balances })
+}
+
+pub fn query_instant_unbond_penalty(
+    deps: Deps,
+    staker: String,
+    amount: Uint128,
+) -> StdResult<InstantUnbondPenaltyResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let lock_tier = staker_info_storage()
+        .may_load(deps.storage, staker_info_key(&staker))?
+        .and_then(|staker_info| staker_info.lock_tier);
+    let penalty = get_instant_unbond_penalty(&config, lock_tier.as_ref(), amount);
+    Ok(InstantUnbondPenaltyResponse {
+        penalty,
+        amount_after_penalty: amount - penalty,
+    })
+}
+
+pub fn query_operators(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let staker = deps.api.addr_validate(&staker)?;
+    let start_after = start_after
+        .map(|operator| deps.api.addr_validate(&operator))
+        .transpose()?;
+
+    let operators = OPERATORS
+        .prefix(&staker)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, expires)| !expires.is_expired(&env.block))
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|(operator, expires)| OperatorInfo {
+                operator: operator.into_string(),
+                expires,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(OperatorsResponse { operators })
+}
@@ -1,10 +1,13 @@
 use cosmwasm_std::{Addr, Decimal, Uint128};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+use cw20::Expiration;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const CONFIG: Item<Config> = Item::new("config_config");
 pub const STATE: Item<State> = Item::new("config_state");
+/// (staker, operator) -> expiration of the approval
+pub const OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new("operators");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -14,27 +17,76 @@ pub enum Denom {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct Config {
-    pub lp_token_contract: String,
-    pub reward_token: Denom,
+pub struct RewardToken {
+    pub denom: Denom,
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+}
+
+/// Stakers in a lock tier unbond over `duration` seconds instead of the default
+/// lock duration, their bond is weighted by `multiplier`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockTier {
+    pub duration: u64,
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// Cw20 LP token or native denom that is bonded
+    pub staking_token: Denom,
+    pub reward_tokens: Vec<RewardToken>,
     pub admin: String,
     pub lock_duration: u64,
+    pub lock_tiers: Vec<LockTier>,
+    /// Share of the amount kept on an instant unbond
+    pub instant_unbond_penalty: Decimal,
+    /// Receives instant unbond penalties, they are distributed to the remaining
+    /// stakers when not set
+    pub penalty_treasury: Option<String>,
+    /// hopers-swap pool of the LP token, rewards are compounded through it
+    pub pool_contract: Option<String>,
 }
 
+/// Reward indexes and amounts are kept in the order of `Config::reward_tokens`
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub last_distributed: u64,
     pub total_bond_amount: Uint128,
-    pub global_reward_index: Decimal,
+    /// Sum of the weights of all stakers, rewards are split by weight
+    pub total_weight: Uint128,
+    pub global_reward_indexes: Vec<Decimal>,
+    /// Reward amounts deposited for the distribution schedules
+    pub funded_rewards: Vec<Uint128>,
+    /// Reward amounts accrued to the stakers, nothing accrues while nothing is bonded
+    pub distributed_rewards: Vec<Uint128>,
+    /// Instant unbond penalties paid to the stakers per weight, in the staking token
+    pub penalty_reward_index: Decimal,
+}
+
+/// Compound in progress, kept between the swap and the add liquidity replies
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CompoundState {
+    pub staker: String,
+    pub reward_is_token1: bool,
+    /// Reward amount that was not swapped
+    pub remaining_amount: Uint128,
+    pub min_lp_out: Uint128,
 }
 
+pub const COMPOUND_STATE: Item<CompoundState> = Item::new("compound_state");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StakerInfo {
     pub address: String,
-    pub reward_index: Decimal,
+    pub reward_indexes: Vec<Decimal>,
     pub bond_amount: Uint128,
-    pub pending_reward: Uint128,
+    /// Bond amount times the multiplier of the lock tier
+    pub weight: Uint128,
+    pub lock_tier: Option<LockTier>,
+    pub pending_rewards: Vec<Uint128>,
+    pub penalty_reward_index: Decimal,
+    /// Share of instant unbond penalties, in the staking token
+    pub pending_penalty_reward: Uint128,
 }
 
 pub type StakerInfoKey<'a> = String;
@@ -69,7 +121,7 @@ pub fn staker_info_storage<'a>(
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UserEarnedInfo {
     pub address: String,
-    pub total_earned: Uint128,
+    pub total_earned: Vec<Uint128>,
 }
 
 pub type UserEarnedInfoKey<'a> = String;
@@ -106,6 +158,8 @@ pub struct UnbondingInfo {
     pub address: String,
     pub time: u64,
     pub amount: Uint128,
+    /// Seconds after `time` the amount can be redeemed
+    pub lock_duration: u64,
 }
 
 pub type UnbondingInfoKey<'a> = (String, u64);
@@ -136,3 +190,61 @@ pub fn unbonding_info_storage<'a>(
     };
     IndexedMap::new("unbonding_info", indexes)
 }
+
+/// Config of 0.1.0, a single cw20 LP token staked for a single reward token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub lp_token_contract: String,
+    pub reward_token: Denom,
+    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub admin: String,
+    pub lock_duration: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
+    pub last_distributed: u64,
+    pub total_bond_amount: Uint128,
+    pub global_reward_index: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyStakerInfo {
+    pub address: String,
+    pub reward_index: Decimal,
+    pub bond_amount: Uint128,
+    pub pending_reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyUserEarnedInfo {
+    pub address: String,
+    pub total_earned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyUnbondingInfo {
+    pub address: String,
+    pub time: u64,
+    pub amount: Uint128,
+}
+
+/// Progress of converting the 0.1.0 entries, the entries after `last_key` of the
+/// current map are still in the legacy layout
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LegacyMigration {
+    StakerInfo { last_key: Option<String> },
+    UserEarnedInfo { last_key: Option<String> },
+    UnbondingInfo { last_key: Option<(String, u64)> },
+}
+
+pub const LEGACY_MIGRATION: Item<LegacyMigration> = Item::new("legacy_migration");
+
+// The 0.1.0 layout is read from the same keys
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config_config");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("config_state");
+pub const LEGACY_STAKER_INFO: Map<String, LegacyStakerInfo> = Map::new("staker_info");
+pub const LEGACY_USER_EARNED_INFO: Map<String, LegacyUserEarnedInfo> = Map::new("user_earned_info");
+pub const LEGACY_UNBONDING_INFO: Map<(String, u64), LegacyUnbondingInfo> =
+    Map::new("unbonding_info");
@@ -1,25 +1,46 @@
 #[cfg(test)]
-use crate::contract::{execute, instantiate};
-use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
-use crate::query::{query_all_unbonding_info, query_staker_info, query_unbonding_info};
-use crate::state::Denom;
+use crate::contract::{execute, instantiate, migrate, reply};
+use crate::error::ContractError;
+use crate::msg::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, OperatorInfo, PoolExecuteMsg,
+    PoolInfoResponse, RewardBalance, TokenSelect,
+};
+use crate::query::{
+    query_all_unbonding_info, query_instant_unbond_penalty, query_operators, query_reward_balance,
+    query_staker_info, query_unbonding_info,
+};
+use crate::state::{
+    Denom, LegacyConfig, LegacyStakerInfo, LegacyState, LegacyUnbondingInfo, LegacyUserEarnedInfo,
+    LockTier, RewardToken, CONFIG, LEGACY_CONFIG, LEGACY_STAKER_INFO, LEGACY_STATE,
+    LEGACY_UNBONDING_INFO, LEGACY_USER_EARNED_INFO, STATE,
+};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{to_binary, BankMsg, Coin, CosmosMsg, DepsMut, Env, Uint128, WasmMsg};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal,
+    DepsMut, Env, Event, Reply, SubMsg, SubMsgResponse, SubMsgResult, SystemResult, Uint128,
+    WasmMsg, WasmQuery,
+};
 
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
 
 fn setup_contract(deps: DepsMut, env: Env) {
     let instantiate_msg = InstantiateMsg {
-        lp_token_contract: "lp_token_contract".to_string(),
-        reward_token: Denom::Native("ujuno".to_string()),
-        distribution_schedule: vec![(
-            env.block.time.seconds(),
-            env.block.time.seconds() + 86400,
-            Uint128::new(100000000),
-        )],
+        staking_token: Denom::Cw20(Addr::unchecked("lp_token_contract")),
+        reward_tokens: vec![RewardToken {
+            denom: Denom::Native("ujuno".to_string()),
+            distribution_schedule: vec![(
+                env.block.time.seconds(),
+                env.block.time.seconds() + 86400,
+                Uint128::new(100000000),
+            )],
+        }],
         lock_duration: 3600,
+        lock_tiers: vec![],
+        instant_unbond_penalty: Decimal::zero(),
+        penalty_treasury: None,
+        pool_contract: None,
     };
-    let info = mock_info("owner", &[]);
+    let info = mock_info("owner", &coins(100000000, "ujuno"));
     let res = instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
     assert_eq!(res.messages.len(), 0);
 }
@@ -31,7 +52,9 @@ fn test_earned() {
     setup_contract(deps.as_mut(), env.clone());
 
     let info = mock_info("lp_token_contract", &[]);
-    let hook_msg = Cw20HookMsg::Bond {};
+    let hook_msg = Cw20HookMsg::Bond {
+        lock_duration: None,
+    };
     let cw20_rcv_msg = Cw20ReceiveMsg {
         sender: "user1".to_string(),
         amount: Uint128::new(1000),
@@ -41,7 +64,9 @@ fn test_earned() {
     execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let info = mock_info("lp_token_contract", &[]);
-    let hook_msg = Cw20HookMsg::Bond {};
+    let hook_msg = Cw20HookMsg::Bond {
+        lock_duration: None,
+    };
     let cw20_rcv_msg = Cw20ReceiveMsg {
         sender: "user2".to_string(),
         amount: Uint128::new(500),
@@ -58,7 +83,10 @@ fn test_earned() {
     println!("{:?}, {:?}", staker1_info, staker2_info);
 
     let info = mock_info("user1", &[]);
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw {
+        owner: None,
+        recipient: None,
+    };
     execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let staker1_info = query_staker_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
@@ -68,7 +96,10 @@ fn test_earned() {
 
     env.block.time = env.block.time.plus_seconds(300);
     let info = mock_info("user1", &[]);
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw {
+        owner: None,
+        recipient: None,
+    };
     execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let staker1_info = query_staker_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
@@ -84,7 +115,9 @@ fn test_unbond() {
     setup_contract(deps.as_mut(), env.clone());
 
     let info = mock_info("lp_token_contract", &[]);
-    let hook_msg = Cw20HookMsg::Bond {};
+    let hook_msg = Cw20HookMsg::Bond {
+        lock_duration: None,
+    };
     let cw20_rcv_msg = Cw20ReceiveMsg {
         sender: "user1".to_string(),
         amount: Uint128::new(1000),
@@ -94,7 +127,9 @@ fn test_unbond() {
     execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let info = mock_info("lp_token_contract", &[]);
-    let hook_msg = Cw20HookMsg::Bond {};
+    let hook_msg = Cw20HookMsg::Bond {
+        lock_duration: None,
+    };
     let cw20_rcv_msg = Cw20ReceiveMsg {
         sender: "user2".to_string(),
         amount: Uint128::new(500),
@@ -128,7 +163,10 @@ fn test_unbond() {
     env.block.time = env.block.time.plus_seconds(3650);
 
     let info = mock_info("user1", &[]);
-    let msg = ExecuteMsg::Redeem {};
+    let msg = ExecuteMsg::Redeem {
+        owner: None,
+        recipient: None,
+    };
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 1);
     assert_eq!(
@@ -156,7 +194,9 @@ fn test_withdraw() {
     setup_contract(deps.as_mut(), env.clone());
 
     let info = mock_info("lp_token_contract", &[]);
-    let hook_msg = Cw20HookMsg::Bond {};
+    let hook_msg = Cw20HookMsg::Bond {
+        lock_duration: None,
+    };
     let cw20_rcv_msg = Cw20ReceiveMsg {
         sender: "user1".to_string(),
         amount: Uint128::new(1000),
@@ -171,7 +211,10 @@ fn test_withdraw() {
     println!("staker1_info,{:?}", staker1_info);
 
     let info = mock_info("user1", &[]);
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw {
+        owner: None,
+        recipient: None,
+    };
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 1);
     // assert_eq!(
@@ -193,8 +236,1252 @@ fn test_withdraw() {
             to_address: "user1".to_string(),
             amount: vec![Coin {
                 denom: "ujuno".to_string(),
-                amount: staker1_info.pending_reward
+                amount: staker1_info.pending_rewards[0]
             }]
         })
     )
 }
+
+#[test]
+fn test_multiple_reward_tokens() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    let instantiate_msg = InstantiateMsg {
+        staking_token: Denom::Cw20(Addr::unchecked("lp_token_contract")),
+        reward_tokens: vec![
+            RewardToken {
+                denom: Denom::Native("ujuno".to_string()),
+                distribution_schedule: vec![(now, now + 86400, Uint128::new(86400000))],
+            },
+            RewardToken {
+                denom: Denom::Cw20(Addr::unchecked("reward_token_contract")),
+                distribution_schedule: vec![],
+            },
+        ],
+        lock_duration: 3600,
+        lock_tiers: vec![],
+        instant_unbond_penalty: Decimal::zero(),
+        penalty_treasury: None,
+        pool_contract: None,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(86400000, "ujuno")),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    // the cw20 schedule has to be funded first
+    let update_msg = ExecuteMsg::UpdateConfig {
+        reward_token: Denom::Cw20(Addr::unchecked("reward_token_contract")),
+        distribution_schedule: vec![(now + 1, now + 86401, Uint128::new(43200000))],
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientRewardFunds {}));
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "owner".to_string(),
+        amount: Uint128::new(43200000),
+        msg: to_binary(&Cw20HookMsg::FundRewards {}).unwrap(),
+    };
+    let info = mock_info("reward_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_msg,
+    )
+    .unwrap();
+
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(100);
+
+    // a new reward token can not distribute into the past
+    let msg = ExecuteMsg::AddRewardToken {
+        reward_token: Denom::Native("uatom".to_string()),
+        distribution_schedule: vec![(now, now + 150, Uint128::new(1000))],
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::NewScheduleAddPastDistribution {}
+    ));
+
+    let msg = ExecuteMsg::AddRewardToken {
+        reward_token: Denom::Native("uatom".to_string()),
+        distribution_schedule: vec![(now + 101, now + 151, Uint128::new(1000))],
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(999, "uatom")),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientRewardFunds {}));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(1000, "uatom")),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::AddRewardToken {
+        reward_token: Denom::Native("uatom".to_string()),
+        distribution_schedule: vec![],
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::DuplicateRewardToken {}));
+
+    env.block.time = env.block.time.plus_seconds(100);
+
+    let staker1_info = query_staker_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+    assert_eq!(
+        staker1_info.pending_rewards,
+        vec![
+            Uint128::new(200000),
+            Uint128::new(99500),
+            Uint128::new(1000)
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Withdraw {
+            owner: None,
+            recipient: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user1".to_string(),
+                amount: vec![Coin {
+                    denom: "ujuno".to_string(),
+                    amount: Uint128::new(200000)
+                }]
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "reward_token_contract".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "user1".to_string(),
+                    amount: Uint128::new(99500)
+                })
+                .unwrap(),
+                funds: vec![]
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user1".to_string(),
+                amount: vec![Coin {
+                    denom: "uatom".to_string(),
+                    amount: Uint128::new(1000)
+                }]
+            }),
+        ]
+    );
+
+    let staker1_info = query_staker_info(deps.as_ref(), env, "user1".to_string()).unwrap();
+    assert_eq!(
+        staker1_info.total_earned,
+        vec![
+            Uint128::new(200000),
+            Uint128::new(99500),
+            Uint128::new(1000)
+        ]
+    );
+}
+
+fn mock_pool_info(token1_reserve: u128, token2_reserve: u128) -> PoolInfoResponse {
+    PoolInfoResponse {
+        token1_reserve: Uint128::new(token1_reserve),
+        token1_denom: Denom::Native("ujuno".to_string()),
+        token2_reserve: Uint128::new(token2_reserve),
+        token2_denom: Denom::Cw20(Addr::unchecked("hopers_token")),
+        lp_token_supply: Uint128::new(1000000),
+        lp_token_address: "lp_token_contract".to_string(),
+    }
+}
+
+#[test]
+fn test_compound() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    setup_contract(deps.as_mut(), env.clone());
+
+    let pool_info = mock_pool_info(1000000, 2000000);
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr == "pool" => {
+            SystemResult::Ok(ContractResult::Ok(to_binary(&pool_info).unwrap()))
+        }
+        _ => panic!("unexpected query"),
+    });
+
+    let msg = ExecuteMsg::Compound {
+        owner: None,
+        min_output: Uint128::new(550000),
+        min_lp_out: Uint128::new(1),
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::PoolNotConfigured {}));
+
+    let msg = ExecuteMsg::UpdatePoolContract {
+        pool_contract: Some("pool".to_string()),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(864);
+
+    // 999999 ujuno earned over 864 seconds, half of it is swapped
+    let msg = ExecuteMsg::Compound {
+        owner: None,
+        min_output: Uint128::new(550000),
+        min_lp_out: Uint128::new(1),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: "pool".to_string(),
+                msg: to_binary(&PoolExecuteMsg::Swap {
+                    input_token: TokenSelect::Token1,
+                    input_amount: Uint128::new(499999),
+                    min_output: Uint128::new(550000),
+                    expiration: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: "ujuno".to_string(),
+                    amount: Uint128::new(499999)
+                }]
+            },
+            1
+        )]
+    );
+
+    let staker1_info = query_staker_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+    assert_eq!(staker1_info.pending_rewards, vec![Uint128::zero()]);
+    assert_eq!(staker1_info.total_earned, vec![Uint128::new(999999)]);
+
+    // the pool sold 600000 hopers for 499999 ujuno
+    let pool_info = mock_pool_info(1500000, 1400000);
+    deps.querier
+        .update_wasm(move |_| SystemResult::Ok(ContractResult::Ok(to_binary(&pool_info).unwrap())));
+    let swap_reply = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![Event::new("wasm-hopers_swap").add_attribute("output_amount", "600000")],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), env.clone(), swap_reply).unwrap();
+    // the remaining ujuno only pair with 466667 hopers, the rest goes back to the staker
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "hopers_token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: "pool".to_string(),
+                    amount: Uint128::new(466667),
+                    expires: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: "pool".to_string(),
+                    msg: to_binary(&PoolExecuteMsg::AddLiquidity {
+                        token1_amount: Uint128::new(500000),
+                        min_liquidity: Uint128::new(1),
+                        max_token2: Uint128::new(466667),
+                        expiration: None,
+                    })
+                    .unwrap(),
+                    funds: vec![Coin {
+                        denom: "ujuno".to_string(),
+                        amount: Uint128::new(500000)
+                    }]
+                },
+                2
+            ),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "hopers_token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "user1".to_string(),
+                    amount: Uint128::new(133333),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    let add_liquidity_reply = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![
+                Event::new("wasm-hopers_add_liquidity").add_attribute("liquidity_amount", "333333")
+            ],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), env.clone(), add_liquidity_reply).unwrap();
+
+    let staker1_info = query_staker_info(deps.as_ref(), env, "user1".to_string()).unwrap();
+    assert_eq!(staker1_info.bond_amount, Uint128::new(334333));
+}
+
+#[test]
+fn test_lock_tiers() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    let instantiate_msg = InstantiateMsg {
+        staking_token: Denom::Cw20(Addr::unchecked("lp_token_contract")),
+        reward_tokens: vec![RewardToken {
+            denom: Denom::Native("ujuno".to_string()),
+            distribution_schedule: vec![(now, now + 86400, Uint128::new(86400000))],
+        }],
+        lock_duration: 3600,
+        lock_tiers: vec![
+            LockTier {
+                duration: 604800,
+                multiplier: Decimal::percent(150),
+            },
+            LockTier {
+                duration: 2592000,
+                multiplier: Decimal::percent(200),
+            },
+        ],
+        instant_unbond_penalty: Decimal::zero(),
+        penalty_treasury: None,
+        pool_contract: None,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(86400000, "ujuno")),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let bond_msg = |sender: &str, lock_duration: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&Cw20HookMsg::Bond { lock_duration }).unwrap(),
+        })
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        bond_msg("user1", None),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        bond_msg("user2", Some(2592000)),
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        bond_msg("user2", Some(86400)),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::LockTierNotFound {}));
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        bond_msg("user2", Some(604800)),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::LockTierDowngrade {}));
+
+    env.block.time = env.block.time.plus_seconds(300);
+
+    // 300000 ujuno split 1:2 by weight
+    let staker1_info = query_staker_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+    let staker2_info = query_staker_info(deps.as_ref(), env.clone(), "user2".to_string()).unwrap();
+    assert_eq!(staker1_info.weight, Uint128::new(1000));
+    assert_eq!(staker2_info.weight, Uint128::new(2000));
+    assert_eq!(staker1_info.pending_rewards, vec![Uint128::new(100000)]);
+    assert_eq!(staker2_info.pending_rewards, vec![Uint128::new(200000)]);
+
+    // the tier also sets the unbonding delay
+    for user in ["user1", "user2"] {
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(1000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(user, &[]), msg).unwrap();
+    }
+    env.block.time = env.block.time.plus_seconds(3600);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Redeem {
+            owner: None,
+            recipient: None,
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user2", &[]),
+        ExecuteMsg::Redeem {
+            owner: None,
+            recipient: None,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::NothingToRedeem {}));
+
+    env.block.time = env.block.time.plus_seconds(2592000);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("user2", &[]),
+        ExecuteMsg::Redeem {
+            owner: None,
+            recipient: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_bond_native() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    let instantiate_msg = InstantiateMsg {
+        staking_token: Denom::Native("ujuno".to_string()),
+        reward_tokens: vec![RewardToken {
+            denom: Denom::Native("ujuno".to_string()),
+            distribution_schedule: vec![(now, now + 86400, Uint128::new(86400000))],
+        }],
+        lock_duration: 3600,
+        lock_tiers: vec![],
+        instant_unbond_penalty: Decimal::zero(),
+        penalty_treasury: None,
+        pool_contract: None,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(86400000, "ujuno")),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BondNative {
+        lock_duration: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &coins(1000, "uatom")),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFunds {}));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &coins(1000, "ujuno")),
+        msg,
+    )
+    .unwrap();
+
+    // cw20 bonds are rejected
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::WrongContractError {}));
+
+    let msg = ExecuteMsg::Unbond {
+        amount: Uint128::new(1000),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(3600);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user1", &[]),
+        ExecuteMsg::Redeem {
+            owner: None,
+            recipient: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user1".to_string(),
+            amount: coins(1000, "ujuno"),
+        })
+    );
+}
+
+#[test]
+fn test_operators() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    setup_contract(deps.as_mut(), env.clone());
+
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(864);
+
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        owner: Some("user1".to_string()),
+        recipient: Some("vault".to_string()),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    let msg = ExecuteMsg::ApproveOperator {
+        operator: "vault".to_string(),
+        expires: Some(Expiration::AtHeight(env.block.height)),
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidExpiration {}));
+
+    let msg = ExecuteMsg::ApproveOperator {
+        operator: "vault".to_string(),
+        expires: Some(Expiration::AtHeight(env.block.height + 10)),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+    let res = query_operators(deps.as_ref(), env.clone(), "user1".to_string(), None, None).unwrap();
+    assert_eq!(
+        res.operators,
+        vec![OperatorInfo {
+            operator: "vault".to_string(),
+            expires: Expiration::AtHeight(env.block.height + 10),
+        }]
+    );
+
+    // the rewards can only be withdrawn to the owner
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::OperatorRecipient {}));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        ExecuteMsg::Withdraw {
+            owner: Some("user1".to_string()),
+            recipient: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user1".to_string(),
+            amount: coins(999999, "ujuno"),
+        })
+    );
+
+    // the principal can only be redeemed to the owner
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Unbond {
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(3600);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        ExecuteMsg::Redeem {
+            owner: Some("user1".to_string()),
+            recipient: Some("vault".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::OperatorRecipient {}));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        ExecuteMsg::Redeem {
+            owner: Some("user1".to_string()),
+            recipient: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "lp_token_contract".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user1".to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // the approval ends at its expiration
+    env.block.height += 10;
+    let res = query_operators(deps.as_ref(), env.clone(), "user1".to_string(), None, None).unwrap();
+    assert_eq!(res.operators, vec![]);
+    let err = execute(deps.as_mut(), env, mock_info("vault", &[]), withdraw_msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+}
+
+#[test]
+fn test_instant_unbond() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    setup_contract(deps.as_mut(), env.clone());
+
+    let msg = ExecuteMsg::UpdateInstantUnbondConfig {
+        penalty: Decimal::percent(101),
+        treasury: None,
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPenalty {}));
+    let msg = ExecuteMsg::UpdateInstantUnbondConfig {
+        penalty: Decimal::percent(10),
+        treasury: None,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    let msg = ExecuteMsg::UpdateLockTiers {
+        lock_tiers: vec![LockTier {
+            duration: 7200,
+            multiplier: Decimal::percent(200),
+        }],
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query_instant_unbond_penalty(deps.as_ref(), "user1".to_string(), Uint128::new(1000))
+        .unwrap();
+    assert_eq!(res.penalty, Uint128::new(100));
+    assert_eq!(res.amount_after_penalty, Uint128::new(900));
+
+    for (user, amount, lock_duration) in
+        [("user1", 1000u128, None), ("user2", 4000u128, Some(7200))]
+    {
+        let cw20_rcv_msg = Cw20ReceiveMsg {
+            sender: user.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&Cw20HookMsg::Bond { lock_duration }).unwrap(),
+        };
+        let info = mock_info("lp_token_contract", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::Receive(cw20_rcv_msg),
+        )
+        .unwrap();
+    }
+
+    // a tier locked for twice the lock duration pays twice the penalty
+    let res = query_instant_unbond_penalty(deps.as_ref(), "user2".to_string(), Uint128::new(1000))
+        .unwrap();
+    assert_eq!(res.penalty, Uint128::new(200));
+    assert_eq!(res.amount_after_penalty, Uint128::new(800));
+
+    // the penalty is paid to user2 in the staking token
+    let msg = ExecuteMsg::InstantUnbond {
+        amount: Uint128::new(1000),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "lp_token_contract".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user1".to_string(),
+                amount: Uint128::new(900),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(res.messages.len(), 1);
+
+    let staker2_info = query_staker_info(deps.as_ref(), env.clone(), "user2".to_string()).unwrap();
+    assert_eq!(staker2_info.pending_rewards, vec![Uint128::zero()]);
+    assert_eq!(staker2_info.pending_penalty_reward, Uint128::new(100));
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.reward_tokens.len(), 1);
+
+    let msg = ExecuteMsg::Withdraw {
+        owner: None,
+        recipient: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("user2", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "lp_token_contract".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user2".to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // with a treasury the penalty is sent to it
+    let msg = ExecuteMsg::UpdateInstantUnbondConfig {
+        penalty: Decimal::percent(10),
+        treasury: Some("treasury".to_string()),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    env.block.time = env.block.time.plus_seconds(10);
+    let msg = ExecuteMsg::InstantUnbond {
+        amount: Uint128::new(4000),
+    };
+    let res = execute(deps.as_mut(), env, mock_info("user2", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "lp_token_contract".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury".to_string(),
+                amount: Uint128::new(800),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_rebond() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    setup_contract(deps.as_mut(), env.clone());
+
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+
+    let first_unbond_time = env.block.time.seconds();
+    let msg = ExecuteMsg::Unbond {
+        amount: Uint128::new(600),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::Unbond {
+        amount: Uint128::new(400),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Rebond {
+        time: Some(first_unbond_time),
+        amount: Uint128::new(700),
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::ExceedUnbondingAmount {}));
+
+    // the latest entry is used first
+    let msg = ExecuteMsg::Rebond {
+        time: None,
+        amount: Uint128::new(500),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+    let unbonding_infos =
+        query_all_unbonding_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+    assert_eq!(unbonding_infos.len(), 1);
+    assert_eq!(unbonding_infos[0].time, first_unbond_time);
+    assert_eq!(unbonding_infos[0].amount, Uint128::new(500));
+
+    let msg = ExecuteMsg::Rebond {
+        time: Some(first_unbond_time),
+        amount: Uint128::new(500),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("user1", &[]), msg).unwrap();
+    let unbonding_infos =
+        query_all_unbonding_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+    assert!(unbonding_infos.is_empty());
+
+    // as the only staker user1 earns the whole distribution of the 964 seconds
+    env.block.time = env.block.time.plus_seconds(864);
+    let staker1_info = query_staker_info(deps.as_ref(), env, "user1".to_string()).unwrap();
+    assert_eq!(staker1_info.bond_amount, Uint128::new(1000));
+    assert_eq!(staker1_info.pending_rewards, vec![Uint128::new(1115739)]);
+}
+
+#[test]
+fn test_update_staking_token() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    setup_contract(deps.as_mut(), env.clone());
+
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdateStakingToken {
+        staking_token: Denom::Native("ujuno".to_string()),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::StakingTokenInUse {}));
+
+    // unbonding tokens are still paid in the old staking token
+    let unbond_msg = ExecuteMsg::Unbond {
+        amount: Uint128::new(1000),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        unbond_msg,
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::StakingTokenInUse {}));
+
+    env.block.time = env.block.time.plus_seconds(3600);
+    let redeem_msg = ExecuteMsg::Redeem {
+        owner: None,
+        recipient: None,
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        redeem_msg,
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.staking_token, Denom::Native("ujuno".to_string()));
+
+    // the former message and field names are still accepted
+    let msg: ExecuteMsg =
+        from_binary(&br#"{"update_token_contract":{"lp_token_contract":"new_lp_token"}}"#.into())
+            .unwrap();
+    execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        config.staking_token,
+        Denom::Cw20(Addr::unchecked("new_lp_token"))
+    );
+
+    let msg: InstantiateMsg = from_binary(
+        &br#"{"lp_token_contract":"lp_token","reward_tokens":[],"lock_duration":3600,"lock_tiers":[],"instant_unbond_penalty":"0","penalty_treasury":null,"pool_contract":null}"#.into(),
+    )
+    .unwrap();
+    assert_eq!(msg.staking_token, Denom::Cw20(Addr::unchecked("lp_token")));
+    let msg: InstantiateMsg = from_binary(
+        &br#"{"staking_token":{"native":"ujuno"},"reward_tokens":[],"lock_duration":3600,"lock_tiers":[],"instant_unbond_penalty":"0","penalty_treasury":null,"pool_contract":null}"#.into(),
+    )
+    .unwrap();
+    assert_eq!(msg.staking_token, Denom::Native("ujuno".to_string()));
+}
+
+#[test]
+fn test_fund_rewards() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    setup_contract(deps.as_mut(), env.clone());
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("funder", &coins(5000, "uatom")),
+        ExecuteMsg::FundRewards {},
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::RewardTokenNotFound {}));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("funder", &coins(5000, "ujuno")),
+        ExecuteMsg::FundRewards {},
+    )
+    .unwrap();
+
+    // nothing accrues while nothing is bonded
+    let mut later_env = env.clone();
+    later_env.block.time = later_env.block.time.plus_seconds(864);
+    let res = query_reward_balance(deps.as_ref(), later_env).unwrap();
+    assert_eq!(res.reward_balances[0].distributed_amount, Uint128::zero());
+
+    let cw20_rcv_msg = Cw20ReceiveMsg {
+        sender: "user1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+        })
+        .unwrap(),
+    };
+    let info = mock_info("lp_token_contract", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Receive(cw20_rcv_msg),
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(864);
+
+    let res = query_reward_balance(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(
+        res.reward_balances,
+        vec![RewardBalance {
+            denom: Denom::Native("ujuno".to_string()),
+            funded_amount: Uint128::new(100005000),
+            scheduled_amount: Uint128::new(100000000),
+            distributed_amount: Uint128::new(999999),
+        }]
+    );
+
+    // everything not distributed goes back in the reward denom
+    let msg = ExecuteMsg::MigrateStaking {
+        new_staking_contract: "new_staking".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "new_staking".to_string(),
+            amount: coins(99005001, "ujuno"),
+        })]
+    );
+
+    let res = query_reward_balance(deps.as_ref(), env).unwrap();
+    assert_eq!(res.reward_balances[0].funded_amount, Uint128::new(999999));
+    assert_eq!(
+        res.reward_balances[0].scheduled_amount,
+        Uint128::new(999999)
+    );
+}
+
+#[test]
+fn test_migrate_legacy_state() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let start = env.block.time.seconds();
+
+    // 0.1.0 layout
+    cw2::set_contract_version(deps.as_mut().storage, "hopers_staking", "0.1.0").unwrap();
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                lp_token_contract: "lp_token_contract".to_string(),
+                reward_token: Denom::Native("ujuno".to_string()),
+                distribution_schedule: vec![(start, start + 86400, Uint128::new(100000000))],
+                admin: "owner".to_string(),
+                lock_duration: 3600,
+            },
+        )
+        .unwrap();
+    LEGACY_STATE
+        .save(
+            deps.as_mut().storage,
+            &LegacyState {
+                last_distributed: start,
+                total_bond_amount: Uint128::new(100),
+                global_reward_index: Decimal::percent(5),
+            },
+        )
+        .unwrap();
+    LEGACY_STAKER_INFO
+        .save(
+            deps.as_mut().storage,
+            "user1".to_string(),
+            &LegacyStakerInfo {
+                address: "user1".to_string(),
+                reward_index: Decimal::percent(5),
+                bond_amount: Uint128::new(100),
+                pending_reward: Uint128::new(7),
+            },
+        )
+        .unwrap();
+    LEGACY_STAKER_INFO
+        .save(
+            deps.as_mut().storage,
+            "user2".to_string(),
+            &LegacyStakerInfo {
+                address: "user2".to_string(),
+                reward_index: Decimal::percent(5),
+                bond_amount: Uint128::zero(),
+                pending_reward: Uint128::new(1),
+            },
+        )
+        .unwrap();
+    LEGACY_USER_EARNED_INFO
+        .save(
+            deps.as_mut().storage,
+            "user1".to_string(),
+            &LegacyUserEarnedInfo {
+                address: "user1".to_string(),
+                total_earned: Uint128::new(3),
+            },
+        )
+        .unwrap();
+    LEGACY_UNBONDING_INFO
+        .save(
+            deps.as_mut().storage,
+            ("user1".to_string(), start),
+            &LegacyUnbondingInfo {
+                address: "user1".to_string(),
+                time: start,
+                amount: Uint128::new(50),
+            },
+        )
+        .unwrap();
+
+    migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+    assert_eq!(
+        cw2::get_contract_version(deps.as_ref().storage)
+            .unwrap()
+            .version,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        config.staking_token,
+        Denom::Cw20(Addr::unchecked("lp_token_contract"))
+    );
+    assert_eq!(
+        config.reward_tokens,
+        vec![RewardToken {
+            denom: Denom::Native("ujuno".to_string()),
+            distribution_schedule: vec![(start, start + 86400, Uint128::new(100000000))],
+        }]
+    );
+    assert_eq!(config.instant_unbond_penalty, Decimal::one());
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_weight, Uint128::new(100));
+    assert_eq!(state.global_reward_indexes, vec![Decimal::percent(5)]);
+    assert_eq!(state.funded_rewards, vec![Uint128::new(100000000)]);
+    assert_eq!(state.distributed_rewards, vec![Uint128::new(5)]);
+
+    // the entries are converted in batches before anything else runs
+    let redeem_msg = ExecuteMsg::Redeem {
+        owner: None,
+        recipient: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        redeem_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::LegacyMigrationPending {}));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::MigrateLegacy { limit: Some(1) },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, "1");
+    assert_eq!(res.attributes[2].value, "false");
+    assert!(LEGACY_STAKER_INFO.has(deps.as_ref().storage, "user2".to_string()));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::MigrateLegacy { limit: None },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, "3");
+    assert_eq!(res.attributes[2].value, "true");
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::MigrateLegacy { limit: None },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::NothingToMigrate {}));
+
+    let staker_info = query_staker_info(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::new(100));
+    assert_eq!(staker_info.weight, Uint128::new(100));
+    assert_eq!(staker_info.lock_tier, None);
+    assert_eq!(staker_info.pending_rewards, vec![Uint128::new(7)]);
+    assert_eq!(staker_info.total_earned, vec![Uint128::new(3)]);
+
+    // the unbonding keeps the lock duration of 0.1.0
+    let unbonding_info =
+        query_unbonding_info(deps.as_ref(), env.clone(), "user1".to_string(), None, None).unwrap();
+    assert_eq!(unbonding_info.unbonding_info[0].lock_duration, 3600);
+    env.block.time = env.block.time.plus_seconds(3600);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        redeem_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "lp_token_contract".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user1".to_string(),
+                amount: Uint128::new(50),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // the version is current now
+    migrate(deps.as_mut(), env, MigrateMsg {}).unwrap();
+}
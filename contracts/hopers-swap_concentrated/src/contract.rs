@@ -0,0 +1,1281 @@
+use cosmwasm_std::{
+    attr, entry_point, to_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, Event, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128, Uint256,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Denom, Expiration};
+use cw_storage_plus::{Bound, Item};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+use hopers_denom::{
+    denom_to_string, get_amount_for_denom, get_bank_transfer_to_msg, get_cw20_transfer_from_msg,
+    get_fee_transfer_msg, get_protocol_fee_amount, get_transfer_to_msg,
+};
+
+use crate::error::ContractError;
+use crate::math::{
+    compute_swap_step, get_amounts_for_liquidity, get_fee_growth, get_fees_earned,
+    get_liquidity_for_amounts, sqrt_price_to_tick, tick_to_sqrt_price, MAX_TICK, MIN_TICK,
+};
+use crate::msg::{
+    ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, MigrateMsg, PositionResponse,
+    PositionsResponse, QueryMsg, Token1ForToken2PriceResponse, Token2ForToken1PriceResponse,
+    TokenSelect, WalletInfo,
+};
+use crate::state::{
+    key_to_tick, positions, tick_key, Fees, Pool, Position, TickInfo, Token, FEES,
+    NEXT_POSITION_ID, OWNER, POOL, TICKS, TOKEN1, TOKEN2,
+};
+
+// Version info for migration info
+pub const CONTRACT_NAME: &str = "crates.io:hopers-swap-concentrated";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const MAX_FEE_PERCENT: &str = "1";
+
+// Query limits
+const DEFAULT_QUERY_LIMIT: u32 = 10;
+const MAX_QUERY_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let token1 = Token {
+        reserve: Uint128::zero(),
+        denom: msg.token1_denom,
+    };
+    TOKEN1.save(deps.storage, &token1)?;
+
+    let token2 = Token {
+        reserve: Uint128::zero(),
+        denom: msg.token2_denom,
+    };
+    TOKEN2.save(deps.storage, &token2)?;
+
+    let owner = msg.owner.map(|h| deps.api.addr_validate(&h)).transpose()?;
+    OWNER.save(deps.storage, &owner)?;
+
+    validate_dev_wallets(deps.as_ref(), &msg.dev_wallet_lists)?;
+
+    let fees = Fees {
+        dev_wallet_lists: msg.dev_wallet_lists,
+        fee_percent_numerator: msg.fee_percent_numerator,
+        lp_fee_percent_numerator: msg.lp_fee_percent_numerator,
+        fee_percent_denominator: msg.fee_percent_denominator,
+    };
+    validate_fees(&fees)?;
+    FEES.save(deps.storage, &fees)?;
+
+    if msg.tick_spacing <= 0 {
+        return Err(ContractError::InvalidTickSpacing {});
+    }
+
+    let sqrt_price = msg.initial_price.sqrt();
+    let pool = Pool {
+        tick_spacing: msg.tick_spacing,
+        sqrt_price,
+        current_tick: sqrt_price_to_tick(sqrt_price)?,
+        liquidity: Uint128::zero(),
+        fee_growth_global1: Uint256::zero(),
+        fee_growth_global2: Uint256::zero(),
+    };
+    POOL.save(deps.storage, &pool)?;
+    NEXT_POSITION_ID.save(deps.storage, &1u64)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "instantiate"),
+        attr("sqrt_price", sqrt_price.to_string()),
+        attr("current_tick", pool.current_tick.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::MintPosition {
+            lower_tick,
+            upper_tick,
+            max_token1,
+            max_token2,
+            min_liquidity,
+            expiration,
+        } => execute_mint_position(
+            deps,
+            info,
+            env,
+            lower_tick,
+            upper_tick,
+            max_token1,
+            max_token2,
+            min_liquidity,
+            expiration,
+        ),
+        ExecuteMsg::IncreaseLiquidity {
+            position_id,
+            max_token1,
+            max_token2,
+            min_liquidity,
+            expiration,
+        } => execute_increase_liquidity(
+            deps,
+            info,
+            env,
+            position_id,
+            max_token1,
+            max_token2,
+            min_liquidity,
+            expiration,
+        ),
+        ExecuteMsg::DecreaseLiquidity {
+            position_id,
+            liquidity,
+            min_token1,
+            min_token2,
+            expiration,
+        } => execute_decrease_liquidity(
+            deps,
+            info,
+            env,
+            position_id,
+            liquidity,
+            min_token1,
+            min_token2,
+            expiration,
+        ),
+        ExecuteMsg::CollectFees { position_id } => execute_collect_fees(deps, info, position_id),
+        ExecuteMsg::Swap {
+            input_token,
+            input_amount,
+            min_output,
+            expiration,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_token,
+            info.sender.to_string(),
+            min_output,
+            expiration,
+        ),
+        ExecuteMsg::SwapAndSendTo {
+            input_token,
+            input_amount,
+            recipient,
+            min_token,
+            expiration,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_token,
+            recipient,
+            min_token,
+            expiration,
+        ),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            fee_percent_numerator,
+            lp_fee_percent_numerator,
+            fee_percent_denominator,
+            dev_wallet_lists,
+        } => execute_update_config(
+            deps,
+            info,
+            owner,
+            fee_percent_numerator,
+            lp_fee_percent_numerator,
+            fee_percent_denominator,
+            dev_wallet_lists,
+        ),
+    }
+}
+
+fn check_expiration(
+    expiration: &Option<Expiration>,
+    block: &BlockInfo,
+) -> Result<(), ContractError> {
+    match expiration {
+        Some(e) => {
+            if e.is_expired(block) {
+                return Err(ContractError::MsgExpirationError {});
+            }
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+fn validate_dev_wallets(deps: Deps, dev_wallet_lists: &[WalletInfo]) -> Result<(), ContractError> {
+    let mut total_ratio = Decimal::zero();
+    for dev_wallet in dev_wallet_lists {
+        deps.api.addr_validate(&dev_wallet.address)?;
+        total_ratio += dev_wallet.ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(ContractError::WrongRatio {});
+    }
+    Ok(())
+}
+
+fn validate_fees(fees: &Fees) -> Result<Decimal, ContractError> {
+    let total_fee_percent = Decimal::from_ratio(
+        fees.fee_percent_numerator + fees.lp_fee_percent_numerator,
+        fees.fee_percent_denominator,
+    );
+    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
+    // LPs must receive part of every swap input, so the full input can not be taken as fees
+    if total_fee_percent >= max_fee_percent {
+        return Err(ContractError::FeesTooHigh {
+            max_fee_percent,
+            total_fee_percent,
+        });
+    }
+    Ok(total_fee_percent)
+}
+
+fn validate_ticks(pool: &Pool, lower_tick: i32, upper_tick: i32) -> Result<(), ContractError> {
+    if lower_tick >= upper_tick
+        || lower_tick < MIN_TICK
+        || upper_tick > MAX_TICK
+        || lower_tick % pool.tick_spacing != 0
+        || upper_tick % pool.tick_spacing != 0
+    {
+        return Err(ContractError::InvalidTickRange {
+            lower_tick,
+            upper_tick,
+        });
+    }
+    Ok(())
+}
+
+fn is_in_range(pool: &Pool, position: &Position) -> bool {
+    position.lower_tick <= pool.current_tick && pool.current_tick < position.upper_tick
+}
+
+fn update_tick(
+    storage: &mut dyn Storage,
+    pool: &Pool,
+    tick: i32,
+    liquidity_delta: Uint128,
+    is_upper: bool,
+    add: bool,
+) -> Result<(), ContractError> {
+    let mut info = match TICKS.may_load(storage, tick_key(tick))? {
+        Some(info) => info,
+        // By convention all the fee growth before a tick is initialized happened below it
+        None => {
+            let (fee_growth_outside1, fee_growth_outside2) = if pool.current_tick >= tick {
+                (pool.fee_growth_global1, pool.fee_growth_global2)
+            } else {
+                (Uint256::zero(), Uint256::zero())
+            };
+            TickInfo {
+                liquidity_gross: Uint128::zero(),
+                liquidity_starting: Uint128::zero(),
+                liquidity_ending: Uint128::zero(),
+                fee_growth_outside1,
+                fee_growth_outside2,
+            }
+        }
+    };
+
+    if add {
+        info.liquidity_gross += liquidity_delta;
+        if is_upper {
+            info.liquidity_ending += liquidity_delta;
+        } else {
+            info.liquidity_starting += liquidity_delta;
+        }
+    } else {
+        info.liquidity_gross = info
+            .liquidity_gross
+            .checked_sub(liquidity_delta)
+            .map_err(StdError::overflow)?;
+        if is_upper {
+            info.liquidity_ending = info
+                .liquidity_ending
+                .checked_sub(liquidity_delta)
+                .map_err(StdError::overflow)?;
+        } else {
+            info.liquidity_starting = info
+                .liquidity_starting
+                .checked_sub(liquidity_delta)
+                .map_err(StdError::overflow)?;
+        }
+    }
+
+    if info.liquidity_gross.is_zero() {
+        TICKS.remove(storage, tick_key(tick));
+    } else {
+        TICKS.save(storage, tick_key(tick), &info)?;
+    }
+    Ok(())
+}
+
+fn get_fee_growth_inside(
+    storage: &dyn Storage,
+    pool: &Pool,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> StdResult<(Uint256, Uint256)> {
+    let lower = TICKS.load(storage, tick_key(lower_tick))?;
+    let upper = TICKS.load(storage, tick_key(upper_tick))?;
+
+    let (below1, below2) = if pool.current_tick >= lower_tick {
+        (lower.fee_growth_outside1, lower.fee_growth_outside2)
+    } else {
+        (
+            pool.fee_growth_global1
+                .wrapping_sub(lower.fee_growth_outside1),
+            pool.fee_growth_global2
+                .wrapping_sub(lower.fee_growth_outside2),
+        )
+    };
+    let (above1, above2) = if pool.current_tick < upper_tick {
+        (upper.fee_growth_outside1, upper.fee_growth_outside2)
+    } else {
+        (
+            pool.fee_growth_global1
+                .wrapping_sub(upper.fee_growth_outside1),
+            pool.fee_growth_global2
+                .wrapping_sub(upper.fee_growth_outside2),
+        )
+    };
+
+    Ok((
+        pool.fee_growth_global1
+            .wrapping_sub(below1)
+            .wrapping_sub(above1),
+        pool.fee_growth_global2
+            .wrapping_sub(below2)
+            .wrapping_sub(above2),
+    ))
+}
+
+// Moves the fees earned since the last update to the position's owed amounts
+fn accrue_position_fees(
+    storage: &dyn Storage,
+    pool: &Pool,
+    position: &mut Position,
+) -> StdResult<()> {
+    let (inside1, inside2) =
+        get_fee_growth_inside(storage, pool, position.lower_tick, position.upper_tick)?;
+    position.tokens_owed1 += get_fees_earned(
+        inside1.wrapping_sub(position.fee_growth_inside1_last),
+        position.liquidity,
+    )?;
+    position.tokens_owed2 += get_fees_earned(
+        inside2.wrapping_sub(position.fee_growth_inside2_last),
+        position.liquidity,
+    )?;
+    position.fee_growth_inside1_last = inside1;
+    position.fee_growth_inside2_last = inside2;
+    Ok(())
+}
+
+fn load_owned_position(
+    deps: Deps,
+    sender: &Addr,
+    position_id: u64,
+) -> Result<Position, ContractError> {
+    let position = positions()
+        .may_load(deps.storage, position_id)?
+        .ok_or(ContractError::PositionNotFound { position_id })?;
+    if &position.owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(position)
+}
+
+fn update_reserve(
+    storage: &mut dyn Storage,
+    item: Item<Token>,
+    amount: Uint128,
+    add: bool,
+) -> Result<(), ContractError> {
+    item.update(storage, |mut token| -> Result<_, ContractError> {
+        token.reserve = if add {
+            token
+                .reserve
+                .checked_add(amount)
+                .map_err(StdError::overflow)?
+        } else {
+            token
+                .reserve
+                .checked_sub(amount)
+                .map_err(StdError::overflow)?
+        };
+        Ok(token)
+    })?;
+    Ok(())
+}
+
+fn add_reserve_attributes(storage: &dyn Storage, event: Event) -> StdResult<Event> {
+    let token1 = TOKEN1.load(storage)?;
+    let token2 = TOKEN2.load(storage)?;
+    Ok(event
+        .add_attribute("token1_reserve", token1.reserve)
+        .add_attribute("token2_reserve", token2.reserve))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_mint_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    lower_tick: i32,
+    upper_tick: i32,
+    max_token1: Uint128,
+    max_token2: Uint128,
+    min_liquidity: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let pool = POOL.load(deps.storage)?;
+    validate_ticks(&pool, lower_tick, upper_tick)?;
+
+    let position_id = NEXT_POSITION_ID.load(deps.storage)?;
+    NEXT_POSITION_ID.save(deps.storage, &(position_id + 1))?;
+
+    let position = Position {
+        owner: info.sender.clone(),
+        lower_tick,
+        upper_tick,
+        liquidity: Uint128::zero(),
+        fee_growth_inside1_last: Uint256::zero(),
+        fee_growth_inside2_last: Uint256::zero(),
+        tokens_owed1: Uint128::zero(),
+        tokens_owed2: Uint128::zero(),
+    };
+
+    add_position_liquidity(
+        deps,
+        &info,
+        &env,
+        "mint_position",
+        position_id,
+        position,
+        max_token1,
+        max_token2,
+        min_liquidity,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_increase_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    position_id: u64,
+    max_token1: Uint128,
+    max_token2: Uint128,
+    min_liquidity: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let position = load_owned_position(deps.as_ref(), &info.sender, position_id)?;
+
+    add_position_liquidity(
+        deps,
+        &info,
+        &env,
+        "increase_liquidity",
+        position_id,
+        position,
+        max_token1,
+        max_token2,
+        min_liquidity,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_position_liquidity(
+    deps: DepsMut,
+    info: &MessageInfo,
+    env: &Env,
+    action: &str,
+    position_id: u64,
+    mut position: Position,
+    max_token1: Uint128,
+    max_token2: Uint128,
+    min_liquidity: Uint128,
+) -> Result<Response, ContractError> {
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+
+    // validate funds
+    validate_input_amount(&info.funds, max_token1, &token1.denom)?;
+    validate_input_amount(&info.funds, max_token2, &token2.denom)?;
+
+    let mut pool = POOL.load(deps.storage)?;
+    let sqrt_price_lower = tick_to_sqrt_price(position.lower_tick)?;
+    let sqrt_price_upper = tick_to_sqrt_price(position.upper_tick)?;
+
+    let liquidity = get_liquidity_for_amounts(
+        pool.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        max_token1,
+        max_token2,
+    )?;
+    if liquidity.is_zero() || liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityError {
+            min_liquidity,
+            liquidity_available: liquidity,
+        });
+    }
+
+    let (token1_amount, token2_amount) = get_amounts_for_liquidity(
+        pool.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )?;
+    if token1_amount > max_token1 {
+        return Err(ContractError::MaxTokenError {
+            max_token: max_token1,
+            tokens_required: token1_amount,
+        });
+    }
+    if token2_amount > max_token2 {
+        return Err(ContractError::MaxTokenError {
+            max_token: max_token2,
+            tokens_required: token2_amount,
+        });
+    }
+
+    update_tick(
+        deps.storage,
+        &pool,
+        position.lower_tick,
+        liquidity,
+        false,
+        true,
+    )?;
+    update_tick(
+        deps.storage,
+        &pool,
+        position.upper_tick,
+        liquidity,
+        true,
+        true,
+    )?;
+    accrue_position_fees(deps.storage, &pool, &mut position)?;
+
+    position.liquidity += liquidity;
+    if is_in_range(&pool, &position) {
+        pool.liquidity += liquidity;
+    }
+    positions().save(deps.storage, position_id, &position)?;
+    POOL.save(deps.storage, &pool)?;
+
+    update_reserve(deps.storage, TOKEN1, token1_amount, true)?;
+    update_reserve(deps.storage, TOKEN2, token2_amount, true)?;
+
+    // Generate cw20 transfer messages if necessary, refund unused native tokens
+    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
+    for (denom, amount, max_amount) in [
+        (&token1.denom, token1_amount, max_token1),
+        (&token2.denom, token2_amount, max_token2),
+    ] {
+        match denom {
+            Denom::Cw20(addr) => {
+                if !amount.is_zero() {
+                    transfer_msgs.push(get_cw20_transfer_from_msg(
+                        &info.sender,
+                        &env.contract.address,
+                        addr,
+                        amount,
+                    )?)
+                }
+            }
+            Denom::Native(denom) => {
+                if amount < max_amount {
+                    transfer_msgs.push(get_bank_transfer_to_msg(
+                        &info.sender,
+                        denom,
+                        max_amount - amount,
+                    ))
+                }
+            }
+        }
+    }
+
+    let event = Event::new("hopers_add_liquidity")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", position.owner.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", token1_amount)
+        .add_attribute("token2_amount", token2_amount)
+        .add_attribute("liquidity_amount", liquidity)
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("lower_tick", position.lower_tick.to_string())
+        .add_attribute("upper_tick", position.upper_tick.to_string());
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(transfer_msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", action),
+            attr("position_id", position_id.to_string()),
+            attr("liquidity_added", liquidity),
+            attr("token1_amount", token1_amount),
+            attr("token2_amount", token2_amount),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_decrease_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    position_id: u64,
+    liquidity: Uint128,
+    min_token1: Uint128,
+    min_token2: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let mut position = load_owned_position(deps.as_ref(), &info.sender, position_id)?;
+    if liquidity.is_zero() || liquidity > position.liquidity {
+        return Err(ContractError::InsufficientLiquidityError {
+            requested: liquidity,
+            available: position.liquidity,
+        });
+    }
+
+    let mut pool = POOL.load(deps.storage)?;
+    accrue_position_fees(deps.storage, &pool, &mut position)?;
+
+    let (token1_amount, token2_amount) = get_amounts_for_liquidity(
+        pool.sqrt_price,
+        tick_to_sqrt_price(position.lower_tick)?,
+        tick_to_sqrt_price(position.upper_tick)?,
+        liquidity,
+        false,
+    )?;
+    if token1_amount < min_token1 {
+        return Err(ContractError::MinToken1Error {
+            requested: min_token1,
+            available: token1_amount,
+        });
+    }
+    if token2_amount < min_token2 {
+        return Err(ContractError::MinToken2Error {
+            requested: min_token2,
+            available: token2_amount,
+        });
+    }
+
+    update_tick(
+        deps.storage,
+        &pool,
+        position.lower_tick,
+        liquidity,
+        false,
+        false,
+    )?;
+    update_tick(
+        deps.storage,
+        &pool,
+        position.upper_tick,
+        liquidity,
+        true,
+        false,
+    )?;
+    if is_in_range(&pool, &position) {
+        pool.liquidity = pool
+            .liquidity
+            .checked_sub(liquidity)
+            .map_err(StdError::overflow)?;
+    }
+    position.liquidity -= liquidity;
+    POOL.save(deps.storage, &pool)?;
+    positions().save(deps.storage, position_id, &position)?;
+
+    update_reserve(deps.storage, TOKEN1, token1_amount, false)?;
+    update_reserve(deps.storage, TOKEN2, token2_amount, false)?;
+
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if !token1_amount.is_zero() {
+        msgs.push(get_transfer_to_msg(
+            &info.sender,
+            &token1.denom,
+            token1_amount,
+        )?);
+    }
+    if !token2_amount.is_zero() {
+        msgs.push(get_transfer_to_msg(
+            &info.sender,
+            &token2.denom,
+            token2_amount,
+        )?);
+    }
+
+    let event = Event::new("hopers_remove_liquidity")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", info.sender.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", token1_amount)
+        .add_attribute("token2_amount", token2_amount)
+        .add_attribute("liquidity_amount", liquidity)
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("lower_tick", position.lower_tick.to_string())
+        .add_attribute("upper_tick", position.upper_tick.to_string());
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", "decrease_liquidity"),
+            attr("position_id", position_id.to_string()),
+            attr("liquidity_removed", liquidity),
+            attr("token1_returned", token1_amount),
+            attr("token2_returned", token2_amount),
+        ]))
+}
+
+pub fn execute_collect_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let mut position = load_owned_position(deps.as_ref(), &info.sender, position_id)?;
+    let pool = POOL.load(deps.storage)?;
+    if !position.liquidity.is_zero() {
+        accrue_position_fees(deps.storage, &pool, &mut position)?;
+    }
+
+    let fees1 = position.tokens_owed1;
+    let fees2 = position.tokens_owed2;
+    position.tokens_owed1 = Uint128::zero();
+    position.tokens_owed2 = Uint128::zero();
+
+    // Positions without liquidity and fees left are closed
+    if position.liquidity.is_zero() {
+        positions().remove(deps.storage, position_id)?;
+    } else {
+        positions().save(deps.storage, position_id, &position)?;
+    }
+
+    update_reserve(deps.storage, TOKEN1, fees1, false)?;
+    update_reserve(deps.storage, TOKEN2, fees2, false)?;
+
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if !fees1.is_zero() {
+        msgs.push(get_transfer_to_msg(&info.sender, &token1.denom, fees1)?);
+    }
+    if !fees2.is_zero() {
+        msgs.push(get_transfer_to_msg(&info.sender, &token2.denom, fees2)?);
+    }
+
+    let event = Event::new("hopers_collect_fees")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", fees1)
+        .add_attribute("token2_amount", fees2)
+        .add_attribute("position_id", position_id.to_string());
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", "collect_fees"),
+            attr("position_id", position_id.to_string()),
+            attr("token1_fees", fees1),
+            attr("token2_fees", fees2),
+        ]))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: Option<String>,
+    fee_percent_numerator: Uint128,
+    lp_fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+    dev_wallet_lists: Vec<WalletInfo>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner_addr = new_owner
+        .as_ref()
+        .map(|h| deps.api.addr_validate(h))
+        .transpose()?;
+    OWNER.save(deps.storage, &new_owner_addr)?;
+
+    validate_dev_wallets(deps.as_ref(), &dev_wallet_lists)?;
+
+    let updated_fees = Fees {
+        dev_wallet_lists,
+        fee_percent_numerator,
+        lp_fee_percent_numerator,
+        fee_percent_denominator,
+    };
+    let total_fee_percent = validate_fees(&updated_fees)?;
+    FEES.save(deps.storage, &updated_fees)?;
+
+    let new_owner = new_owner.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("new_owner", new_owner),
+        attr("fee_percent", total_fee_percent.to_string()),
+    ]))
+}
+
+fn validate_input_amount(
+    actual_funds: &[Coin],
+    given_amount: Uint128,
+    given_denom: &Denom,
+) -> Result<(), ContractError> {
+    match given_denom {
+        Denom::Cw20(_) => Ok(()),
+        Denom::Native(denom) => {
+            let actual = get_amount_for_denom(actual_funds, denom);
+            if actual.amount != given_amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            if &actual.denom != denom {
+                return Err(ContractError::IncorrectNativeDenom {
+                    provided: actual.denom,
+                    required: denom.to_string(),
+                });
+            };
+            Ok(())
+        }
+    }
+}
+
+pub struct SwapResult {
+    pub amount_out: Uint128,
+    pub pool: Pool,
+    pub crossed_ticks: Vec<(i32, TickInfo)>,
+}
+
+/// Runs a swap across the initialized ticks without writing to storage
+pub fn compute_swap(
+    storage: &dyn Storage,
+    pool: &Pool,
+    fees: &Fees,
+    input_token: &TokenSelect,
+    amount_in: Uint128,
+) -> Result<SwapResult, ContractError> {
+    // Selling token1 moves the price down, selling token2 moves it up
+    let token1_in = *input_token == TokenSelect::Token1;
+    let mut pool = pool.clone();
+    let mut crossed_ticks = vec![];
+    let mut amount_remaining = amount_in;
+    let mut amount_out = Uint128::zero();
+
+    while !amount_remaining.is_zero() {
+        let next_tick = if token1_in {
+            TICKS
+                .range_de(
+                    storage,
+                    None,
+                    Some(Bound::inclusive_int(tick_key(pool.current_tick))),
+                    Order::Descending,
+                )
+                .next()
+                .transpose()?
+        } else {
+            TICKS
+                .range_de(
+                    storage,
+                    Some(Bound::exclusive_int(tick_key(pool.current_tick))),
+                    None,
+                    Order::Ascending,
+                )
+                .next()
+                .transpose()?
+        }
+        .map(|(key, info)| (key_to_tick(key), info));
+        let sqrt_price_target = match &next_tick {
+            Some((tick, _)) => tick_to_sqrt_price(*tick)?,
+            None if token1_in => tick_to_sqrt_price(MIN_TICK)?,
+            None => tick_to_sqrt_price(MAX_TICK)?,
+        };
+
+        let step = compute_swap_step(
+            pool.sqrt_price,
+            sqrt_price_target,
+            pool.liquidity,
+            amount_remaining,
+            fees.lp_fee_percent_numerator,
+            fees.fee_percent_denominator,
+        )?;
+        amount_remaining -= step.amount_in + step.fee_amount;
+        amount_out += step.amount_out;
+
+        let fee_growth = get_fee_growth(step.fee_amount, pool.liquidity)?;
+        if token1_in {
+            pool.fee_growth_global1 = pool.fee_growth_global1.wrapping_add(fee_growth);
+        } else {
+            pool.fee_growth_global2 = pool.fee_growth_global2.wrapping_add(fee_growth);
+        }
+        pool.sqrt_price = step.sqrt_price_next;
+
+        if step.sqrt_price_next != sqrt_price_target {
+            pool.current_tick = sqrt_price_to_tick(pool.sqrt_price)?;
+            continue;
+        }
+
+        match next_tick {
+            Some((tick, mut info)) => {
+                info.fee_growth_outside1 = pool
+                    .fee_growth_global1
+                    .wrapping_sub(info.fee_growth_outside1);
+                info.fee_growth_outside2 = pool
+                    .fee_growth_global2
+                    .wrapping_sub(info.fee_growth_outside2);
+                if token1_in {
+                    pool.liquidity = (pool.liquidity + info.liquidity_ending)
+                        .checked_sub(info.liquidity_starting)
+                        .map_err(StdError::overflow)?;
+                    pool.current_tick = tick - 1;
+                } else {
+                    pool.liquidity = (pool.liquidity + info.liquidity_starting)
+                        .checked_sub(info.liquidity_ending)
+                        .map_err(StdError::overflow)?;
+                    pool.current_tick = tick;
+                }
+                crossed_ticks.push((tick, info));
+            }
+            // The price limit was reached without filling the whole input
+            None => return Err(ContractError::SwapLiquidityError {}),
+        }
+    }
+
+    Ok(SwapResult {
+        amount_out,
+        pool,
+        crossed_ticks,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap(
+    deps: DepsMut,
+    info: &MessageInfo,
+    input_amount: Uint128,
+    env: Env,
+    input_token_enum: TokenSelect,
+    recipient: String,
+    min_token: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let input_token_item = match input_token_enum {
+        TokenSelect::Token1 => TOKEN1,
+        TokenSelect::Token2 => TOKEN2,
+    };
+    let input_token = input_token_item.load(deps.storage)?;
+    let output_token_item = match input_token_enum {
+        TokenSelect::Token1 => TOKEN2,
+        TokenSelect::Token2 => TOKEN1,
+    };
+    let output_token = output_token_item.load(deps.storage)?;
+
+    // validate input_amount if native input token
+    validate_input_amount(&info.funds, input_amount, &input_token.denom)?;
+
+    let fees = FEES.load(deps.storage)?;
+    let protocol_fee_amount = get_protocol_fee_amount(
+        input_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    let input_amount_minus_protocol_fee = input_amount - protocol_fee_amount;
+
+    let pool = POOL.load(deps.storage)?;
+    let result = compute_swap(
+        deps.storage,
+        &pool,
+        &fees,
+        &input_token_enum,
+        input_amount_minus_protocol_fee,
+    )?;
+    let token_bought = result.amount_out;
+
+    if min_token > token_bought {
+        return Err(ContractError::SwapMinError {
+            min: min_token,
+            available: token_bought,
+        });
+    }
+
+    for (tick, tick_info) in result.crossed_ticks {
+        TICKS.save(deps.storage, tick_key(tick), &tick_info)?;
+    }
+    POOL.save(deps.storage, &result.pool)?;
+
+    update_reserve(
+        deps.storage,
+        input_token_item,
+        input_amount_minus_protocol_fee,
+        true,
+    )?;
+    update_reserve(deps.storage, output_token_item, token_bought, false)?;
+
+    let mut msgs = match input_token.denom.clone() {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            &addr,
+            input_amount_minus_protocol_fee,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    // Send protocol fee to protocol fee recipient
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if fee_amount > Uint128::zero() {
+            msgs.push(get_fee_transfer_msg(
+                &info.sender,
+                &deps.api.addr_validate(&dev_wallet.address)?,
+                &input_token.denom,
+                fee_amount,
+            )?)
+        }
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    msgs.push(get_transfer_to_msg(
+        &recipient,
+        &output_token.denom,
+        token_bought,
+    )?);
+
+    let event = Event::new("hopers_swap")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("input_denom", denom_to_string(&input_token.denom))
+        .add_attribute("output_denom", denom_to_string(&output_token.denom))
+        .add_attribute("input_amount", input_amount)
+        .add_attribute("output_amount", token_bought)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount)
+        .add_attribute("sqrt_price", result.pool.sqrt_price.to_string())
+        .add_attribute("current_tick", result.pool.current_tick.to_string());
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", "swap"),
+            attr("input_amount", input_amount),
+            attr("token_bought", token_bought),
+            attr("protocol_fee_amount", protocol_fee_amount),
+            attr("sqrt_price", result.pool.sqrt_price.to_string()),
+            attr("current_tick", result.pool.current_tick.to_string()),
+        ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Info {} => to_binary(&query_info(deps)?),
+        QueryMsg::Position { position_id } => to_binary(&query_position(deps, position_id)?),
+        QueryMsg::Positions {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_positions(deps, owner, start_after, limit)?),
+        QueryMsg::Token1ForToken2Price { token1_amount } => {
+            to_binary(&query_token1_for_token2_price(deps, token1_amount)?)
+        }
+        QueryMsg::Token2ForToken1Price { token2_amount } => {
+            to_binary(&query_token2_for_token1_price(deps, token2_amount)?)
+        }
+        QueryMsg::Fee {} => to_binary(&query_fee(deps)?),
+    }
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+
+    Ok(InfoResponse {
+        token1_reserve: token1.reserve,
+        token1_denom: token1.denom,
+        token2_reserve: token2.reserve,
+        token2_denom: token2.denom,
+        price: pool.sqrt_price * pool.sqrt_price,
+        sqrt_price: pool.sqrt_price,
+        current_tick: pool.current_tick,
+        tick_spacing: pool.tick_spacing,
+        liquidity: pool.liquidity,
+    })
+}
+
+fn to_position_response(
+    deps: Deps,
+    pool: &Pool,
+    position_id: u64,
+    mut position: Position,
+) -> StdResult<PositionResponse> {
+    if !position.liquidity.is_zero() {
+        accrue_position_fees(deps.storage, pool, &mut position)?;
+    }
+    let (token1_amount, token2_amount) = get_amounts_for_liquidity(
+        pool.sqrt_price,
+        tick_to_sqrt_price(position.lower_tick)?,
+        tick_to_sqrt_price(position.upper_tick)?,
+        position.liquidity,
+        false,
+    )?;
+    Ok(PositionResponse {
+        position_id,
+        fees_owed1: position.tokens_owed1,
+        fees_owed2: position.tokens_owed2,
+        position,
+        token1_amount,
+        token2_amount,
+    })
+}
+
+pub fn query_position(deps: Deps, position_id: u64) -> StdResult<PositionResponse> {
+    let pool = POOL.load(deps.storage)?;
+    let position = positions().load(deps.storage, position_id)?;
+    to_position_response(deps, &pool, position_id, position)
+}
+
+pub fn query_positions(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PositionsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let pool = POOL.load(deps.storage)?;
+
+    let positions = positions()
+        .idx
+        .owner
+        .prefix(owner.as_bytes().to_vec())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| {
+            let (pk, position) = res?;
+            to_position_response(deps, &pool, parse_position_id(&pk)?, position)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PositionsResponse { positions })
+}
+
+fn parse_position_id(pk: &[u8]) -> StdResult<u64> {
+    pk.try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| StdError::generic_err("Invalid position key"))
+}
+
+fn simulate_swap(
+    deps: Deps,
+    input_token: TokenSelect,
+    input_amount: Uint128,
+) -> StdResult<Uint128> {
+    let fees = FEES.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+    let protocol_fee_amount = get_protocol_fee_amount(
+        input_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    let result = compute_swap(
+        deps.storage,
+        &pool,
+        &fees,
+        &input_token,
+        input_amount - protocol_fee_amount,
+    )
+    .map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(result.amount_out)
+}
+
+pub fn query_token1_for_token2_price(
+    deps: Deps,
+    token1_amount: Uint128,
+) -> StdResult<Token1ForToken2PriceResponse> {
+    let token2_amount = simulate_swap(deps, TokenSelect::Token1, token1_amount)?;
+    Ok(Token1ForToken2PriceResponse { token2_amount })
+}
+
+pub fn query_token2_for_token1_price(
+    deps: Deps,
+    token2_amount: Uint128,
+) -> StdResult<Token2ForToken1PriceResponse> {
+    let token1_amount = simulate_swap(deps, TokenSelect::Token2, token2_amount)?;
+    Ok(Token2ForToken1PriceResponse { token1_amount })
+}
+
+pub fn query_fee(deps: Deps) -> StdResult<FeeResponse> {
+    let fees = FEES.load(deps.storage)?;
+    let owner = OWNER.load(deps.storage)?.map(|o| o.into_string());
+
+    Ok(FeeResponse {
+        owner,
+        total_fee_percent: Decimal::from_ratio(
+            fees.fee_percent_numerator,
+            fees.fee_percent_denominator,
+        ),
+        lp_fee_percent: Decimal::from_ratio(
+            fees.lp_fee_percent_numerator,
+            fees.fee_percent_denominator,
+        ),
+        dev_wallet_lists: fees.dev_wallet_lists,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.contract,
+        });
+    }
+
+    if version.version != CONTRACT_VERSION {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.version,
+        });
+    }
+
+    Ok(Response::default())
+}
@@ -0,0 +1,77 @@
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("The sum of ratino is not the same as 1")]
+    WrongRatio {},
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid tick range: lower: {lower_tick}, upper: {upper_tick}")]
+    InvalidTickRange { lower_tick: i32, upper_tick: i32 },
+
+    #[error("Tick spacing must be positive")]
+    InvalidTickSpacing {},
+
+    #[error("Position {position_id} not found")]
+    PositionNotFound { position_id: u64 },
+
+    #[error("Min liquidity error: requested: {min_liquidity}, available: {liquidity_available}")]
+    MinLiquidityError {
+        min_liquidity: Uint128,
+        liquidity_available: Uint128,
+    },
+
+    #[error("Max token error: max_token: {max_token}, tokens_required: {tokens_required}")]
+    MaxTokenError {
+        max_token: Uint128,
+        tokens_required: Uint128,
+    },
+
+    #[error("Insufficient liquidity error: requested: {requested}, available: {available}")]
+    InsufficientLiquidityError {
+        requested: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Not enough liquidity in the pool to fill the swap")]
+    SwapLiquidityError {},
+
+    #[error("Min token1 error: requested: {requested}, available: {available}")]
+    MinToken1Error {
+        requested: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Min token2 error: requested: {requested}, available: {available}")]
+    MinToken2Error {
+        requested: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Incorrect native denom: provided: {provided}, required: {required}")]
+    IncorrectNativeDenom { provided: String, required: String },
+
+    #[error("Swap min error: min: {min}, available: {available}")]
+    SwapMinError { min: Uint128, available: Uint128 },
+
+    #[error("MsgExpirationError")]
+    MsgExpirationError {},
+
+    #[error("Total fee ({total_fee_percent}) percent is higher than max ({max_fee_percent})")]
+    FeesTooHigh {
+        max_fee_percent: Decimal,
+        total_fee_percent: Decimal,
+    },
+
+    #[error("InsufficientFunds")]
+    InsufficientFunds {},
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+}
@@ -0,0 +1,515 @@
+#![cfg(test)]
+
+use std::borrow::BorrowMut;
+
+use cosmwasm_std::{coins, Addr, Coin, Decimal, Decimal256, Empty, Uint128};
+use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Denom};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use std::str::FromStr;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, PositionResponse, PositionsResponse,
+    QueryMsg, Token1ForToken2PriceResponse, TokenSelect, WalletInfo,
+};
+
+const NATIVE_TOKEN_DENOM: &str = "juno";
+
+fn mock_app() -> App {
+    App::default()
+}
+
+pub fn contract_amm() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_migrate(crate::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn get_info(router: &App, contract_addr: &Addr) -> InfoResponse {
+    router
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Info {})
+        .unwrap()
+}
+
+fn get_fee(router: &App, contract_addr: &Addr) -> FeeResponse {
+    router
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Fee {})
+        .unwrap()
+}
+
+fn get_position(router: &App, contract_addr: &Addr, position_id: u64) -> PositionResponse {
+    router
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Position { position_id })
+        .unwrap()
+}
+
+fn instantiate_msg(token2_denom: Denom, owner: &Addr) -> InstantiateMsg {
+    InstantiateMsg {
+        token1_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        token2_denom,
+        initial_price: Decimal256::one(),
+        tick_spacing: 10,
+        owner: Some(owner.to_string()),
+        fee_percent_numerator: Uint128::new(1),
+        lp_fee_percent_numerator: Uint128::new(3),
+        fee_percent_denominator: Uint128::new(1000),
+        dev_wallet_lists: vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    }
+}
+
+fn create_amm(router: &mut App, owner: &Addr, token2_denom: Denom) -> Addr {
+    let amm_id = router.store_code(contract_amm());
+    let msg = instantiate_msg(token2_denom, owner);
+    router
+        .instantiate_contract(amm_id, owner.clone(), &msg, &[], "amm", None)
+        .unwrap()
+}
+
+// CreateCW20 create new cw20 with given initial balance belonging to owner
+fn create_cw20(
+    router: &mut App,
+    owner: &Addr,
+    name: String,
+    symbol: String,
+    balance: Uint128,
+) -> Cw20Contract {
+    // set up cw20 contract with some tokens
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name,
+        symbol,
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: balance,
+        }],
+        mint: None,
+        marketing: None,
+    };
+    let addr = router
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "CASH", None)
+        .unwrap();
+    Cw20Contract(addr)
+}
+
+fn bank_balance(router: &mut App, addr: &Addr, denom: String) -> Coin {
+    router
+        .wrap()
+        .query_balance(addr.to_string(), denom)
+        .unwrap()
+}
+
+// Sets up a juno/cw20 pool at price 1 with the owner funded and the allowance given
+fn setup() -> (App, Addr, Cw20Contract, Addr) {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(100_000_000, NATIVE_TOKEN_DENOM);
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let cw20_token = create_cw20(
+        &mut router,
+        &owner,
+        "token".to_string(),
+        "CWTOKEN".to_string(),
+        Uint128::new(100_000_000),
+    );
+    let amm_addr = create_amm(&mut router, &owner, Denom::Cw20(cw20_token.addr()));
+
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(100_000_000),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), cw20_token.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    (router, owner, cw20_token, amm_addr)
+}
+
+fn mint_position(
+    router: &mut App,
+    owner: &Addr,
+    amm_addr: &Addr,
+    lower_tick: i32,
+    upper_tick: i32,
+    max_token1: u128,
+    max_token2: u128,
+) -> Result<(), ContractError> {
+    let msg = ExecuteMsg::MintPosition {
+        lower_tick,
+        upper_tick,
+        max_token1: Uint128::new(max_token1),
+        max_token2: Uint128::new(max_token2),
+        min_liquidity: Uint128::new(1),
+        expiration: None,
+    };
+    let funds = if max_token1 > 0 {
+        coins(max_token1, NATIVE_TOKEN_DENOM)
+    } else {
+        vec![]
+    };
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &msg, &funds)
+        .map(|_| ())
+        .map_err(|err| err.downcast().unwrap())
+}
+
+fn swap_token1(router: &mut App, owner: &Addr, amm_addr: &Addr, amount: u128) -> Uint128 {
+    let price: Token1ForToken2PriceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            amm_addr,
+            &QueryMsg::Token1ForToken2Price {
+                token1_amount: Uint128::new(amount),
+            },
+        )
+        .unwrap();
+    let msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(amount),
+        min_output: price.token2_amount,
+        expiration: None,
+    };
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &msg,
+            &coins(amount, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == "wasm-hopers_swap")
+        .unwrap();
+    assert!(event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "output_amount" && attr.value == price.token2_amount.to_string()));
+    price.token2_amount
+}
+
+#[test]
+fn test_instantiate() {
+    let (mut router, owner, cw20_token, amm_addr) = setup();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.price, Decimal256::one());
+    assert_eq!(info.current_tick, 0);
+    assert_eq!(info.tick_spacing, 10);
+    assert_eq!(info.liquidity, Uint128::zero());
+
+    let fee = get_fee(&router, &amm_addr);
+    assert_eq!(fee.owner.unwrap(), owner.to_string());
+    assert_eq!(fee.total_fee_percent, Decimal::from_str("0.001").unwrap());
+    assert_eq!(fee.lp_fee_percent, Decimal::from_str("0.003").unwrap());
+
+    let amm_id = router.store_code(contract_amm());
+    let mut msg = instantiate_msg(Denom::Cw20(cw20_token.addr()), &owner);
+    msg.lp_fee_percent_numerator = Uint128::new(999);
+    let err = router
+        .instantiate_contract(amm_id, owner.clone(), &msg, &[], "amm", None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::FeesTooHigh {
+            max_fee_percent: Decimal::from_str("1").unwrap(),
+            total_fee_percent: Decimal::one(),
+        },
+        err
+    );
+
+    let mut msg = instantiate_msg(Denom::Cw20(cw20_token.addr()), &owner);
+    msg.tick_spacing = 0;
+    let err = router
+        .instantiate_contract(amm_id, owner, &msg, &[], "amm", None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidTickSpacing {}, err);
+}
+
+#[test]
+fn mint_and_decrease_position() {
+    let (mut router, owner, cw20_token, amm_addr) = setup();
+
+    // ticks must be aligned to the tick spacing
+    let err = mint_position(&mut router, &owner, &amm_addr, -105, 100, 1_000, 1_000).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidTickRange {
+            lower_tick: -105,
+            upper_tick: 100
+        },
+        err
+    );
+
+    mint_position(
+        &mut router,
+        &owner,
+        &amm_addr,
+        -1000,
+        1000,
+        1_000_000,
+        1_000_000,
+    )
+    .unwrap();
+
+    let position = get_position(&router, &amm_addr, 1);
+    assert_eq!(position.position.owner, owner);
+    // the range is symmetric around the current price, so both sides are fully used
+    assert!(position.token1_amount <= Uint128::new(1_000_000));
+    assert!(position.token1_amount >= Uint128::new(999_990));
+    assert_eq!(position.token1_amount, position.token2_amount);
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.liquidity, position.position.liquidity);
+    assert_eq!(info.token1_reserve, info.token2_reserve);
+
+    // unused native funds are refunded
+    let owner_balance = bank_balance(&mut router, &owner, NATIVE_TOKEN_DENOM.to_string());
+    assert_eq!(
+        owner_balance.amount,
+        Uint128::new(100_000_000) - info.token1_reserve
+    );
+    assert_eq!(
+        cw20_token.balance(&router, amm_addr.clone()).unwrap(),
+        info.token2_reserve
+    );
+
+    // only the owner can manage the position
+    let decrease_msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity: position.position.liquidity,
+        min_token1: Uint128::zero(),
+        min_token2: Uint128::zero(),
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(Addr::unchecked("bob"), amm_addr.clone(), &decrease_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &decrease_msg, &[])
+        .unwrap();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.liquidity, Uint128::zero());
+    // principal is rounded down, at most one unit of dust is left per side
+    assert!(info.token1_reserve <= Uint128::new(1));
+    assert!(info.token2_reserve <= Uint128::new(1));
+
+    // collecting with nothing owed closes the position
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CollectFees { position_id: 1 },
+            &[],
+        )
+        .unwrap();
+    let positions: PositionsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Positions {
+                owner: owner.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(positions.positions.is_empty());
+}
+
+#[test]
+fn out_of_range_position_is_single_sided() {
+    let (mut router, owner, _cw20_token, amm_addr) = setup();
+
+    // above the current price only token1 is needed
+    mint_position(&mut router, &owner, &amm_addr, 100, 200, 10_000, 0).unwrap();
+    let position = get_position(&router, &amm_addr, 1);
+    assert_eq!(position.token2_amount, Uint128::zero());
+    assert!(!position.token1_amount.is_zero());
+
+    // it does not add to the active liquidity
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.liquidity, Uint128::zero());
+
+    // below the current price only token2 is needed
+    mint_position(&mut router, &owner, &amm_addr, -200, -100, 0, 10_000).unwrap();
+    let position = get_position(&router, &amm_addr, 2);
+    assert_eq!(position.token1_amount, Uint128::zero());
+    assert!(!position.token2_amount.is_zero());
+}
+
+#[test]
+fn swap_and_collect_fees() {
+    let (mut router, owner, cw20_token, amm_addr) = setup();
+
+    mint_position(
+        &mut router,
+        &owner,
+        &amm_addr,
+        -1000,
+        1000,
+        1_000_000,
+        1_000_000,
+    )
+    .unwrap();
+    let info_before = get_info(&router, &amm_addr);
+    let cw20_before = cw20_token.balance(&router, owner.clone()).unwrap();
+
+    let token_bought = swap_token1(&mut router, &owner, &amm_addr, 10_000);
+    // price is slightly below 1 after fees and price impact
+    assert!(token_bought < Uint128::new(9_960));
+    assert!(token_bought > Uint128::new(9_800));
+    assert_eq!(
+        cw20_token.balance(&router, owner.clone()).unwrap(),
+        cw20_before + token_bought
+    );
+
+    // protocol fee is sent to the dev wallet
+    let dev_balance = bank_balance(
+        &mut router,
+        &Addr::unchecked("dev"),
+        NATIVE_TOKEN_DENOM.to_string(),
+    );
+    assert_eq!(dev_balance.amount, Uint128::new(10));
+
+    let info = get_info(&router, &amm_addr);
+    assert!(info.sqrt_price < info_before.sqrt_price);
+    assert!(info.current_tick < 0);
+    assert_eq!(
+        info.token1_reserve,
+        info_before.token1_reserve + Uint128::new(9_990)
+    );
+    assert_eq!(
+        info.token2_reserve,
+        info_before.token2_reserve - token_bought
+    );
+
+    // the single position earned all the LP fees, 0.3% of the input after protocol fee
+    let position = get_position(&router, &amm_addr, 1);
+    assert!(position.fees_owed1 >= Uint128::new(29));
+    assert!(position.fees_owed1 <= Uint128::new(30));
+    assert_eq!(position.fees_owed2, Uint128::zero());
+
+    let owner_balance = bank_balance(&mut router, &owner, NATIVE_TOKEN_DENOM.to_string());
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CollectFees { position_id: 1 },
+            &[],
+        )
+        .unwrap();
+    let new_owner_balance = bank_balance(&mut router, &owner, NATIVE_TOKEN_DENOM.to_string());
+    assert_eq!(
+        new_owner_balance.amount,
+        owner_balance.amount + position.fees_owed1
+    );
+
+    let position = get_position(&router, &amm_addr, 1);
+    assert_eq!(position.fees_owed1, Uint128::zero());
+}
+
+#[test]
+fn swap_crosses_ticks() {
+    let (mut router, owner, _cw20_token, amm_addr) = setup();
+
+    mint_position(
+        &mut router,
+        &owner,
+        &amm_addr,
+        -1000,
+        1000,
+        100_000,
+        100_000,
+    )
+    .unwrap();
+    mint_position(&mut router, &owner, &amm_addr, -100, 100, 100_000, 100_000).unwrap();
+    let narrow = get_position(&router, &amm_addr, 2);
+    let wide = get_position(&router, &amm_addr, 1);
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(
+        info.liquidity,
+        narrow.position.liquidity + wide.position.liquidity
+    );
+
+    // moves the price below the narrow range
+    swap_token1(&mut router, &owner, &amm_addr, 150_000);
+    let info = get_info(&router, &amm_addr);
+    assert!(info.current_tick < -100);
+    assert_eq!(info.liquidity, wide.position.liquidity);
+
+    // the narrow position is now fully in token1 and both positions earned fees
+    let narrow = get_position(&router, &amm_addr, 2);
+    assert_eq!(narrow.token2_amount, Uint128::zero());
+    assert!(!narrow.fees_owed1.is_zero());
+    assert!(!get_position(&router, &amm_addr, 1).fees_owed1.is_zero());
+
+    // swapping back crosses into the narrow range again
+    let msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token2,
+        input_amount: Uint128::new(150_000),
+        min_output: Uint128::zero(),
+        expiration: None,
+    };
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &msg, &[])
+        .unwrap();
+    let info = get_info(&router, &amm_addr);
+    assert!(info.current_tick >= -100);
+    assert_eq!(
+        info.liquidity,
+        narrow.position.liquidity + wide.position.liquidity
+    );
+
+    // a swap larger than the liquidity in range fails
+    let msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(10_000_000),
+        min_output: Uint128::zero(),
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &msg,
+            &coins(10_000_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::SwapLiquidityError {}, err);
+}
@@ -0,0 +1,488 @@
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint256, Uint512};
+use std::convert::TryFrom;
+
+// Price of token1 in token2 at a tick is 1.0001^tick
+pub const MIN_TICK: i32 = -200_000;
+pub const MAX_TICK: i32 = 200_000;
+
+pub const FEE_GROWTH_SCALE: Uint256 = Uint256::from_u128(1_000_000_000_000_000_000);
+
+// Ticks per doubling of the sqrt price, 2 / log2(1.0001), scaled by 2^32
+const TICKS_PER_LOG2_X32: i128 = 59_543_866_431_248;
+
+fn tick_base() -> Decimal256 {
+    Decimal256::from_ratio(10_001u128, 10_000u128)
+}
+
+fn to_decimal(amount: Uint128) -> Decimal256 {
+    Decimal256::from_ratio(amount, 1u128)
+}
+
+fn checked_mul(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    a.checked_mul(b).map_err(StdError::overflow)
+}
+
+fn checked_div(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    a.checked_div(b)
+        .map_err(|_| StdError::generic_err("Decimal division error"))
+}
+
+fn to_uint(value: Decimal256, round_up: bool) -> StdResult<Uint128> {
+    let value = if round_up {
+        value.ceil()
+    } else {
+        value.floor()
+    };
+    let amount = value.atomics() / Decimal256::one().atomics();
+    Ok(Uint128::try_from(amount)?)
+}
+
+pub fn tick_to_sqrt_price(tick: i32) -> StdResult<Decimal256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(StdError::generic_err(format!("Tick {} out of range", tick)));
+    }
+    let price = tick_base()
+        .checked_pow(tick.unsigned_abs())
+        .map_err(StdError::overflow)?;
+    let price = if tick < 0 {
+        checked_div(Decimal256::one(), price)?
+    } else {
+        price
+    };
+    Ok(price.sqrt())
+}
+
+// log2 of the sqrt price scaled by 2^32. The integer part is the position of the most
+// significant bit, each bit of the fraction is found by squaring the normalized value
+fn log2_x32(sqrt_price: Decimal256) -> StdResult<i128> {
+    // Q64.64 fixed point, the sqrt price of MAX_TICK takes less than 80 bits
+    let ratio = sqrt_price
+        .atomics()
+        .checked_multiply_ratio(Uint256::from(1u128 << 64), Decimal256::one().atomics())
+        .map_err(|_| StdError::generic_err("Price out of range"))?;
+    let ratio = Uint128::try_from(ratio)?.u128();
+    if ratio == 0 {
+        return Err(StdError::generic_err("Price out of range"));
+    }
+
+    let msb = 127 - ratio.leading_zeros();
+    // Q1.63 in [1, 2)
+    let mut r = if msb >= 63 {
+        ratio >> (msb - 63)
+    } else {
+        ratio << (63 - msb)
+    };
+    let mut log2 = (msb as i128 - 64) << 32;
+    for bit in (0..32).rev() {
+        r = (r * r) >> 63;
+        if r >= 1 << 64 {
+            r >>= 1;
+            log2 += 1 << bit;
+        }
+    }
+    Ok(log2)
+}
+
+/// Returns the greatest tick whose sqrt price is lower than or equal to the given one
+pub fn sqrt_price_to_tick(sqrt_price: Decimal256) -> StdResult<i32> {
+    if sqrt_price < tick_to_sqrt_price(MIN_TICK)? || sqrt_price > tick_to_sqrt_price(MAX_TICK)? {
+        return Err(StdError::generic_err("Price out of range"));
+    }
+
+    // The estimate is off by less than one tick, so only its neighbour is checked
+    let estimate = (log2_x32(sqrt_price)? * TICKS_PER_LOG2_X32) >> 64;
+    let tick = (estimate as i32).clamp(MIN_TICK, MAX_TICK);
+    if tick_to_sqrt_price(tick)? > sqrt_price {
+        Ok(tick - 1)
+    } else if tick < MAX_TICK && tick_to_sqrt_price(tick + 1)? <= sqrt_price {
+        Ok(tick + 1)
+    } else {
+        Ok(tick)
+    }
+}
+
+fn sort(a: Decimal256, b: Decimal256) -> (Decimal256, Decimal256) {
+    if a > b {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+/// Amount of token1 held by `liquidity` between the two sqrt prices: L * (b - a) / (a * b)
+pub fn get_amount1_delta(
+    liquidity: Uint128,
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    let (a, b) = sort(sqrt_price_a, sqrt_price_b);
+    if a.is_zero() {
+        return Err(StdError::generic_err("Sqrt price must be positive"));
+    }
+    let amount = checked_mul(to_decimal(liquidity), b - a)?;
+    let amount = checked_div(checked_div(amount, a)?, b)?;
+    to_uint(amount, round_up)
+}
+
+/// Amount of token2 held by `liquidity` between the two sqrt prices: L * (b - a)
+pub fn get_amount2_delta(
+    liquidity: Uint128,
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    let (a, b) = sort(sqrt_price_a, sqrt_price_b);
+    let amount = checked_mul(to_decimal(liquidity), b - a)?;
+    to_uint(amount, round_up)
+}
+
+pub fn get_liquidity_for_amount1(
+    amount: Uint128,
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+) -> StdResult<Uint128> {
+    let (a, b) = sort(sqrt_price_a, sqrt_price_b);
+    let liquidity = checked_mul(checked_mul(to_decimal(amount), a)?, b)?;
+    to_uint(checked_div(liquidity, b - a)?, false)
+}
+
+pub fn get_liquidity_for_amount2(
+    amount: Uint128,
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+) -> StdResult<Uint128> {
+    let (a, b) = sort(sqrt_price_a, sqrt_price_b);
+    to_uint(checked_div(to_decimal(amount), b - a)?, false)
+}
+
+/// Returns the amounts of token1 and token2 matching `liquidity` over the range
+/// `[sqrt_price_lower, sqrt_price_upper)` at the current sqrt price
+pub fn get_amounts_for_liquidity(
+    sqrt_price: Decimal256,
+    sqrt_price_lower: Decimal256,
+    sqrt_price_upper: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<(Uint128, Uint128)> {
+    if sqrt_price <= sqrt_price_lower {
+        Ok((
+            get_amount1_delta(liquidity, sqrt_price_lower, sqrt_price_upper, round_up)?,
+            Uint128::zero(),
+        ))
+    } else if sqrt_price < sqrt_price_upper {
+        Ok((
+            get_amount1_delta(liquidity, sqrt_price, sqrt_price_upper, round_up)?,
+            get_amount2_delta(liquidity, sqrt_price_lower, sqrt_price, round_up)?,
+        ))
+    } else {
+        Ok((
+            Uint128::zero(),
+            get_amount2_delta(liquidity, sqrt_price_lower, sqrt_price_upper, round_up)?,
+        ))
+    }
+}
+
+/// Returns the largest liquidity that can be minted over the range with at most the given amounts
+pub fn get_liquidity_for_amounts(
+    sqrt_price: Decimal256,
+    sqrt_price_lower: Decimal256,
+    sqrt_price_upper: Decimal256,
+    token1_amount: Uint128,
+    token2_amount: Uint128,
+) -> StdResult<Uint128> {
+    if sqrt_price <= sqrt_price_lower {
+        get_liquidity_for_amount1(token1_amount, sqrt_price_lower, sqrt_price_upper)
+    } else if sqrt_price < sqrt_price_upper {
+        let liquidity1 = get_liquidity_for_amount1(token1_amount, sqrt_price, sqrt_price_upper)?;
+        let liquidity2 = get_liquidity_for_amount2(token2_amount, sqrt_price_lower, sqrt_price)?;
+        Ok(std::cmp::min(liquidity1, liquidity2))
+    } else {
+        get_liquidity_for_amount2(token2_amount, sqrt_price_lower, sqrt_price_upper)
+    }
+}
+
+// a * b / denominator, rounded up
+fn mul_div_round_up(a: Uint256, b: Uint256, denominator: Uint256) -> StdResult<Uint256> {
+    let product = a.full_mul(b);
+    let denominator = Uint512::from(denominator);
+    let quotient = product
+        .checked_div(denominator)
+        .map_err(StdError::divide_by_zero)?;
+    let remainder = product
+        .checked_rem(denominator)
+        .map_err(StdError::divide_by_zero)?;
+    let quotient = if remainder.is_zero() {
+        quotient
+    } else {
+        quotient + Uint512::one()
+    };
+    Ok(Uint256::try_from(quotient)?)
+}
+
+// The next price is rounded against the trader: up when token1 pushes it down and down
+// when token2 pushes it up, so the output never exceeds what the input pays for
+fn get_next_sqrt_price_from_input(
+    sqrt_price: Decimal256,
+    liquidity: Uint128,
+    amount_in: Uint128,
+    token1_in: bool,
+) -> StdResult<Decimal256> {
+    let scale = Decimal256::one().atomics();
+    let price = sqrt_price.atomics();
+    let liquidity = Uint256::from(liquidity);
+    let amount_in = Uint256::from(amount_in);
+    if token1_in {
+        // L * P / (L + amount_in * P)
+        let numerator = liquidity.checked_mul(price).map_err(StdError::overflow)?;
+        let denominator = liquidity
+            .checked_mul(scale)
+            .map_err(StdError::overflow)?
+            .checked_add(amount_in.checked_mul(price).map_err(StdError::overflow)?)
+            .map_err(StdError::overflow)?;
+        Ok(Decimal256::new(mul_div_round_up(
+            numerator,
+            scale,
+            denominator,
+        )?))
+    } else {
+        // P + amount_in / L
+        let delta = amount_in
+            .checked_multiply_ratio(scale, liquidity)
+            .map_err(|_| StdError::generic_err("Decimal division error"))?;
+        Ok(Decimal256::new(
+            price.checked_add(delta).map_err(StdError::overflow)?,
+        ))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SwapStep {
+    pub sqrt_price_next: Decimal256,
+    pub amount_in: Uint128,
+    pub amount_out: Uint128,
+    pub fee_amount: Uint128,
+}
+
+/// Swaps `amount_remaining` (including the LP fee) within a single tick range, stopping
+/// at `sqrt_price_target` if the input is large enough to reach it
+pub fn compute_swap_step(
+    sqrt_price: Decimal256,
+    sqrt_price_target: Decimal256,
+    liquidity: Uint128,
+    amount_remaining: Uint128,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+) -> StdResult<SwapStep> {
+    let token1_in = sqrt_price_target < sqrt_price;
+    let fee_complement = fee_percent_denominator
+        .checked_sub(fee_percent_numerator)
+        .map_err(StdError::overflow)?;
+    let amount_remaining_less_fee =
+        amount_remaining.multiply_ratio(fee_complement, fee_percent_denominator);
+
+    let amount_in_to_target = if token1_in {
+        get_amount1_delta(liquidity, sqrt_price_target, sqrt_price, true)?
+    } else {
+        get_amount2_delta(liquidity, sqrt_price, sqrt_price_target, true)?
+    };
+
+    let (sqrt_price_next, amount_in) = if amount_remaining_less_fee >= amount_in_to_target {
+        (sqrt_price_target, amount_in_to_target)
+    } else {
+        let sqrt_price_next = get_next_sqrt_price_from_input(
+            sqrt_price,
+            liquidity,
+            amount_remaining_less_fee,
+            token1_in,
+        )?;
+        // Rounding must never move the price past the target
+        let sqrt_price_next = if token1_in {
+            std::cmp::max(sqrt_price_next, sqrt_price_target)
+        } else {
+            std::cmp::min(sqrt_price_next, sqrt_price_target)
+        };
+        (sqrt_price_next, amount_remaining_less_fee)
+    };
+
+    let amount_out = if token1_in {
+        get_amount2_delta(liquidity, sqrt_price_next, sqrt_price, false)?
+    } else {
+        get_amount1_delta(liquidity, sqrt_price, sqrt_price_next, false)?
+    };
+
+    let fee_amount = if sqrt_price_next != sqrt_price_target {
+        amount_remaining - amount_in
+    } else {
+        let fee = Uint256::from(amount_in)
+            .checked_mul(Uint256::from(fee_percent_numerator))
+            .map_err(StdError::overflow)?;
+        let fee = to_uint(
+            Decimal256::from_ratio(fee, Uint256::from(fee_complement)),
+            true,
+        )?;
+        std::cmp::min(fee, amount_remaining - amount_in)
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// Fee growth per unit of liquidity for the given fee amount
+pub fn get_fee_growth(fee_amount: Uint128, liquidity: Uint128) -> StdResult<Uint256> {
+    if liquidity.is_zero() {
+        return Ok(Uint256::zero());
+    }
+    Uint256::from(fee_amount)
+        .checked_multiply_ratio(FEE_GROWTH_SCALE, Uint256::from(liquidity))
+        .map_err(|_| StdError::generic_err("Fee growth overflow"))
+}
+
+/// Fees earned by `liquidity` for a fee growth delta
+pub fn get_fees_earned(fee_growth_delta: Uint256, liquidity: Uint128) -> StdResult<Uint128> {
+    let fees = fee_growth_delta
+        .checked_multiply_ratio(Uint256::from(liquidity), FEE_GROWTH_SCALE)
+        .map_err(|_| StdError::generic_err("Fee overflow"))?;
+    Ok(Uint128::try_from(fees)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_tick_to_sqrt_price() {
+        assert_eq!(tick_to_sqrt_price(0).unwrap(), Decimal256::one());
+
+        // 1.0001^20000 ~= 7.3883
+        let sqrt_price = tick_to_sqrt_price(20_000).unwrap();
+        let price = sqrt_price * sqrt_price;
+        assert!(price > Decimal256::from_str("7.388").unwrap());
+        assert!(price < Decimal256::from_str("7.389").unwrap());
+
+        let sqrt_price = tick_to_sqrt_price(-20_000).unwrap();
+        let price = sqrt_price * sqrt_price;
+        assert!(price > Decimal256::from_str("0.1353").unwrap());
+        assert!(price < Decimal256::from_str("0.1354").unwrap());
+
+        tick_to_sqrt_price(MAX_TICK + 1).unwrap_err();
+        tick_to_sqrt_price(MIN_TICK - 1).unwrap_err();
+    }
+
+    #[test]
+    fn test_sqrt_price_to_tick() {
+        for tick in [-150_000, -60, -1, 0, 1, 60, 12_345, 150_000] {
+            let sqrt_price = tick_to_sqrt_price(tick).unwrap();
+            assert_eq!(sqrt_price_to_tick(sqrt_price).unwrap(), tick);
+        }
+
+        let between = tick_to_sqrt_price(10).unwrap() + Decimal256::from_str("0.0000001").unwrap();
+        assert_eq!(sqrt_price_to_tick(between).unwrap(), 10);
+
+        // just below a tick price rounds down to the previous tick
+        for tick in [MIN_TICK + 1, -99_999, -7, 1, 7, 99_999, MAX_TICK] {
+            let below = tick_to_sqrt_price(tick).unwrap() - Decimal256::raw(1);
+            assert_eq!(sqrt_price_to_tick(below).unwrap(), tick - 1);
+        }
+        assert_eq!(
+            sqrt_price_to_tick(tick_to_sqrt_price(MIN_TICK).unwrap()).unwrap(),
+            MIN_TICK
+        );
+        assert_eq!(
+            sqrt_price_to_tick(tick_to_sqrt_price(MAX_TICK).unwrap()).unwrap(),
+            MAX_TICK
+        );
+        sqrt_price_to_tick(tick_to_sqrt_price(MAX_TICK).unwrap() + Decimal256::raw(1)).unwrap_err();
+    }
+
+    #[test]
+    fn test_next_sqrt_price_rounding() {
+        let liquidity = Uint128::new(1_000_000);
+
+        // 1_000_000 / 1_000_003 = 0.999997000008999973000..., rounded up
+        let next =
+            get_next_sqrt_price_from_input(Decimal256::one(), liquidity, Uint128::new(3), true)
+                .unwrap();
+        assert_eq!(next, Decimal256::from_str("0.999997000008999974").unwrap());
+
+        // 1 + 1 / 3_000_000 = 1.000000333333333333..., rounded down
+        let next = get_next_sqrt_price_from_input(
+            Decimal256::one(),
+            Uint128::new(3_000_000),
+            Uint128::new(1),
+            false,
+        )
+        .unwrap();
+        assert_eq!(next, Decimal256::from_str("1.000000333333333333").unwrap());
+    }
+
+    #[test]
+    fn test_amounts_for_liquidity() {
+        let lower = Decimal256::from_str("0.5").unwrap();
+        let upper = Decimal256::from_str("2").unwrap();
+        let liquidity = Uint128::new(1_000_000);
+
+        // below the range only token1 is needed
+        let (amount1, amount2) =
+            get_amounts_for_liquidity(lower, lower, upper, liquidity, true).unwrap();
+        assert_eq!(amount1, Uint128::new(1_500_000));
+        assert_eq!(amount2, Uint128::zero());
+
+        // in range both tokens are needed
+        let (amount1, amount2) =
+            get_amounts_for_liquidity(Decimal256::one(), lower, upper, liquidity, true).unwrap();
+        assert_eq!(amount1, Uint128::new(500_000));
+        assert_eq!(amount2, Uint128::new(500_000));
+
+        let minted = get_liquidity_for_amounts(
+            Decimal256::one(),
+            lower,
+            upper,
+            Uint128::new(500_000),
+            Uint128::new(600_000),
+        )
+        .unwrap();
+        assert_eq!(minted, liquidity);
+    }
+
+    #[test]
+    fn test_compute_swap_step() {
+        let liquidity = Uint128::new(1_000_000);
+
+        // partial step for token1 in, 1% fee
+        let step = compute_swap_step(
+            Decimal256::one(),
+            Decimal256::from_str("0.5").unwrap(),
+            liquidity,
+            Uint128::new(1_000),
+            Uint128::new(1),
+            Uint128::new(100),
+        )
+        .unwrap();
+        assert_eq!(step.amount_in, Uint128::new(990));
+        assert_eq!(step.fee_amount, Uint128::new(10));
+        assert_eq!(step.amount_out, Uint128::new(989));
+        assert!(step.sqrt_price_next < Decimal256::one());
+
+        // enough input to reach the target
+        let target = Decimal256::from_str("1.001").unwrap();
+        let step = compute_swap_step(
+            Decimal256::one(),
+            target,
+            liquidity,
+            Uint128::new(10_000),
+            Uint128::new(1),
+            Uint128::new(100),
+        )
+        .unwrap();
+        assert_eq!(step.sqrt_price_next, target);
+        assert_eq!(step.amount_in, Uint128::new(1_000));
+        assert_eq!(step.fee_amount, Uint128::new(11));
+        assert_eq!(step.amount_out, Uint128::new(999));
+    }
+}
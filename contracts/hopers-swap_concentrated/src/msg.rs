@@ -0,0 +1,157 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::QueryResponses;
+
+use cosmwasm_std::{Decimal, Decimal256, Uint128};
+
+use cw20::{Denom, Expiration};
+
+use crate::state::Position;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub token1_denom: Denom,
+    pub token2_denom: Denom,
+    /// Price of token1 denominated in token2 the pool starts at
+    pub initial_price: Decimal256,
+    pub tick_spacing: i32,
+    pub owner: Option<String>,
+    // NOTE: Fees percents are out of 100 e.g., 1 = 1%
+    pub fee_percent_numerator: Uint128,
+    pub lp_fee_percent_numerator: Uint128,
+    pub fee_percent_denominator: Uint128,
+    pub dev_wallet_lists: Vec<WalletInfo>,
+}
+
+#[cw_serde]
+pub enum TokenSelect {
+    Token1,
+    Token2,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Opens a new position providing liquidity between the two ticks
+    MintPosition {
+        lower_tick: i32,
+        upper_tick: i32,
+        max_token1: Uint128,
+        max_token2: Uint128,
+        min_liquidity: Uint128,
+        expiration: Option<Expiration>,
+    },
+    IncreaseLiquidity {
+        position_id: u64,
+        max_token1: Uint128,
+        max_token2: Uint128,
+        min_liquidity: Uint128,
+        expiration: Option<Expiration>,
+    },
+    DecreaseLiquidity {
+        position_id: u64,
+        liquidity: Uint128,
+        min_token1: Uint128,
+        min_token2: Uint128,
+        expiration: Option<Expiration>,
+    },
+    /// Sends the swap fees earned by the position to its owner
+    CollectFees { position_id: u64 },
+    Swap {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        min_output: Uint128,
+        expiration: Option<Expiration>,
+    },
+    SwapAndSendTo {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        recipient: String,
+        min_token: Uint128,
+        expiration: Option<Expiration>,
+    },
+    UpdateConfig {
+        owner: Option<String>,
+        fee_percent_numerator: Uint128,
+        lp_fee_percent_numerator: Uint128,
+        fee_percent_denominator: Uint128,
+        dev_wallet_lists: Vec<WalletInfo>,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(InfoResponse)]
+    Info {},
+    #[returns(PositionResponse)]
+    Position { position_id: u64 },
+    #[returns(PositionsResponse)]
+    Positions {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Token1ForToken2PriceResponse)]
+    Token1ForToken2Price { token1_amount: Uint128 },
+    #[returns(Token2ForToken1PriceResponse)]
+    Token2ForToken1Price { token2_amount: Uint128 },
+    #[returns(FeeResponse)]
+    Fee {},
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub token1_reserve: Uint128,
+    pub token1_denom: Denom,
+    pub token2_reserve: Uint128,
+    pub token2_denom: Denom,
+    pub price: Decimal256,
+    pub sqrt_price: Decimal256,
+    pub current_tick: i32,
+    pub tick_spacing: i32,
+    pub liquidity: Uint128,
+}
+
+#[cw_serde]
+pub struct PositionResponse {
+    pub position_id: u64,
+    pub position: Position,
+    /// Amounts the position's liquidity is currently worth
+    pub token1_amount: Uint128,
+    pub token2_amount: Uint128,
+    /// Fees that would be sent by `CollectFees`
+    pub fees_owed1: Uint128,
+    pub fees_owed2: Uint128,
+}
+
+#[cw_serde]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
+}
+
+#[cw_serde]
+pub struct FeeResponse {
+    pub owner: Option<String>,
+    pub total_fee_percent: Decimal,
+    pub lp_fee_percent: Decimal,
+    pub dev_wallet_lists: Vec<WalletInfo>,
+}
+
+#[cw_serde]
+pub struct Token1ForToken2PriceResponse {
+    pub token2_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Token2ForToken1PriceResponse {
+    pub token1_amount: Uint128,
+}
+
+#[cw_serde]
+#[derive(Eq)]
+pub struct WalletInfo {
+    pub address: String,
+    pub ratio: Decimal,
+}
@@ -0,0 +1,103 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Decimal256, Uint128, Uint256};
+use cw20::Denom;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+
+use crate::msg::WalletInfo;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Token {
+    pub reserve: Uint128,
+    pub denom: Denom,
+}
+
+pub const TOKEN1: Item<Token> = Item::new("token1");
+pub const TOKEN2: Item<Token> = Item::new("token2");
+
+pub const OWNER: Item<Option<Addr>> = Item::new("owner");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Fees {
+    pub dev_wallet_lists: Vec<WalletInfo>,
+    pub fee_percent_numerator: Uint128,
+    pub lp_fee_percent_numerator: Uint128,
+    pub fee_percent_denominator: Uint128,
+}
+
+pub const FEES: Item<Fees> = Item::new("fees");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Pool {
+    pub tick_spacing: i32,
+    /// Square root of the token1 price denominated in token2
+    pub sqrt_price: Decimal256,
+    pub current_tick: i32,
+    /// Liquidity of the positions whose range contains the current tick
+    pub liquidity: Uint128,
+    /// Accumulated LP fees per unit of liquidity, scaled by `FEE_GROWTH_SCALE`.
+    /// These values wrap around on overflow, only their differences are meaningful.
+    pub fee_growth_global1: Uint256,
+    pub fee_growth_global2: Uint256,
+}
+
+pub const POOL: Item<Pool> = Item::new("pool");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TickInfo {
+    pub liquidity_gross: Uint128,
+    /// Liquidity added when the price crosses this tick upwards
+    pub liquidity_starting: Uint128,
+    /// Liquidity removed when the price crosses this tick upwards
+    pub liquidity_ending: Uint128,
+    pub fee_growth_outside1: Uint256,
+    pub fee_growth_outside2: Uint256,
+}
+
+// Signed integer keys don't sort in order, so ticks are keyed with the sign bit flipped
+pub const TICKS: Map<u32, TickInfo> = Map::new("ticks");
+
+pub fn tick_key(tick: i32) -> u32 {
+    (tick as u32) ^ 0x8000_0000
+}
+
+pub fn key_to_tick(key: u32) -> i32 {
+    (key ^ 0x8000_0000) as i32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Position {
+    pub owner: Addr,
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity: Uint128,
+    pub fee_growth_inside1_last: Uint256,
+    pub fee_growth_inside2_last: Uint256,
+    pub tokens_owed1: Uint128,
+    pub tokens_owed2: Uint128,
+}
+
+pub const NEXT_POSITION_ID: Item<u64> = Item::new("next_position_id");
+
+pub struct PositionIndicies<'a> {
+    pub owner: MultiIndex<'a, (Vec<u8>, Vec<u8>), Position>,
+}
+
+impl<'a> IndexList<Position> for PositionIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Position>> + '_> {
+        let v: Vec<&dyn Index<Position>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn positions<'a>() -> IndexedMap<'a, u64, Position, PositionIndicies<'a>> {
+    let indexes = PositionIndicies {
+        owner: MultiIndex::new(
+            |d: &Position, k| (d.owner.as_bytes().to_vec(), k),
+            "positions",
+            "positions__owner",
+        ),
+    };
+    IndexedMap::new("positions", indexes)
+}
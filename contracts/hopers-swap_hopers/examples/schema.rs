@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
-use hopers_swap_hopers::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg};
+use hopers_swap_hopers::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg, SudoMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
-        migrate: MigrateMsg
+        migrate: MigrateMsg,
+        sudo: SudoMsg
     }
 }
\ No newline at end of file
@@ -1,32 +1,73 @@
 use cosmwasm_std::{
-    attr, entry_point, to_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps, DepsMut,
-    Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128,
-    WasmMsg,
+    attr, entry_point, to_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, Event, MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult,
+    Storage, SubMsg, SubMsgResult, Timestamp, Uint128, WasmMsg,
 };
 use cw0::parse_reply_instantiate_data;
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Denom::Cw20;
 use cw20::{Cw20ExecuteMsg, Denom, Expiration, MinterResponse};
 use cw20_base::contract::query_balance;
+use cw_storage_plus::Bound;
+use osmosis_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use osmosis_std::types::ibc::applications::transfer::v1::{MsgTransfer, MsgTransferResponse};
+use std::convert::TryFrom;
 use std::str::FromStr;
 
+use hopers_denom::{
+    denom_to_string, get_amount_for_denom, get_bank_transfer_to_msg, get_cw20_transfer_from_msg,
+    get_cw20_transfer_to_msg, get_fee_transfer_msg,
+};
+
+use hopers_allowlist::{
+    is_allowlisted, query_allowlist_config, query_is_allowlisted, update_allowlist,
+    update_allowlist_config,
+};
+
 use crate::error::ContractError;
 use crate::msg::{
-    ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, MigrateMsg, QueryMsg,
-    Token1ForToken2PriceResponse, Token2ForToken1PriceResponse, TokenSelect, WalletInfo,
+    ExecuteMsg, FeeResponse, HookInfo, HooksResponse, IbcLifecycleComplete, IbcRefundsResponse,
+    InfoResponse, InstantiateMsg, LockInfo, LocksResponse, MigrateMsg, OutputPoolExecuteMsg,
+    PoolHookExecuteMsg, PoolHookMsg, PositionResponse, QueryMsg, ReferralEarningsResponse,
+    ShareResponse, SudoMsg, Token1ForToken2PriceResponse, Token2ForToken1PriceResponse,
+    TokenSelect, TotalLockedResponse, WalletInfo,
+};
+use crate::state::{
+    Fees, Hook, IbcTransfer, Lock, Token, BATCH_LP_SUPPLY, BURN_FEE_INFO, FEES, HOOKS, IBC_REFUNDS,
+    IBC_TRANSFERS, LOCKS, LOCK_COUNT, LP_TOKEN, MAX_REFERRAL_COMMISSION, OWNER,
+    PENDING_IBC_TRANSFERS, REFERRAL_EARNINGS, TOKEN1, TOKEN2, TOTAL_LOCKED,
 };
-use crate::state::{Fees, Token, BURN_FEE_INFO, FEES, LP_TOKEN, OWNER, TOKEN1, TOKEN2};
 
 // Version info for migration info
 pub const CONTRACT_NAME: &str = "crates.io:wasmswap";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const INSTANTIATE_LP_TOKEN_REPLY_ID: u64 = 0;
+const HOOK_REPLY_ID: u64 = 1;
+const IBC_TRANSFER_REPLY_ID: u64 = 2;
+
+// Every hook runs on each pool action, so their number and gas are bounded
+const MAX_HOOKS: usize = 10;
+const MAX_HOOK_GAS_LIMIT: u64 = 1_000_000;
+
+// Pagination of the locks query
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 //const FEE_SCALE_FACTOR: Uint128 = Uint128::new(10_000);
 const MAX_FEE_PERCENT: &str = "1";
 //const FEE_DECIMAL_PRECISION: Uint128 = Uint128::new(10u128.pow(20));
 
+/// Where the output of a swap is sent to
+pub enum SwapRecipient {
+    Address(String),
+    Ibc {
+        channel_id: String,
+        remote_recipient: String,
+        timeout_seconds: u64,
+    },
+}
+
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -130,7 +171,25 @@ pub fn execute(
             token1_amount,
             max_token2,
             expiration,
+            None,
         ),
+        ExecuteMsg::AddLiquidityLocked {
+            token1_amount,
+            min_liquidity,
+            max_token2,
+            expiration,
+            unlock_time,
+        } => execute_add_liquidity(
+            deps,
+            &info,
+            env,
+            min_liquidity,
+            token1_amount,
+            max_token2,
+            expiration,
+            Some(unlock_time),
+        ),
+        ExecuteMsg::WithdrawLocked { lock_id } => execute_withdraw_locked(deps, info, env, lock_id),
         ExecuteMsg::RemoveLiquidity {
             amount,
             min_token1,
@@ -142,16 +201,19 @@ pub fn execute(
             input_amount,
             min_output,
             expiration,
-            ..
+            referrer,
+            referral_commission,
         } => execute_swap(
             deps,
             &info,
             input_amount,
             env,
             input_token,
-            info.sender.to_string(),
+            SwapRecipient::Address(info.sender.to_string()),
             min_output,
             expiration,
+            referrer,
+            referral_commission,
         ),
         ExecuteMsg::PassThroughSwap {
             output_amm_address,
@@ -175,16 +237,52 @@ pub fn execute(
             recipient,
             min_token,
             expiration,
+            referrer,
+            referral_commission,
         } => execute_swap(
             deps,
             &info,
             input_amount,
             env,
             input_token,
-            recipient,
+            SwapRecipient::Address(recipient),
+            min_token,
+            expiration,
+            referrer,
+            referral_commission,
+        ),
+        ExecuteMsg::SwapAndIbcTransfer {
+            input_token,
+            input_amount,
+            min_token,
+            ibc_channel,
+            remote_recipient,
+            timeout_seconds,
+            expiration,
+            referrer,
+            referral_commission,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_token,
+            SwapRecipient::Ibc {
+                channel_id: ibc_channel,
+                remote_recipient,
+                timeout_seconds,
+            },
             min_token,
             expiration,
+            referrer,
+            referral_commission,
         ),
+        ExecuteMsg::UpdateAllowlistConfig { enabled, public_at } => {
+            execute_update_allowlist_config(deps, info, enabled, public_at)
+        }
+        ExecuteMsg::UpdateAllowlist { add, remove } => {
+            execute_update_allowlist(deps, info, add, remove)
+        }
         ExecuteMsg::UpdateConfig {
             owner,
             dev_wallet_lists,
@@ -200,9 +298,157 @@ pub fn execute(
             fee_percent_denominator,
             dev_wallet_lists,
         ),
+        ExecuteMsg::UpdateReferralConfig {
+            max_referral_commission,
+        } => execute_update_referral_config(deps, info, max_referral_commission),
+        ExecuteMsg::AddHook { addr, gas_limit } => execute_add_hook(deps, info, addr, gas_limit),
+        ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, info, addr),
+        ExecuteMsg::ClaimIbcRefunds {} => execute_claim_ibc_refunds(deps, info),
+        ExecuteMsg::Batch(msgs) => execute_batch(deps, env, info, msgs),
     }
 }
 
+/// Native funds a batch step has to be sent with
+fn get_batch_step_inputs(
+    storage: &dyn Storage,
+    msg: &ExecuteMsg,
+) -> Result<Vec<(Denom, Uint128)>, ContractError> {
+    let (input_token, input_amount) = match msg {
+        ExecuteMsg::AddLiquidity {
+            token1_amount,
+            max_token2,
+            ..
+        }
+        | ExecuteMsg::AddLiquidityLocked {
+            token1_amount,
+            max_token2,
+            ..
+        } => {
+            return Ok(vec![
+                (TOKEN1.load(storage)?.denom, *token1_amount),
+                (TOKEN2.load(storage)?.denom, *max_token2),
+            ])
+        }
+        ExecuteMsg::WithdrawLocked { .. } | ExecuteMsg::RemoveLiquidity { .. } => {
+            return Ok(vec![])
+        }
+        ExecuteMsg::Swap {
+            input_token,
+            input_amount,
+            ..
+        }
+        | ExecuteMsg::SwapAndSendTo {
+            input_token,
+            input_amount,
+            ..
+        }
+        | ExecuteMsg::SwapAndIbcTransfer {
+            input_token,
+            input_amount,
+            ..
+        }
+        | ExecuteMsg::PassThroughSwap {
+            input_token,
+            input_token_amount: input_amount,
+            ..
+        } => (input_token, *input_amount),
+        _ => return Err(ContractError::InvalidBatchMessage {}),
+    };
+    let token = match input_token {
+        TokenSelect::Token1 => TOKEN1.load(storage)?,
+        TokenSelect::Token2 => TOKEN2.load(storage)?,
+    };
+    Ok(vec![(token.denom, input_amount)])
+}
+
+pub fn execute_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msgs: Vec<ExecuteMsg>,
+) -> Result<Response, ContractError> {
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+    let lp_token_supply = get_lp_token_supply(deps.as_ref(), &lp_token_addr)?;
+    BATCH_LP_SUPPLY.save(deps.storage, &lp_token_supply)?;
+
+    // Native funds available to the steps
+    let mut balances = info.funds.clone();
+    let mut sub_msgs = vec![];
+    let mut events = vec![];
+    let steps = msgs.len();
+    for (index, msg) in msgs.into_iter().enumerate() {
+        let mut funds = vec![];
+        for (denom, amount) in get_batch_step_inputs(deps.storage, &msg)? {
+            if let Denom::Native(denom) = denom {
+                if amount.is_zero() {
+                    continue;
+                }
+                let balance = balances
+                    .iter_mut()
+                    .find(|coin| coin.denom == denom)
+                    .ok_or(ContractError::InsufficientFunds {})?;
+                balance.amount = balance
+                    .amount
+                    .checked_sub(amount)
+                    .map_err(|_| ContractError::InsufficientFunds {})?;
+                funds.push(Coin { denom, amount });
+            }
+        }
+        let step_info = MessageInfo {
+            sender: info.sender.clone(),
+            funds,
+        };
+        let res = execute(deps.branch(), env.clone(), step_info, msg)?;
+
+        // Native outputs for the sender are kept for the next steps
+        for sub_msg in res.messages {
+            match &sub_msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount })
+                    if sub_msg.reply_on == ReplyOn::Never && *to_address == info.sender =>
+                {
+                    for coin in amount {
+                        match balances.iter_mut().find(|c| c.denom == coin.denom) {
+                            Some(balance) => balance.amount += coin.amount,
+                            None => balances.push(coin.clone()),
+                        }
+                    }
+                }
+                _ => sub_msgs.push(sub_msg),
+            }
+        }
+        events.push(
+            Event::new("hopers_batch_step")
+                .add_attribute("index", index.to_string())
+                .add_attributes(res.attributes),
+        );
+        events.extend(res.events);
+    }
+    BATCH_LP_SUPPLY.remove(deps.storage);
+
+    balances.retain(|coin| !coin.amount.is_zero());
+    if !balances.is_empty() {
+        sub_msgs.push(SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: balances,
+        }));
+    }
+
+    Ok(Response::new()
+        .add_submessages(sub_msgs)
+        .add_events(events)
+        .add_attributes(vec![
+            attr("action", "batch"),
+            attr("steps", steps.to_string()),
+        ]))
+}
+
+fn check_allowlist(deps: Deps, env: &Env, address: &Addr) -> Result<(), ContractError> {
+    if !is_allowlisted(deps, env, address)? {
+        return Err(ContractError::NotAllowlisted {});
+    }
+    Ok(())
+}
+
 fn check_expiration(
     expiration: &Option<Expiration>,
     block: &BlockInfo,
@@ -254,6 +500,7 @@ fn get_token2_amount_required(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_add_liquidity(
     deps: DepsMut,
     info: &MessageInfo,
@@ -262,9 +509,16 @@ pub fn execute_add_liquidity(
     token1_amount: Uint128,
     max_token2: Uint128,
     expiration: Option<Expiration>,
+    unlock_time: Option<Timestamp>,
 ) -> Result<Response, ContractError> {
     let action = "add_liquidity".to_string();
     check_expiration(&expiration, &env.block)?;
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
+    if let Some(unlock_time) = unlock_time {
+        if unlock_time <= env.block.time {
+            return Err(ContractError::InvalidUnlockTime {});
+        }
+    }
 
     let token1 = TOKEN1.load(deps.storage)?;
     let token2 = TOKEN2.load(deps.storage)?;
@@ -302,11 +556,11 @@ pub fn execute_add_liquidity(
 
     // Generate cw20 transfer messages if necessary
     let mut transfer_msgs: Vec<CosmosMsg> = vec![];
-    if let Cw20(addr) = token1.denom {
+    if let Cw20(addr) = &token1.denom {
         transfer_msgs.push(get_cw20_transfer_from_msg(
             &info.sender,
             &env.contract.address,
-            &addr,
+            addr,
             token1_amount,
         )?)
     }
@@ -320,11 +574,11 @@ pub fn execute_add_liquidity(
     }
 
     // Refund token 2 if is a native token and not all is spent
-    if let Denom::Native(denom) = token2.denom {
+    if let Denom::Native(denom) = &token2.denom {
         if token2_amount < max_token2 {
             transfer_msgs.push(get_bank_transfer_to_msg(
                 &info.sender,
-                &denom,
+                denom,
                 max_token2 - token2_amount,
             ))
         }
@@ -339,10 +593,59 @@ pub fn execute_add_liquidity(
         Ok(token2)
     })?;
 
-    let mint_msg = mint_lp_tokens(&info.sender, liquidity_amount, &lp_token_addr)?;
+    // Locked liquidity is minted to the pool and recorded for the sender
+    let lp_recipient = match unlock_time {
+        Some(unlock_time) => {
+            let lock_id = LOCK_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+            LOCK_COUNT.save(deps.storage, &lock_id)?;
+            LOCKS.save(
+                deps.storage,
+                (&info.sender, lock_id),
+                &Lock {
+                    amount: liquidity_amount,
+                    unlock_time,
+                },
+            )?;
+            let total_locked = TOTAL_LOCKED.may_load(deps.storage)?.unwrap_or_default();
+            TOTAL_LOCKED.save(deps.storage, &(total_locked + liquidity_amount))?;
+            env.contract.address.clone()
+        }
+        None => info.sender.clone(),
+    };
+
+    let event = Event::new("hopers_add_liquidity")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", lp_recipient.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", token1_amount)
+        .add_attribute("token2_amount", token2_amount)
+        .add_attribute("liquidity_amount", liquidity_amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    let (token1_reserve, token2_reserve) = get_reserves(deps.storage)?;
+    let hook_msgs = get_hook_msgs(
+        deps.storage,
+        PoolHookMsg::AddLiquidity {
+            provider: info.sender.to_string(),
+            token1_amount,
+            token2_amount,
+            liquidity_amount,
+            token1_reserve,
+            token2_reserve,
+        },
+    )?;
+
+    if let Some(supply) = BATCH_LP_SUPPLY.may_load(deps.storage)? {
+        BATCH_LP_SUPPLY.save(deps.storage, &(supply + liquidity_amount))?;
+    }
+
+    let mint_msg = mint_lp_tokens(&lp_recipient, liquidity_amount, &lp_token_addr)?;
     Ok(Response::new()
         .add_messages(transfer_msgs)
         .add_message(mint_msg)
+        .add_submessages(hook_msgs)
+        .add_event(event)
         .add_attributes(vec![
             attr("action", action),
             attr("token1_amount", token1_amount),
@@ -352,12 +655,28 @@ pub fn execute_add_liquidity(
 }
 
 fn get_lp_token_supply(deps: Deps, lp_token_addr: &Addr) -> StdResult<Uint128> {
+    if let Some(supply) = BATCH_LP_SUPPLY.may_load(deps.storage)? {
+        return Ok(supply);
+    }
     let resp: cw20::TokenInfoResponse = deps
         .querier
         .query_wasm_smart(lp_token_addr, &cw20_base::msg::QueryMsg::TokenInfo {})?;
     Ok(resp.total_supply)
 }
 
+/// Amount of a reserve backing `lp_amount` LP tokens
+fn get_liquidity_value(
+    lp_amount: Uint128,
+    reserve: Uint128,
+    lp_token_supply: Uint128,
+) -> StdResult<Uint128> {
+    lp_amount
+        .checked_mul(reserve)
+        .map_err(StdError::overflow)?
+        .checked_div(lp_token_supply)
+        .map_err(StdError::divide_by_zero)
+}
+
 fn mint_lp_tokens(
     recipient: &Addr,
     liquidity_amount: Uint128,
@@ -408,27 +727,6 @@ fn validate_input_amount(
     }
 }
 
-fn get_cw20_transfer_from_msg(
-    owner: &Addr,
-    recipient: &Addr,
-    token_addr: &Addr,
-    token_amount: Uint128,
-) -> StdResult<CosmosMsg> {
-    // create transfer cw20 msg
-    let transfer_cw20_msg = Cw20ExecuteMsg::TransferFrom {
-        owner: owner.into(),
-        recipient: recipient.into(),
-        amount: token_amount,
-    };
-    let exec_cw20_transfer = WasmMsg::Execute {
-        contract_addr: token_addr.into(),
-        msg: to_binary(&transfer_cw20_msg)?,
-        funds: vec![],
-    };
-    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
-    Ok(cw20_transfer_cosmos_msg)
-}
-
 fn get_cw20_burn_from_msg(
     owner: &Addr,
     token_addr: &Addr,
@@ -540,6 +838,182 @@ pub fn execute_update_config(
     ]))
 }
 
+pub fn execute_update_referral_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_referral_commission: Decimal,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if max_referral_commission > Decimal::one() {
+        return Err(ContractError::InvalidMaxReferralCommission {});
+    }
+    MAX_REFERRAL_COMMISSION.save(deps.storage, &max_referral_commission)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_referral_config"),
+        attr(
+            "max_referral_commission",
+            max_referral_commission.to_string(),
+        ),
+    ]))
+}
+
+pub fn execute_add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+    gas_limit: u64,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if gas_limit == 0 || gas_limit > MAX_HOOK_GAS_LIMIT {
+        return Err(ContractError::InvalidHookGasLimit {
+            max: MAX_HOOK_GAS_LIMIT,
+        });
+    }
+
+    let hook = deps.api.addr_validate(&addr)?;
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.iter().any(|h| h.addr == hook) {
+        return Err(ContractError::HookAlreadyRegistered {});
+    }
+    if hooks.len() >= MAX_HOOKS {
+        return Err(ContractError::TooManyHooks { max: MAX_HOOKS });
+    }
+    hooks.push(Hook {
+        addr: hook,
+        gas_limit,
+    });
+    HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_hook"),
+        attr("hook", addr),
+        attr("gas_limit", gas_limit.to_string()),
+    ]))
+}
+
+pub fn execute_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let hook = deps.api.addr_validate(&addr)?;
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if !hooks.iter().any(|h| h.addr == hook) {
+        return Err(ContractError::HookNotRegistered {});
+    }
+    hooks.retain(|h| h.addr != hook);
+    HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "remove_hook"), attr("hook", addr)]))
+}
+
+fn get_ibc_refunds(storage: &dyn Storage, address: &Addr) -> StdResult<Vec<Coin>> {
+    IBC_REFUNDS
+        .prefix_de(address)
+        .range_de(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, amount) = item?;
+            Ok(Coin { denom, amount })
+        })
+        .collect()
+}
+
+pub fn execute_claim_ibc_refunds(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let refunds = get_ibc_refunds(deps.storage, &info.sender)?;
+    if refunds.is_empty() {
+        return Err(ContractError::NoIbcRefunds {});
+    }
+    for refund in &refunds {
+        IBC_REFUNDS.remove(deps.storage, (&info.sender, &refund.denom));
+    }
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: refunds,
+        })
+        .add_attributes(vec![
+            attr("action", "claim_ibc_refunds"),
+            attr("sender", info.sender),
+        ]))
+}
+
+pub fn execute_withdraw_locked(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    lock_id: u64,
+) -> Result<Response, ContractError> {
+    let lock = LOCKS
+        .may_load(deps.storage, (&info.sender, lock_id))?
+        .ok_or(ContractError::LockNotFound { id: lock_id })?;
+    if env.block.time < lock.unlock_time {
+        return Err(ContractError::LockNotExpired {
+            unlock_time: lock.unlock_time,
+        });
+    }
+
+    LOCKS.remove(deps.storage, (&info.sender, lock_id));
+    TOTAL_LOCKED.update(deps.storage, |total_locked| -> StdResult<_> {
+        Ok(total_locked.checked_sub(lock.amount)?)
+    })?;
+
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+    let transfer_msg = get_cw20_transfer_to_msg(&info.sender, &lp_token_addr, lock.amount)?;
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attributes(vec![
+            attr("action", "withdraw_locked"),
+            attr("lock_id", lock_id.to_string()),
+            attr("amount", lock.amount),
+        ]))
+}
+
+pub fn execute_update_allowlist_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+    public_at: Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(update_allowlist_config(deps, enabled, public_at)?)
+}
+
+pub fn execute_update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(update_allowlist(deps, add, remove)?)
+}
+
 pub fn execute_remove_liquidity(
     deps: DepsMut,
     info: MessageInfo,
@@ -565,11 +1039,7 @@ pub fn execute_remove_liquidity(
         });
     }
 
-    let token1_amount = amount
-        .checked_mul(token1.reserve)
-        .map_err(StdError::overflow)?
-        .checked_div(lp_token_supply)
-        .map_err(StdError::divide_by_zero)?;
+    let token1_amount = get_liquidity_value(amount, token1.reserve, lp_token_supply)?;
     if token1_amount < min_token1 {
         return Err(ContractError::MinToken1Error {
             requested: min_token1,
@@ -577,11 +1047,7 @@ pub fn execute_remove_liquidity(
         });
     }
 
-    let token2_amount = amount
-        .checked_mul(token2.reserve)
-        .map_err(StdError::overflow)?
-        .checked_div(lp_token_supply)
-        .map_err(StdError::divide_by_zero)?;
+    let token2_amount = get_liquidity_value(amount, token2.reserve, lp_token_supply)?;
     if token2_amount < min_token2 {
         return Err(ContractError::MinToken2Error {
             requested: min_token2,
@@ -605,23 +1071,52 @@ pub fn execute_remove_liquidity(
         Ok(token2)
     })?;
 
-    let token1_transfer_msg = match token1.denom {
-        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, &addr, token1_amount)?,
-        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, &denom, token1_amount),
+    let token1_transfer_msg = match &token1.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, addr, token1_amount)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, denom, token1_amount),
     };
-    let token2_transfer_msg = match token2.denom {
-        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, &addr, token2_amount)?,
-        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, &denom, token2_amount),
+    let token2_transfer_msg = match &token2.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, addr, token2_amount)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, denom, token2_amount),
     };
 
+    if let Some(supply) = BATCH_LP_SUPPLY.may_load(deps.storage)? {
+        BATCH_LP_SUPPLY.save(deps.storage, &(supply - amount))?;
+    }
+
     let lp_token_burn_msg = get_burn_msg(&lp_token_addr, &info.sender, amount)?;
 
+    let event = Event::new("hopers_remove_liquidity")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", info.sender.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", token1_amount)
+        .add_attribute("token2_amount", token2_amount)
+        .add_attribute("liquidity_amount", amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    let (token1_reserve, token2_reserve) = get_reserves(deps.storage)?;
+    let hook_msgs = get_hook_msgs(
+        deps.storage,
+        PoolHookMsg::RemoveLiquidity {
+            provider: info.sender.to_string(),
+            token1_amount,
+            token2_amount,
+            liquidity_amount: amount,
+            token1_reserve,
+            token2_reserve,
+        },
+    )?;
+
     Ok(Response::new()
         .add_messages(vec![
             token1_transfer_msg,
             token2_transfer_msg,
             lp_token_burn_msg,
         ])
+        .add_submessages(hook_msgs)
+        .add_event(event)
         .add_attributes(vec![
             attr("action", action),
             attr("liquidity_burned", amount),
@@ -643,48 +1138,92 @@ fn get_burn_msg(contract: &Addr, owner: &Addr, amount: Uint128) -> StdResult<Cos
     .into())
 }
 
-fn get_cw20_transfer_to_msg(
-    recipient: &Addr,
-    token_addr: &Addr,
-    token_amount: Uint128,
-) -> StdResult<CosmosMsg> {
-    // create transfer cw20 msg
-    let transfer_cw20_msg = Cw20ExecuteMsg::Transfer {
-        recipient: recipient.into(),
-        amount: token_amount,
-    };
-    let exec_cw20_transfer = WasmMsg::Execute {
-        contract_addr: token_addr.into(),
-        msg: to_binary(&transfer_cw20_msg)?,
-        funds: vec![],
-    };
-    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
-    Ok(cw20_transfer_cosmos_msg)
-}
-
-fn get_bank_transfer_to_msg(recipient: &Addr, denom: &str, native_amount: Uint128) -> CosmosMsg {
-    let transfer_bank_msg = cosmwasm_std::BankMsg::Send {
-        to_address: recipient.into(),
-        amount: vec![Coin {
+// ICS20 MsgTransfer with an `ibc_callback` memo, so the ibc-hooks middleware reports the
+// acknowledgement or timeout of the packet back to the pool. `IbcMsg::Transfer` has no memo,
+// so this requires the ibc-hooks middleware on the chain the pool is deployed to
+fn get_ibc_transfer_msg(
+    contract: &Addr,
+    channel_id: &str,
+    remote_recipient: &str,
+    denom: &str,
+    amount: Uint128,
+    timeout: Timestamp,
+) -> SubMsg {
+    let msg = MsgTransfer {
+        source_port: "transfer".to_string(),
+        source_channel: channel_id.to_string(),
+        token: Some(ProtoCoin {
             denom: denom.to_string(),
-            amount: native_amount,
-        }],
+            amount: amount.to_string(),
+        }),
+        sender: contract.to_string(),
+        receiver: remote_recipient.to_string(),
+        timeout_height: None,
+        timeout_timestamp: timeout.nanos(),
+        memo: format!(r#"{{"ibc_callback":"{}"}}"#, contract),
     };
 
-    let transfer_bank_cosmos_msg: CosmosMsg = transfer_bank_msg.into();
-    transfer_bank_cosmos_msg
+    SubMsg::reply_on_success(msg, IBC_TRANSFER_REPLY_ID)
 }
 
-fn get_fee_transfer_msg(
+// Validates the referrer of a swap and the commission of the protocol fee it receives
+fn get_referral(
+    deps: Deps,
     sender: &Addr,
-    recipient: &Addr,
-    fee_denom: &Denom,
-    amount: Uint128,
-) -> StdResult<CosmosMsg> {
-    match fee_denom {
-        Denom::Cw20(addr) => get_cw20_transfer_from_msg(sender, recipient, addr, amount),
-        Denom::Native(denom) => Ok(get_bank_transfer_to_msg(recipient, denom, amount)),
+    referrer: Option<String>,
+    referral_commission: Option<Decimal>,
+) -> Result<Option<(Addr, Decimal)>, ContractError> {
+    let referrer = match referrer {
+        Some(referrer) => deps.api.addr_validate(&referrer)?,
+        None => return Ok(None),
+    };
+    if &referrer == sender {
+        return Err(ContractError::SelfReferral {});
+    }
+
+    let max_referral_commission = MAX_REFERRAL_COMMISSION
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let referral_commission = referral_commission.unwrap_or(max_referral_commission);
+    if referral_commission > max_referral_commission {
+        return Err(ContractError::ReferralCommissionTooHigh {
+            max_referral_commission,
+            referral_commission,
+        });
     }
+    Ok(Some((referrer, referral_commission)))
+}
+
+fn get_reserves(storage: &dyn Storage) -> StdResult<(Uint128, Uint128)> {
+    let token1 = TOKEN1.load(storage)?;
+    let token2 = TOKEN2.load(storage)?;
+    Ok((token1.reserve, token2.reserve))
+}
+
+// Adds both reserves after the action, so the pool state can be rebuilt from events alone
+fn add_reserve_attributes(storage: &dyn Storage, event: Event) -> StdResult<Event> {
+    let (token1_reserve, token2_reserve) = get_reserves(storage)?;
+    Ok(event
+        .add_attribute("token1_reserve", token1_reserve)
+        .add_attribute("token2_reserve", token2_reserve))
+}
+
+// Hooks are dispatched fire-and-forget, a failing hook is handled in the reply
+// and never reverts the pool action. The gas limit makes running out of gas a
+// failure of the hook instead of the whole transaction
+fn get_hook_msgs(storage: &dyn Storage, msg: PoolHookMsg) -> StdResult<Vec<SubMsg>> {
+    let hooks = HOOKS.may_load(storage)?.unwrap_or_default();
+    hooks
+        .into_iter()
+        .map(|hook| {
+            let hook_msg = WasmMsg::Execute {
+                contract_addr: hook.addr.to_string(),
+                msg: to_binary(&PoolHookExecuteMsg::PoolHook(msg.clone()))?,
+                funds: vec![],
+            };
+            Ok(SubMsg::reply_on_error(hook_msg, HOOK_REPLY_ID).with_gas_limit(hook.gas_limit))
+        })
+        .collect()
 }
 
 /* 
@@ -755,18 +1294,6 @@ pub fn get_percent_amount(
         .map_err(StdError::divide_by_zero)?)
 }
 
-fn get_amount_for_denom(coins: &[Coin], denom: &str) -> Coin {
-    let amount: Uint128 = coins
-        .iter()
-        .filter(|c| c.denom == denom)
-        .map(|c| c.amount)
-        .sum();
-    Coin {
-        amount,
-        denom: denom.to_string(),
-    }
-}
-
 #[allow(clippy::too_many_arguments)]
 pub fn execute_swap(
     deps: DepsMut,
@@ -774,12 +1301,30 @@ pub fn execute_swap(
     input_amount: Uint128,
     _env: Env,
     input_token_enum: TokenSelect,
-    recipient: String,
+    recipient: SwapRecipient,
     min_token: Uint128,
     expiration: Option<Expiration>,
+    referrer: Option<String>,
+    referral_commission: Option<Decimal>,
 ) -> Result<Response, ContractError> {
     let action = "swap".to_string();
     check_expiration(&expiration, &_env.block)?;
+    check_allowlist(deps.as_ref(), &_env, &info.sender)?;
+    if let SwapRecipient::Ibc {
+        channel_id,
+        remote_recipient,
+        timeout_seconds,
+    } = &recipient
+    {
+        if channel_id.is_empty() || remote_recipient.is_empty() {
+            return Err(ContractError::InvalidIbcRecipient {});
+        }
+        if *timeout_seconds == 0 {
+            return Err(ContractError::InvalidIbcTimeout {});
+        }
+    }
+
+    let referral = get_referral(deps.as_ref(), &info.sender, referrer, referral_commission)?;
 
     let input_token_item = match input_token_enum {
         TokenSelect::Token1 => TOKEN1,
@@ -814,6 +1359,13 @@ pub fn execute_swap(
     )?;
     let input_amount_minus_protocol_burn_fee: Uint128;
 
+    // The referral share is carved out of the protocol fee
+    let referral_fee_amount = match &referral {
+        Some((_, commission)) => protocol_fee_amount * *commission,
+        None => Uint128::zero(),
+    };
+    let dev_fee_amount = protocol_fee_amount - referral_fee_amount;
+
     //Token1 is always hopers token so if the input is Token1, we should burn some percent of input token
     //else we should burn after swap
     match input_token_enum {
@@ -903,7 +1455,7 @@ pub fn execute_swap(
     // Send protocol fee to protocol fee recipient
 
     for dev_wallet in fees.dev_wallet_lists {
-        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        let fee_amount = dev_fee_amount * dev_wallet.ratio;
         if fee_amount > Uint128::zero() {
             msgs.push(get_fee_transfer_msg(
                 &info.sender,
@@ -914,12 +1466,66 @@ pub fn execute_swap(
         }
     }
 
-    let recipient = deps.api.addr_validate(&recipient)?;
+    if let Some((referrer, _)) = &referral {
+        if referral_fee_amount > Uint128::zero() {
+            msgs.push(get_fee_transfer_msg(
+                &info.sender,
+                referrer,
+                &input_token.denom,
+                referral_fee_amount,
+            )?);
+            REFERRAL_EARNINGS.update(deps.storage, referrer, |earnings| -> StdResult<_> {
+                let mut earnings = earnings.unwrap_or_default();
+                match input_token_enum {
+                    TokenSelect::Token1 => earnings.token1_amount += referral_fee_amount,
+                    TokenSelect::Token2 => earnings.token2_amount += referral_fee_amount,
+                }
+                Ok(earnings)
+            })?;
+        }
+    }
+
     // Create transfer to message
-    msgs.push(match output_token.denom {
-        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&recipient, &addr, token_bought)?,
-        Denom::Native(denom) => get_bank_transfer_to_msg(&recipient, &denom, token_bought),
-    });
+    let mut ibc_transfer_msg = None;
+    let recipient = match recipient {
+        SwapRecipient::Address(recipient) => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            msgs.push(match &output_token.denom {
+                Denom::Cw20(addr) => get_cw20_transfer_to_msg(&recipient, addr, token_bought)?,
+                Denom::Native(denom) => get_bank_transfer_to_msg(&recipient, denom, token_bought),
+            });
+            recipient.to_string()
+        }
+        SwapRecipient::Ibc {
+            channel_id,
+            remote_recipient,
+            timeout_seconds,
+        } => {
+            let denom = match &output_token.denom {
+                Denom::Cw20(_) => return Err(ContractError::IbcTransferCw20 {}),
+                Denom::Native(denom) => denom,
+            };
+            ibc_transfer_msg = Some(get_ibc_transfer_msg(
+                &_env.contract.address,
+                &channel_id,
+                &remote_recipient,
+                denom,
+                token_bought,
+                _env.block.time.plus_seconds(timeout_seconds),
+            ));
+            let mut pending = PENDING_IBC_TRANSFERS
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            pending.push(IbcTransfer {
+                sender: info.sender.clone(),
+                channel_id,
+                denom: denom.clone(),
+                amount: token_bought,
+            });
+            PENDING_IBC_TRANSFERS.save(deps.storage, &pending)?;
+            remote_recipient
+        }
+    };
 
     input_token_item.update(
         deps.storage,
@@ -943,19 +1549,51 @@ pub fn execute_swap(
         },
     )?;
 
+    let event = Event::new("hopers_swap")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("input_denom", denom_to_string(&input_token.denom))
+        .add_attribute("output_denom", denom_to_string(&output_token.denom))
+        .add_attribute("input_amount", input_amount)
+        .add_attribute("output_amount", token_bought)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount)
+        .add_attribute("burn_fee_amount", burn_fee_amount)
+        .add_attribute("referral_fee_amount", referral_fee_amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    let (token1_reserve, token2_reserve) = get_reserves(deps.storage)?;
+    let hook_msgs = get_hook_msgs(
+        deps.storage,
+        PoolHookMsg::Swap {
+            trader: info.sender.to_string(),
+            recipient,
+            input_denom: input_token.denom,
+            output_denom: output_token.denom,
+            input_amount,
+            output_amount: token_bought,
+            token1_reserve,
+            token2_reserve,
+        },
+    )?;
+
     let input_denom: String;
     match input_token_enum {
         TokenSelect::Token1 => input_denom = "Token1".to_string(),
         TokenSelect::Token2 => input_denom = "Token2".to_string(),
     }
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", action),
-        attr("native_sold", input_amount),
-        attr("token_bought", token_bought),
-        attr("protocol_fee_amount", protocol_fee_amount),
-        attr("input_token", input_denom),
-    ]))
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_submessages(ibc_transfer_msg)
+        .add_submessages(hook_msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", action),
+            attr("native_sold", input_amount),
+            attr("token_bought", token_bought),
+            attr("protocol_fee_amount", protocol_fee_amount),
+            attr("input_token", input_denom),
+        ]))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -971,6 +1609,7 @@ pub fn execute_pass_through_swap(
 ) -> Result<Response, ContractError> {
     let action = "pass_swap".to_string();
     check_expiration(&expiration, &_env.block)?;
+    check_allowlist(deps.as_ref(), &_env, &info.sender)?;
 
     let input_token_state = match input_token_enum {
         TokenSelect::Token1 => TOKEN1,
@@ -1119,7 +1758,7 @@ pub fn execute_pass_through_swap(
         Err(ContractError::InvalidOutputPool {})
     }?;
 
-    let swap_msg = ExecuteMsg::SwapAndSendTo {
+    let swap_msg = OutputPoolExecuteMsg::SwapAndSendTo {
         input_token: transfer_input_token_enum,
         input_amount: amount_to_transfer,
         recipient: info.sender.to_string(),
@@ -1129,12 +1768,12 @@ pub fn execute_pass_through_swap(
 
     msgs.push(
         WasmMsg::Execute {
-            contract_addr: output_amm_address.into(),
+            contract_addr: output_amm_address.to_string(),
             msg: to_binary(&swap_msg)?,
-            funds: match transfer_token.denom {
+            funds: match &transfer_token.denom {
                 Denom::Cw20(_) => vec![],
                 Denom::Native(denom) => vec![Coin {
-                    denom,
+                    denom: denom.clone(),
                     amount: amount_to_transfer,
                 }],
             },
@@ -1159,15 +1798,46 @@ pub fn execute_pass_through_swap(
         Ok(token)
     })?;
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", action),
-        attr("input_token_amount", input_token_amount),
-        attr("native_transferred", amount_to_transfer),
-    ]))
+    let event = Event::new("hopers_swap")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", output_amm_address.as_str())
+        .add_attribute("input_denom", denom_to_string(&input_token.denom))
+        .add_attribute("output_denom", denom_to_string(&transfer_token.denom))
+        .add_attribute("input_amount", input_token_amount)
+        .add_attribute("output_amount", amount_to_transfer)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount)
+        .add_attribute("burn_fee_amount", burn_fee_amount)
+        .add_attribute("referral_fee_amount", Uint128::zero());
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    let (token1_reserve, token2_reserve) = get_reserves(deps.storage)?;
+    let hook_msgs = get_hook_msgs(
+        deps.storage,
+        PoolHookMsg::Swap {
+            trader: info.sender.to_string(),
+            recipient: output_amm_address.to_string(),
+            input_denom: input_token.denom,
+            output_denom: transfer_token.denom,
+            input_amount: input_token_amount,
+            output_amount: amount_to_transfer,
+            token1_reserve,
+            token2_reserve,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_submessages(hook_msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", action),
+            attr("input_token_amount", input_token_amount),
+            attr("native_transferred", amount_to_transfer),
+        ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
         QueryMsg::Info {} => to_binary(&query_info(deps)?),
@@ -1178,6 +1848,23 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_token2_for_token1_price(deps, token2_amount)?)
         }
         QueryMsg::Fee {} => to_binary(&query_fee(deps)?),
+        QueryMsg::Share { lp_amount } => to_binary(&query_share(deps, lp_amount)?),
+        QueryMsg::Position { address } => to_binary(&query_position(deps, address)?),
+        QueryMsg::Locks {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_locks(deps, owner, start_after, limit)?),
+        QueryMsg::TotalLocked {} => to_binary(&query_total_locked(deps)?),
+        QueryMsg::AllowlistConfig {} => to_binary(&query_allowlist_config(deps)?),
+        QueryMsg::IsAllowlisted { address } => {
+            to_binary(&query_is_allowlisted(deps, env, address)?)
+        }
+        QueryMsg::Hooks {} => to_binary(&query_hooks(deps)?),
+        QueryMsg::ReferralEarnings { address } => {
+            to_binary(&query_referral_earnings(deps, address)?)
+        }
+        QueryMsg::IbcRefunds { address } => to_binary(&query_ibc_refunds(deps, address)?),
     }
 }
 
@@ -1197,6 +1884,43 @@ pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
     })
 }
 
+pub fn query_share(deps: Deps, lp_amount: Uint128) -> StdResult<ShareResponse> {
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let lp_token_supply = get_lp_token_supply(deps, &LP_TOKEN.load(deps.storage)?)?;
+
+    if lp_token_supply.is_zero() {
+        return Ok(ShareResponse {
+            token1_amount: Uint128::zero(),
+            token2_amount: Uint128::zero(),
+        });
+    }
+    Ok(ShareResponse {
+        token1_amount: get_liquidity_value(lp_amount, token1.reserve, lp_token_supply)?,
+        token2_amount: get_liquidity_value(lp_amount, token2.reserve, lp_token_supply)?,
+    })
+}
+
+pub fn query_position(deps: Deps, address: String) -> StdResult<PositionResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+    let lp_balance = get_token_balance(deps, &lp_token_addr, &address)?;
+    let lp_token_supply = get_lp_token_supply(deps, &lp_token_addr)?;
+
+    let share = query_share(deps, lp_balance)?;
+    let pool_share = if lp_token_supply.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(lp_balance, lp_token_supply)
+    };
+    Ok(PositionResponse {
+        lp_balance,
+        token1_amount: share.token1_amount,
+        token2_amount: share.token2_amount,
+        pool_share,
+    })
+}
+
 pub fn query_token1_for_token2_price(
     deps: Deps,
     token1_amount: Uint128,
@@ -1260,11 +1984,90 @@ pub fn query_fee(deps: Deps) -> StdResult<FeeResponse> {
         owner,
         total_fee_percent,
         dev_wallet_lists: fees.dev_wallet_lists,
+        max_referral_commission: MAX_REFERRAL_COMMISSION
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn query_referral_earnings(deps: Deps, address: String) -> StdResult<ReferralEarningsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let earnings = REFERRAL_EARNINGS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    Ok(ReferralEarningsResponse {
+        token1_denom: token1.denom,
+        token1_amount: earnings.token1_amount,
+        token2_denom: token2.denom,
+        token2_amount: earnings.token2_amount,
+    })
+}
+
+pub fn query_hooks(deps: Deps) -> StdResult<HooksResponse> {
+    let hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(HooksResponse {
+        hooks: hooks
+            .into_iter()
+            .map(|hook| HookInfo {
+                addr: hook.addr.into_string(),
+                gas_limit: hook.gas_limit,
+            })
+            .collect(),
+    })
+}
+
+pub fn query_ibc_refunds(deps: Deps, address: String) -> StdResult<IbcRefundsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let refunds = get_ibc_refunds(deps.storage, &address)?;
+    Ok(IbcRefundsResponse { refunds })
+}
+
+pub fn query_locks(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LocksResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let locks = LOCKS
+        .prefix_de(&owner)
+        .range_de(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, lock) = item?;
+            Ok(LockInfo {
+                id,
+                amount: lock.amount,
+                unlock_time: lock.unlock_time,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(LocksResponse { locks })
+}
+
+pub fn query_total_locked(deps: Deps) -> StdResult<TotalLockedResponse> {
+    Ok(TotalLockedResponse {
+        total_locked: TOTAL_LOCKED.may_load(deps.storage)?.unwrap_or_default(),
     })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id == HOOK_REPLY_ID {
+        return match msg.result {
+            SubMsgResult::Err(error) => Ok(Response::new()
+                .add_attributes(vec![attr("action", "hook_failed"), attr("error", error)])),
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+        };
+    }
+    if msg.id == IBC_TRANSFER_REPLY_ID {
+        return reply_ibc_transfer(deps, msg);
+    }
     if msg.id != INSTANTIATE_LP_TOKEN_REPLY_ID {
         return Err(ContractError::UnknownReplyId { id: msg.id });
     };
@@ -1283,6 +2086,93 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
     }
 }
 
+// Moves the oldest transfer of the transaction to the ones waiting for an acknowledgement
+fn reply_ibc_transfer(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let mut pending = PENDING_IBC_TRANSFERS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if pending.is_empty() {
+        return Err(ContractError::InvalidIbcTransferReply {});
+    }
+    let transfer = pending.remove(0);
+    if pending.is_empty() {
+        PENDING_IBC_TRANSFERS.remove(deps.storage);
+    } else {
+        PENDING_IBC_TRANSFERS.save(deps.storage, &pending)?;
+    }
+
+    let data = msg
+        .result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .data
+        .ok_or(ContractError::InvalidIbcTransferReply {})?;
+    let sequence = MsgTransferResponse::try_from(data)
+        .map_err(|_| ContractError::InvalidIbcTransferReply {})?
+        .sequence;
+    IBC_TRANSFERS.save(deps.storage, (&transfer.channel_id, sequence), &transfer)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "ibc_transfer"),
+        attr("channel", transfer.channel_id),
+        attr("sequence", sequence.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        }) => sudo_ibc_transfer_complete(deps, channel, sequence, success),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout { channel, sequence }) => {
+            sudo_ibc_transfer_complete(deps, channel, sequence, false)
+        }
+    }
+}
+
+// The transfer module refunds a failed or timed out transfer to the pool, outside of the
+// reserves, so it is credited to the sender of the swap. Callbacks for packets the pool did
+// not send are ignored, so that the middleware does not fail on them
+fn sudo_ibc_transfer_complete(
+    deps: DepsMut,
+    channel: String,
+    sequence: u64,
+    success: bool,
+) -> Result<Response, ContractError> {
+    let transfer = match IBC_TRANSFERS.may_load(deps.storage, (&channel, sequence))? {
+        Some(transfer) => transfer,
+        None => {
+            return Ok(Response::new().add_attributes(vec![
+                attr("action", "ibc_transfer_complete"),
+                attr("channel", channel),
+                attr("sequence", sequence.to_string()),
+                attr("ignored", "true"),
+            ]))
+        }
+    };
+    IBC_TRANSFERS.remove(deps.storage, (&channel, sequence));
+
+    if !success {
+        IBC_REFUNDS.update(
+            deps.storage,
+            (&transfer.sender, &transfer.denom),
+            |refund| -> StdResult<_> { Ok(refund.unwrap_or_default() + transfer.amount) },
+        )?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "ibc_transfer_complete"),
+        attr("channel", channel),
+        attr("sequence", sequence.to_string()),
+        attr("success", success.to_string()),
+        attr("sender", transfer.sender),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let version = get_contract_version(deps.storage)?;
@@ -1306,6 +2196,8 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, SubMsgResponse};
 
     #[test]
     fn test_get_liquidity_amount() {
@@ -1342,4 +2234,304 @@ mod tests {
         .unwrap();
         assert_eq!(liquidity, Uint128::new(201));
     }
+
+    #[test]
+    fn test_swap_and_ibc_transfer() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        TOKEN1
+            .save(
+                deps.as_mut().storage,
+                &Token {
+                    reserve: Uint128::new(100),
+                    denom: Denom::Native("juno".to_string()),
+                },
+            )
+            .unwrap();
+        TOKEN2
+            .save(
+                deps.as_mut().storage,
+                &Token {
+                    reserve: Uint128::new(100),
+                    denom: Denom::Native("ibc/atom".to_string()),
+                },
+            )
+            .unwrap();
+        FEES.save(
+            deps.as_mut().storage,
+            &Fees {
+                dev_wallet_lists: vec![WalletInfo {
+                    address: "dev".to_string(),
+                    ratio: Decimal::one(),
+                }],
+                fee_percent_numerator: Uint128::new(20),
+                fee_percent_denominator: Uint128::new(100),
+            },
+        )
+        .unwrap();
+        BURN_FEE_INFO
+            .save(deps.as_mut().storage, &Uint128::new(10))
+            .unwrap();
+
+        let swap_msg = |ibc_channel: &str, timeout_seconds| ExecuteMsg::SwapAndIbcTransfer {
+            input_token: TokenSelect::Token1,
+            input_amount: Uint128::new(10),
+            min_token: Uint128::new(5),
+            ibc_channel: ibc_channel.to_string(),
+            remote_recipient: "osmo1recipient".to_string(),
+            timeout_seconds,
+            expiration: None,
+            referrer: None,
+            referral_commission: None,
+        };
+        let transfer_reply = |sequence| Reply {
+            id: IBC_TRANSFER_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(MsgTransferResponse { sequence }.into()),
+            }),
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(10, "juno")),
+            swap_msg("", 600),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidIbcRecipient {});
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(10, "juno")),
+            swap_msg("channel-0", 0),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidIbcTimeout {});
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(10, "juno")),
+            swap_msg("channel-0", 600),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages.last().unwrap(),
+            &get_ibc_transfer_msg(
+                &env.contract.address,
+                "channel-0",
+                "osmo1recipient",
+                "ibc/atom",
+                Uint128::new(6),
+                env.block.time.plus_seconds(600),
+            )
+        );
+        assert_eq!(
+            TOKEN2.load(&deps.storage).unwrap().reserve,
+            Uint128::new(94)
+        );
+        reply(deps.as_mut(), env.clone(), transfer_reply(7)).unwrap();
+
+        // a timed out transfer is claimable by the buyer
+        let timeout = SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: "channel-0".to_string(),
+            sequence: 7,
+        });
+        sudo(deps.as_mut(), env.clone(), timeout.clone()).unwrap();
+
+        // a callback for a packet the pool is not waiting on is ignored
+        let res = sudo(deps.as_mut(), env.clone(), timeout).unwrap();
+        assert!(res.attributes.contains(&attr("ignored", "true")));
+        assert_eq!(
+            query_ibc_refunds(deps.as_ref(), "buyer".to_string())
+                .unwrap()
+                .refunds,
+            coins(6, "ibc/atom")
+        );
+
+        // an acknowledged transfer is not
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(10, "juno")),
+            swap_msg("channel-0", 600),
+        )
+        .unwrap();
+        reply(deps.as_mut(), env.clone(), transfer_reply(8)).unwrap();
+        let ack = SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: "channel-0".to_string(),
+            sequence: 8,
+            ack: "eyJyZXN1bHQiOiJBUT09In0=".to_string(),
+            success: true,
+        });
+        sudo(deps.as_mut(), env.clone(), ack).unwrap();
+
+        let res = query_ibc_refunds(deps.as_ref(), "buyer".to_string()).unwrap();
+        assert_eq!(res.refunds, coins(6, "ibc/atom"));
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &[]),
+            ExecuteMsg::ClaimIbcRefunds {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: coins(6, "ibc/atom"),
+            })
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &[]),
+            ExecuteMsg::ClaimIbcRefunds {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoIbcRefunds {});
+
+        // cw20 outputs can not be sent over IBC
+        TOKEN1
+            .save(
+                deps.as_mut().storage,
+                &Token {
+                    reserve: Uint128::new(100),
+                    denom: Denom::Cw20(Addr::unchecked("hopers")),
+                },
+            )
+            .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("buyer", &coins(10, "ibc/atom")),
+            ExecuteMsg::SwapAndIbcTransfer {
+                input_token: TokenSelect::Token2,
+                input_amount: Uint128::new(10),
+                min_token: Uint128::zero(),
+                ibc_channel: "channel-0".to_string(),
+                remote_recipient: "osmo1recipient".to_string(),
+                timeout_seconds: 600,
+                expiration: None,
+                referrer: None,
+                referral_commission: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::IbcTransferCw20 {});
+    }
+
+    #[test]
+    fn test_ibc_transfer_encoding() {
+        let msg = get_ibc_transfer_msg(
+            &Addr::unchecked("pool"),
+            "channel-0",
+            "osmo1r",
+            "uatom",
+            Uint128::new(6),
+            Timestamp::from_seconds(1),
+        );
+        assert_eq!(msg.id, IBC_TRANSFER_REPLY_ID);
+        let value = match msg.msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/ibc.applications.transfer.v1.MsgTransfer");
+                value
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        assert_eq!(
+            MsgTransfer::try_from(value).unwrap(),
+            MsgTransfer {
+                source_port: "transfer".to_string(),
+                source_channel: "channel-0".to_string(),
+                token: Some(ProtoCoin {
+                    denom: "uatom".to_string(),
+                    amount: "6".to_string(),
+                }),
+                sender: "pool".to_string(),
+                receiver: "osmo1r".to_string(),
+                timeout_height: None,
+                timeout_timestamp: 1_000_000_000,
+                memo: r#"{"ibc_callback":"pool"}"#.to_string(),
+            }
+        );
+
+        let data: Binary = MsgTransferResponse { sequence: 300 }.into();
+        assert_eq!(data, Binary::from(vec![0x08, 0xac, 0x02]));
+        assert_eq!(MsgTransferResponse::try_from(data).unwrap().sequence, 300);
+    }
+
+    #[test]
+    fn test_output_pool_swap_encoding() {
+        // pools without referrals reject unknown fields
+        let msg = OutputPoolExecuteMsg::SwapAndSendTo {
+            input_token: TokenSelect::Token1,
+            input_amount: Uint128::new(10),
+            recipient: "buyer".to_string(),
+            min_token: Uint128::new(5),
+            expiration: None,
+        };
+        assert_eq!(
+            to_binary(&msg).unwrap(),
+            Binary::from(
+                br#"{"swap_and_send_to":{"input_token":"token1","input_amount":"10","recipient":"buyer","min_token":"5","expiration":null}}"#
+                    .as_slice()
+            )
+        );
+    }
+
+    #[test]
+    fn test_hook_reply() {
+        let mut deps = mock_dependencies();
+        HOOKS
+            .save(
+                deps.as_mut().storage,
+                &vec![Hook {
+                    addr: Addr::unchecked("hook"),
+                    gas_limit: 200_000,
+                }],
+            )
+            .unwrap();
+        let msgs = get_hook_msgs(
+            &deps.storage,
+            PoolHookMsg::AddLiquidity {
+                provider: "provider".to_string(),
+                token1_amount: Uint128::new(1),
+                token2_amount: Uint128::new(1),
+                liquidity_amount: Uint128::new(1),
+                token1_reserve: Uint128::new(1),
+                token2_reserve: Uint128::new(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(msgs[0].gas_limit, Some(200_000));
+        assert_eq!(msgs[0].reply_on, ReplyOn::Error);
+
+        let res = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: HOOK_REPLY_ID,
+                result: SubMsgResult::Err("out of gas".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "hook_failed"));
+        let res = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: HOOK_REPLY_ID,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                }),
+            },
+        )
+        .unwrap();
+        assert!(res.attributes.is_empty());
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Decimal, StdError, Uint128};
+use cosmwasm_std::{Decimal, StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -76,6 +76,60 @@ pub enum ContractError {
     #[error("The output amm provided is invalid")]
     InvalidOutputPool {},
 
+    #[error("Only native tokens can be sent over IBC")]
+    IbcTransferCw20 {},
+
+    #[error("The IBC transfer timeout must be greater than zero")]
+    InvalidIbcTimeout {},
+
+    #[error("The IBC channel and remote recipient can not be empty")]
+    InvalidIbcRecipient {},
+
+    #[error("Failed to parse the sequence of the IBC transfer")]
+    InvalidIbcTransferReply {},
+
+    #[error("No IBC refunds to claim")]
+    NoIbcRefunds {},
+
+    #[error("Hook is already registered")]
+    HookAlreadyRegistered {},
+
+    #[error("Hook is not registered")]
+    HookNotRegistered {},
+
+    #[error("Only {max} hooks can be registered")]
+    TooManyHooks { max: usize },
+
+    #[error("Hook gas limit must be between 1 and {max}")]
+    InvalidHookGasLimit { max: u64 },
+
+    #[error("Referral commission ({referral_commission}) is higher than max ({max_referral_commission})")]
+    ReferralCommissionTooHigh {
+        max_referral_commission: Decimal,
+        referral_commission: Decimal,
+    },
+
+    #[error("Max referral commission can not be higher than 1")]
+    InvalidMaxReferralCommission {},
+
+    #[error("Cannot refer yourself")]
+    SelfReferral {},
+
+    #[error("Address is not allowlisted")]
+    NotAllowlisted {},
+
+    #[error("Unlock time must be in the future")]
+    InvalidUnlockTime {},
+
+    #[error("Lock {id} not found")]
+    LockNotFound { id: u64 },
+
+    #[error("Lock is not expired until {unlock_time}")]
+    LockNotExpired { unlock_time: Timestamp },
+
+    #[error("Only pool operations can be batched")]
+    InvalidBatchMessage {},
+
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 }
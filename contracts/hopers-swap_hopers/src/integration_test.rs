@@ -4,7 +4,8 @@ use std::borrow::BorrowMut;
 
 use cosmwasm_std::{
     coins,
-    to_binary, Addr, Coin, CosmosMsg, Decimal, Empty, Uint128, WasmMsg,
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
 };
 use cw0::Expiration;
 
@@ -14,10 +15,15 @@ use crate::{
     msg::{MigrateMsg, WalletInfo},
 };
 use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Denom};
-use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
 use std::str::FromStr;
 
-use crate::msg::{ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, QueryMsg, TokenSelect};
+use crate::msg::{
+    AllowlistConfigResponse, ExecuteMsg, FeeResponse, HookInfo, HooksResponse, InfoResponse,
+    InstantiateMsg, IsAllowlistedResponse, LockInfo, LocksResponse, PoolHookExecuteMsg,
+    PoolHookMsg, PositionResponse, QueryMsg, ReferralEarningsResponse, ShareResponse, TokenSelect,
+    TotalLockedResponse,
+};
 
 fn mock_app() -> App {
     App::default()
@@ -683,6 +689,8 @@ fn swap_tokens_happy_path() {
         input_amount: Uint128::new(10),
         min_output: Uint128::new(6),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -725,6 +733,8 @@ fn swap_tokens_happy_path() {
         input_amount: Uint128::new(6),
         min_output: Uint128::new(4),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(buyer.clone(), amm_addr.clone(), &swap_msg, &[])
@@ -752,6 +762,8 @@ fn swap_tokens_happy_path() {
         recipient: owner.to_string(),
         min_token: Uint128::new(3),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -864,6 +876,8 @@ fn swap_with_fee_split() {
         input_amount: Uint128::new(10_000_000),
         min_output: Uint128::new(6_000_000),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -899,6 +913,8 @@ fn swap_with_fee_split() {
         input_amount: Uint128::new(10_000_000),
         min_output: Uint128::new(5_000_000),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -946,6 +962,8 @@ fn swap_with_fee_split() {
         input_amount: Uint128::new(12_000_000),
         min_output: Uint128::new(5_000_000),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(buyer.clone(), amm_addr.clone(), &swap_msg, &[])
@@ -975,6 +993,8 @@ fn swap_with_fee_split() {
         recipient: owner.to_string(),
         min_token: Uint128::new(3_000_000),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -1206,6 +1226,8 @@ fn swap_native_to_native_tokens_happy_path() {
         input_amount: Uint128::new(10),
         min_output: Uint128::new(6),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -1234,6 +1256,8 @@ fn swap_native_to_native_tokens_happy_path() {
         input_amount: Uint128::new(10),
         min_output: Uint128::new(5),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -1263,6 +1287,8 @@ fn swap_native_to_native_tokens_happy_path() {
         input_amount: Uint128::new(11),
         min_output: Uint128::new(9),
         expiration: None,
+        referrer: None,
+        referral_commission: None,
     };
     let _res = router
         .execute_contract(
@@ -1994,3 +2020,1266 @@ fn test_swap() {
         get_percent_amount(Uint128::new(1000), Uint128::new(5), Uint128::new(1000)).unwrap();
     println!("fee_amount {:?}", fee_amount)
 }
+
+fn get_event(res: &AppResponse, ty: &str) -> Event {
+    res.events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{}", ty))
+        .cloned()
+        .unwrap()
+}
+
+fn get_event_attribute(event: &Event, key: &str) -> String {
+    event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+        .unwrap()
+}
+
+#[test]
+fn swap_and_liquidity_events() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let funds = vec![
+        Coin {
+            denom: NATIVE_TOKEN_DENOM.into(),
+            amount: Uint128::new(2000),
+        },
+        Coin {
+            denom: IBC_TOKEN_DENOM.into(),
+            amount: Uint128::new(5000),
+        },
+    ];
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(100),
+        min_liquidity: Uint128::new(100),
+        max_token2: Uint128::new(100),
+        expiration: None,
+    };
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[
+                Coin {
+                    denom: NATIVE_TOKEN_DENOM.into(),
+                    amount: Uint128::new(100),
+                },
+                Coin {
+                    denom: IBC_TOKEN_DENOM.into(),
+                    amount: Uint128::new(100),
+                },
+            ],
+        )
+        .unwrap();
+    let event = get_event(&res, "hopers_add_liquidity");
+    assert_eq!(get_event_attribute(&event, "sender"), owner.to_string());
+    assert_eq!(
+        get_event_attribute(&event, "token1_denom"),
+        NATIVE_TOKEN_DENOM
+    );
+    assert_eq!(get_event_attribute(&event, "token2_denom"), IBC_TOKEN_DENOM);
+    assert_eq!(get_event_attribute(&event, "token1_amount"), "100");
+    assert_eq!(get_event_attribute(&event, "token2_amount"), "100");
+    assert_eq!(get_event_attribute(&event, "liquidity_amount"), "100");
+    assert_eq!(get_event_attribute(&event, "token1_reserve"), "100");
+    assert_eq!(get_event_attribute(&event, "token2_reserve"), "100");
+
+    let swap_msg = ExecuteMsg::SwapAndSendTo {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(10),
+        recipient: "bob".to_string(),
+        min_token: Uint128::new(6),
+        expiration: None,
+        referrer: None,
+        referral_commission: None,
+    };
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let event = get_event(&res, "hopers_swap");
+    assert_eq!(get_event_attribute(&event, "sender"), owner.to_string());
+    assert_eq!(get_event_attribute(&event, "recipient"), "bob");
+    assert_eq!(
+        get_event_attribute(&event, "input_denom"),
+        NATIVE_TOKEN_DENOM
+    );
+    assert_eq!(get_event_attribute(&event, "output_denom"), IBC_TOKEN_DENOM);
+    assert_eq!(get_event_attribute(&event, "input_amount"), "10");
+    assert_eq!(get_event_attribute(&event, "output_amount"), "6");
+    assert_eq!(get_event_attribute(&event, "protocol_fee_amount"), "2");
+    assert_eq!(get_event_attribute(&event, "burn_fee_amount"), "1");
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(
+        get_event_attribute(&event, "token1_reserve"),
+        info.token1_reserve.to_string()
+    );
+    assert_eq!(
+        get_event_attribute(&event, "token2_reserve"),
+        info.token2_reserve.to_string()
+    );
+
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(50),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), lp_token.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    let remove_liquidity_msg = ExecuteMsg::RemoveLiquidity {
+        amount: Uint128::new(50),
+        min_token1: Uint128::zero(),
+        min_token2: Uint128::zero(),
+        expiration: None,
+    };
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &remove_liquidity_msg, &[])
+        .unwrap();
+    let event = get_event(&res, "hopers_remove_liquidity");
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(get_event_attribute(&event, "recipient"), owner.to_string());
+    assert_eq!(get_event_attribute(&event, "liquidity_amount"), "50");
+    assert_eq!(
+        get_event_attribute(&event, "token1_reserve"),
+        info.token1_reserve.to_string()
+    );
+    assert_eq!(
+        get_event_attribute(&event, "token2_reserve"),
+        info.token2_reserve.to_string()
+    );
+}
+
+fn hook_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+// Records the last hook message, so it can be queried back
+fn hook_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: PoolHookExecuteMsg,
+) -> StdResult<Response> {
+    let PoolHookExecuteMsg::PoolHook(msg) = msg;
+    deps.storage.set(b"last_hook", &to_binary(&msg)?);
+    Ok(Response::new())
+}
+
+fn hook_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Ok(Binary(deps.storage.get(b"last_hook").unwrap_or_default()))
+}
+
+fn failing_hook_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: PoolHookExecuteMsg,
+) -> StdResult<Response> {
+    Err(StdError::generic_err("hook failed"))
+}
+
+#[test]
+fn pool_hooks() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let funds = vec![
+        Coin {
+            denom: NATIVE_TOKEN_DENOM.into(),
+            amount: Uint128::new(2000),
+        },
+        Coin {
+            denom: IBC_TOKEN_DENOM.into(),
+            amount: Uint128::new(5000),
+        },
+    ];
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let hook_id = router.store_code(Box::new(ContractWrapper::new(
+        hook_execute,
+        hook_instantiate,
+        hook_query,
+    )));
+    let hook_addr = router
+        .instantiate_contract(hook_id, owner.clone(), &Empty {}, &[], "hook", None)
+        .unwrap();
+    let failing_hook_id = router.store_code(Box::new(ContractWrapper::new(
+        failing_hook_execute,
+        hook_instantiate,
+        hook_query,
+    )));
+    let failing_hook_addr = router
+        .instantiate_contract(failing_hook_id, owner.clone(), &Empty {}, &[], "hook", None)
+        .unwrap();
+
+    // only the owner can register hooks
+    let err = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            amm_addr.clone(),
+            &ExecuteMsg::AddHook {
+                addr: hook_addr.to_string(),
+                gas_limit: 200_000,
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddHook {
+                addr: hook_addr.to_string(),
+                gas_limit: 1_000_001,
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidHookGasLimit { max: 1_000_000 }, err);
+
+    for hook in [&failing_hook_addr, &hook_addr] {
+        router
+            .execute_contract(
+                owner.clone(),
+                amm_addr.clone(),
+                &ExecuteMsg::AddHook {
+                    addr: hook.to_string(),
+                    gas_limit: 200_000,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddHook {
+                addr: hook_addr.to_string(),
+                gas_limit: 200_000,
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::HookAlreadyRegistered {}, err);
+
+    let hooks: HooksResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::Hooks {})
+        .unwrap();
+    assert_eq!(
+        hooks.hooks,
+        vec![
+            HookInfo {
+                addr: failing_hook_addr.to_string(),
+                gas_limit: 200_000,
+            },
+            HookInfo {
+                addr: hook_addr.to_string(),
+                gas_limit: 200_000,
+            },
+        ]
+    );
+
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(100),
+        min_liquidity: Uint128::new(100),
+        max_token2: Uint128::new(100),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[
+                Coin {
+                    denom: NATIVE_TOKEN_DENOM.into(),
+                    amount: Uint128::new(100),
+                },
+                Coin {
+                    denom: IBC_TOKEN_DENOM.into(),
+                    amount: Uint128::new(100),
+                },
+            ],
+        )
+        .unwrap();
+    let last_hook: PoolHookMsg = router
+        .wrap()
+        .query_wasm_smart(&hook_addr, &Empty {})
+        .unwrap();
+    assert_eq!(
+        last_hook,
+        PoolHookMsg::AddLiquidity {
+            provider: owner.to_string(),
+            token1_amount: Uint128::new(100),
+            token2_amount: Uint128::new(100),
+            liquidity_amount: Uint128::new(100),
+            token1_reserve: Uint128::new(100),
+            token2_reserve: Uint128::new(100),
+        }
+    );
+
+    // the failing hook does not revert the swap
+    let swap_msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(10),
+        min_output: Uint128::new(6),
+        expiration: None,
+        referrer: None,
+        referral_commission: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let info = get_info(&router, &amm_addr);
+    let last_hook: PoolHookMsg = router
+        .wrap()
+        .query_wasm_smart(&hook_addr, &Empty {})
+        .unwrap();
+    assert_eq!(
+        last_hook,
+        PoolHookMsg::Swap {
+            trader: owner.to_string(),
+            recipient: owner.to_string(),
+            input_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+            output_denom: Denom::Native(IBC_TOKEN_DENOM.into()),
+            input_amount: Uint128::new(10),
+            output_amount: Uint128::new(6),
+            token1_reserve: info.token1_reserve,
+            token2_reserve: info.token2_reserve,
+        }
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::RemoveHook {
+                addr: hook_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::RemoveHook {
+                addr: hook_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::HookNotRegistered {}, err);
+
+    let hooks: HooksResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::Hooks {})
+        .unwrap();
+    assert_eq!(
+        hooks.hooks,
+        vec![HookInfo {
+            addr: failing_hook_addr.to_string(),
+            gas_limit: 200_000,
+        }]
+    );
+
+    // the number of hooks is capped
+    let add_hook = |router: &mut App, addr: String| {
+        router.execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddHook {
+                addr,
+                gas_limit: 200_000,
+            },
+            &[],
+        )
+    };
+    for i in 1..10 {
+        add_hook(&mut router, format!("hook{}", i)).unwrap();
+    }
+    let err = add_hook(&mut router, "hook10".to_string())
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::TooManyHooks { max: 10 }, err);
+}
+
+#[test]
+fn swap_with_referral() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let partner = Addr::unchecked("partner");
+    let funds = vec![
+        Coin {
+            denom: NATIVE_TOKEN_DENOM.into(),
+            amount: Uint128::new(20_000),
+        },
+        Coin {
+            denom: IBC_TOKEN_DENOM.into(),
+            amount: Uint128::new(20_000),
+        },
+    ];
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(10_000),
+        min_liquidity: Uint128::new(10_000),
+        max_token2: Uint128::new(10_000),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[
+                Coin {
+                    denom: NATIVE_TOKEN_DENOM.into(),
+                    amount: Uint128::new(10_000),
+                },
+                Coin {
+                    denom: IBC_TOKEN_DENOM.into(),
+                    amount: Uint128::new(10_000),
+                },
+            ],
+        )
+        .unwrap();
+
+    let update_msg = ExecuteMsg::UpdateReferralConfig {
+        max_referral_commission: Decimal::from_str("0.5").unwrap(),
+    };
+    let err = router
+        .execute_contract(partner.clone(), amm_addr.clone(), &update_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &update_msg, &[])
+        .unwrap();
+    let fee = get_fee(&router, &amm_addr);
+    assert_eq!(
+        fee.max_referral_commission,
+        Decimal::from_str("0.5").unwrap()
+    );
+
+    let swap_msg = |referrer: &Addr, referral_commission| ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(1_000),
+        min_output: Uint128::zero(),
+        expiration: None,
+        referrer: Some(referrer.to_string()),
+        referral_commission,
+    };
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg(&partner, Some(Decimal::from_str("0.6").unwrap())),
+            &coins(1_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::ReferralCommissionTooHigh {
+            max_referral_commission: Decimal::from_str("0.5").unwrap(),
+            referral_commission: Decimal::from_str("0.6").unwrap(),
+        },
+        err
+    );
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg(&owner, None),
+            &coins(1_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::SelfReferral {}, err);
+
+    // the commission defaults to the maximum, half of the protocol fee of 200
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg(&partner, None),
+            &coins(1_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &partner, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(100)
+    );
+    assert_eq!(
+        bank_balance(
+            &mut router,
+            &Addr::unchecked("dev"),
+            NATIVE_TOKEN_DENOM.to_string()
+        )
+        .amount,
+        Uint128::new(100)
+    );
+
+    let earnings: ReferralEarningsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ReferralEarnings {
+                address: partner.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        earnings,
+        ReferralEarningsResponse {
+            token1_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+            token1_amount: Uint128::new(100),
+            token2_denom: Denom::Native(IBC_TOKEN_DENOM.into()),
+            token2_amount: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn allowlisted_pool() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let trader = Addr::unchecked("trader");
+    let outsider = Addr::unchecked("outsider");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &trader, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &outsider, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let public_at = router.block_info().time.plus_seconds(1000);
+    let config_msg = ExecuteMsg::UpdateAllowlistConfig {
+        enabled: true,
+        public_at: Some(public_at),
+    };
+    let err = router
+        .execute_contract(trader.clone(), amm_addr.clone(), &config_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &config_msg, &[])
+        .unwrap();
+    let config: AllowlistConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::AllowlistConfig {})
+        .unwrap();
+    assert_eq!(
+        config,
+        AllowlistConfigResponse {
+            enabled: true,
+            public_at: Some(public_at),
+        }
+    );
+
+    // the owner has to be allowlisted to provide liquidity as well
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(1000),
+        min_liquidity: Uint128::new(1000),
+        max_token2: Uint128::new(1000),
+        expiration: None,
+    };
+    let liquidity_funds = [
+        Coin::new(1000, NATIVE_TOKEN_DENOM),
+        Coin::new(1000, IBC_TOKEN_DENOM),
+    ];
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &liquidity_funds,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::NotAllowlisted {}, err);
+
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::UpdateAllowlist {
+                add: vec![owner.to_string(), trader.to_string()],
+                remove: vec![],
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &liquidity_funds,
+        )
+        .unwrap();
+
+    let swap_msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(10),
+        min_output: Uint128::zero(),
+        expiration: None,
+        referrer: None,
+        referral_commission: None,
+    };
+    router
+        .execute_contract(
+            trader.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let err = router
+        .execute_contract(
+            outsider.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::NotAllowlisted {}, err);
+
+    let is_allowlisted = |router: &App, address: &Addr| -> bool {
+        let res: IsAllowlistedResponse = router
+            .wrap()
+            .query_wasm_smart(
+                &amm_addr,
+                &QueryMsg::IsAllowlisted {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.allowed
+    };
+    assert!(is_allowlisted(&router, &trader));
+    assert!(!is_allowlisted(&router, &outsider));
+
+    // the pool is public after the launch window
+    router.update_block(|block| block.time = block.time.plus_seconds(1000));
+    assert!(is_allowlisted(&router, &outsider));
+    router
+        .execute_contract(
+            outsider,
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+}
+
+#[test]
+fn locked_liquidity() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    let info = get_info(&router, &amm_addr);
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+
+    // unlock time has to be in the future
+    let now = router.block_info().time;
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidityLocked {
+                token1_amount: Uint128::new(100),
+                min_liquidity: Uint128::new(100),
+                max_token2: Uint128::new(100),
+                expiration: None,
+                unlock_time: now,
+            },
+            &[
+                Coin::new(100, NATIVE_TOKEN_DENOM),
+                Coin::new(100, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidUnlockTime {}, err);
+
+    let first_unlock = now.plus_seconds(100);
+    let second_unlock = now.plus_seconds(200);
+    // token2 is rounded up once the pool has reserves
+    for (amount, token2_amount, unlock_time) in [(100, 100, first_unlock), (50, 51, second_unlock)]
+    {
+        router
+            .execute_contract(
+                owner.clone(),
+                amm_addr.clone(),
+                &ExecuteMsg::AddLiquidityLocked {
+                    token1_amount: Uint128::new(amount),
+                    min_liquidity: Uint128::new(amount),
+                    max_token2: Uint128::new(token2_amount),
+                    expiration: None,
+                    unlock_time,
+                },
+                &[
+                    Coin::new(amount, NATIVE_TOKEN_DENOM),
+                    Coin::new(token2_amount, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+    }
+
+    // LP tokens are held by the pool
+    assert_eq!(
+        lp_token.balance(&router, owner.clone()).unwrap(),
+        Uint128::zero()
+    );
+    assert_eq!(
+        lp_token.balance(&router, amm_addr.clone()).unwrap(),
+        Uint128::new(150)
+    );
+    let locks: LocksResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Locks {
+                owner: owner.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        locks.locks,
+        vec![
+            LockInfo {
+                id: 1,
+                amount: Uint128::new(100),
+                unlock_time: first_unlock,
+            },
+            LockInfo {
+                id: 2,
+                amount: Uint128::new(50),
+                unlock_time: second_unlock,
+            },
+        ]
+    );
+    let locks: LocksResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Locks {
+                owner: owner.to_string(),
+                start_after: Some(1),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(locks.locks.len(), 1);
+    assert_eq!(locks.locks[0].id, 2);
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::WithdrawLocked { lock_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::LockNotExpired {
+            unlock_time: first_unlock
+        },
+        err
+    );
+
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::WithdrawLocked { lock_id: 1 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        lp_token.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(100)
+    );
+
+    // a lock can only be withdrawn once
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::WithdrawLocked { lock_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::LockNotFound { id: 1 }, err);
+
+    let total: TotalLockedResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::TotalLocked {})
+        .unwrap();
+    assert_eq!(total.total_locked, Uint128::new(50));
+}
+
+#[test]
+fn share_and_position() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    // an empty pool has no value
+    let position: PositionResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Position {
+                address: owner.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        position,
+        PositionResponse {
+            lp_balance: Uint128::zero(),
+            token1_amount: Uint128::zero(),
+            token2_amount: Uint128::zero(),
+            pool_share: Decimal::zero(),
+        }
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidity {
+                token1_amount: Uint128::new(300),
+                min_liquidity: Uint128::new(300),
+                max_token2: Uint128::new(600),
+                expiration: None,
+            },
+            &[
+                Coin::new(300, NATIVE_TOKEN_DENOM),
+                Coin::new(600, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap();
+
+    let share: ShareResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Share {
+                lp_amount: Uint128::new(150),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        share,
+        ShareResponse {
+            token1_amount: Uint128::new(150),
+            token2_amount: Uint128::new(300),
+        }
+    );
+
+    let info = get_info(&router, &amm_addr);
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    router
+        .execute_contract(
+            owner.clone(),
+            lp_token.addr(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: user.to_string(),
+                amount: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let position: PositionResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Position {
+                address: user.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        position,
+        PositionResponse {
+            lp_balance: Uint128::new(100),
+            token1_amount: Uint128::new(100),
+            token2_amount: Uint128::new(200),
+            pool_share: Decimal::from_ratio(1u128, 3u128),
+        }
+    );
+}
+
+#[test]
+fn batch() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidity {
+                token1_amount: Uint128::new(1000),
+                min_liquidity: Uint128::new(1000),
+                max_token2: Uint128::new(1000),
+                expiration: None,
+            },
+            &[
+                Coin::new(1000, NATIVE_TOKEN_DENOM),
+                Coin::new(1000, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap();
+    let info = get_info(&router, &amm_addr);
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    router
+        .execute_contract(
+            owner.clone(),
+            lp_token.addr(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: amm_addr.to_string(),
+                amount: Uint128::new(500),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Batch(vec![ExecuteMsg::AddHook {
+                addr: "hook".to_string(),
+                gas_limit: 200_000,
+            }]),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidBatchMessage {}, err);
+
+    let swap_msg = |min_output: u128| ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(100),
+        min_output: Uint128::new(min_output),
+        expiration: None,
+        referrer: None,
+        referral_commission: None,
+    };
+    let remove_msg = ExecuteMsg::RemoveLiquidity {
+        amount: Uint128::new(500),
+        min_token1: Uint128::zero(),
+        min_token2: Uint128::zero(),
+        expiration: None,
+    };
+
+    // the swap needs more juno than the batch was sent
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Batch(vec![swap_msg(1)]),
+            &coins(50, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InsufficientFunds {}, err);
+
+    // a failing step reverts the whole batch
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Batch(vec![remove_msg.clone(), swap_msg(1000)]),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        get_info(&router, &amm_addr).token1_reserve,
+        Uint128::new(1000)
+    );
+
+    // remove liquidity, swap and add back with the proceeds in one transaction
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Batch(vec![
+                remove_msg,
+                swap_msg(1),
+                ExecuteMsg::AddLiquidity {
+                    token1_amount: Uint128::new(400),
+                    min_liquidity: Uint128::new(1),
+                    max_token2: Uint128::new(500),
+                    expiration: None,
+                },
+            ]),
+            &[],
+        )
+        .unwrap();
+    let steps: Vec<_> = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm-hopers_batch_step")
+        .map(|event| get_event_attribute(event, "action"))
+        .collect();
+    assert_eq!(steps, vec!["remove_liquidity", "swap", "add_liquidity"]);
+
+    // the 500 juno and 561 atom removed pay for the swap and the 400 juno and 309 atom
+    // added, the rest is refunded
+    assert_eq!(
+        bank_balance(&mut router, &owner, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(1000)
+    );
+    assert_eq!(
+        bank_balance(&mut router, &owner, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(1252)
+    );
+    // liquidity is minted against the supply after the burn
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(970));
+    assert_eq!(info.token2_reserve, Uint128::new(748));
+    assert_eq!(info.lp_token_supply, Uint128::new(850));
+    assert_eq!(
+        lp_token.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(850)
+    );
+}
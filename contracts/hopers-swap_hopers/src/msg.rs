@@ -1,10 +1,12 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_schema::QueryResponses;
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Coin, Decimal, Timestamp, Uint128};
 
 use cw20::{Denom, Expiration};
 
+pub use hopers_allowlist::{AllowlistConfigResponse, IsAllowlistedResponse};
+
 #[cw_serde]
 pub struct InstantiateMsg {
     pub token1_denom: Denom,
@@ -34,6 +36,19 @@ pub enum ExecuteMsg {
         max_token2: Uint128,
         expiration: Option<Expiration>,
     },
+    /// Adds liquidity like `AddLiquidity`, but the pool holds the LP tokens for the
+    /// sender until `unlock_time`
+    AddLiquidityLocked {
+        token1_amount: Uint128,
+        min_liquidity: Uint128,
+        max_token2: Uint128,
+        expiration: Option<Expiration>,
+        unlock_time: Timestamp,
+    },
+    /// Sends the LP tokens of an expired lock to its owner
+    WithdrawLocked {
+        lock_id: u64,
+    },
     RemoveLiquidity {
         amount: Uint128,
         min_token1: Uint128,
@@ -45,6 +60,10 @@ pub enum ExecuteMsg {
         input_amount: Uint128,
         min_output: Uint128,
         expiration: Option<Expiration>,
+        /// Receives `referral_commission` of the protocol fee, the commission
+        /// defaults to the configured maximum
+        referrer: Option<String>,
+        referral_commission: Option<Decimal>,
     },
     /// Chained swap converting A -> B and B -> C by leveraging two swap contracts
     PassThroughSwap {
@@ -60,6 +79,34 @@ pub enum ExecuteMsg {
         recipient: String,
         min_token: Uint128,
         expiration: Option<Expiration>,
+        referrer: Option<String>,
+        referral_commission: Option<Decimal>,
+    },
+    /// Swap and send the output token over IBC to `remote_recipient` on the chain
+    /// behind `ibc_channel`. Only available when the output token is native. If the
+    /// transfer fails or times out, the output can be claimed with `ClaimIbcRefunds`.
+    /// Requires the ibc-hooks middleware, which reports the result of the transfer
+    SwapAndIbcTransfer {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        min_token: Uint128,
+        ibc_channel: String,
+        remote_recipient: String,
+        timeout_seconds: u64,
+        expiration: Option<Expiration>,
+        referrer: Option<String>,
+        referral_commission: Option<Decimal>,
+    },
+    /// Restricts trading and providing liquidity to allowlisted addresses until
+    /// `public_at`. Owner only
+    UpdateAllowlistConfig {
+        enabled: bool,
+        public_at: Option<Timestamp>,
+    },
+    /// Adds and removes addresses of the allowlist in bulk. Owner only
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
     },
     UpdateConfig {
         owner: Option<String>,
@@ -68,6 +115,95 @@ pub enum ExecuteMsg {
         fee_percent_denominator: Uint128,
         dev_wallet_lists: Vec<WalletInfo>,
     },
+    /// Sets the maximum share of the protocol fee paid to referrers. Owner only
+    UpdateReferralConfig {
+        max_referral_commission: Decimal,
+    },
+    /// Registers a contract notified after every swap, add or remove, running with at
+    /// most `gas_limit` gas. Owner only
+    AddHook {
+        addr: String,
+        gas_limit: u64,
+    },
+    RemoveHook {
+        addr: String,
+    },
+    /// Sends the outputs of failed or timed out IBC transfers to the sender of the swap
+    ClaimIbcRefunds {},
+    /// Executes pool operations atomically in order. Native funds sent with the batch and
+    /// native outputs of earlier steps pay for later steps, the rest is refunded
+    Batch(Vec<ExecuteMsg>),
+}
+
+/// Sent to every registered hook after pool activity
+#[cw_serde]
+pub enum PoolHookMsg {
+    Swap {
+        trader: String,
+        recipient: String,
+        input_denom: Denom,
+        output_denom: Denom,
+        input_amount: Uint128,
+        output_amount: Uint128,
+        token1_reserve: Uint128,
+        token2_reserve: Uint128,
+    },
+    AddLiquidity {
+        provider: String,
+        token1_amount: Uint128,
+        token2_amount: Uint128,
+        liquidity_amount: Uint128,
+        token1_reserve: Uint128,
+        token2_reserve: Uint128,
+    },
+    RemoveLiquidity {
+        provider: String,
+        token1_amount: Uint128,
+        token2_amount: Uint128,
+        liquidity_amount: Uint128,
+        token1_reserve: Uint128,
+        token2_reserve: Uint128,
+    },
+}
+
+/// Result of an IBC transfer, sent by the ibc-hooks middleware to the `ibc_callback`
+/// contract named in the transfer memo
+#[cw_serde]
+pub enum SudoMsg {
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
+
+/// Swap of the output pool of a pass through swap. Referrals are only supported by
+/// this pool, so only the fields every pool accepts are sent
+#[cw_serde]
+pub enum OutputPoolExecuteMsg {
+    SwapAndSendTo {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        recipient: String,
+        min_token: Uint128,
+        expiration: Option<Expiration>,
+    },
+}
+
+/// Execute message a hook contract has to handle
+#[cw_serde]
+pub enum PoolHookExecuteMsg {
+    PoolHook(PoolHookMsg),
 }
 
 #[cw_serde]
@@ -90,6 +226,41 @@ pub enum QueryMsg {
     },
     #[returns(FeeResponse)]
     Fee {},
+    /// Returns the underlying token amounts of `lp_amount` LP tokens
+    #[returns(ShareResponse)]
+    Share {
+        lp_amount: Uint128,
+    },
+    /// Returns the LP balance of `address` with its underlying value and pool share
+    #[returns(PositionResponse)]
+    Position {
+        address: String,
+    },
+    #[returns(LocksResponse)]
+    Locks {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(TotalLockedResponse)]
+    TotalLocked {},
+    #[returns(AllowlistConfigResponse)]
+    AllowlistConfig {},
+    #[returns(IsAllowlistedResponse)]
+    IsAllowlisted {
+        address: String,
+    },
+    #[returns(HooksResponse)]
+    Hooks {},
+    #[returns(ReferralEarningsResponse)]
+    ReferralEarnings {
+        address: String,
+    },
+    /// Returns the outputs of failed or timed out IBC transfers claimable by `address`
+    #[returns(IbcRefundsResponse)]
+    IbcRefunds {
+        address: String,
+    },
 }
 #[cw_serde]
 pub struct MigrateMsg {
@@ -117,6 +288,7 @@ pub struct FeeResponse {
     pub owner: Option<String>,
     pub total_fee_percent: Decimal,
     pub dev_wallet_lists: Vec<WalletInfo>,
+    pub max_referral_commission: Decimal,
 }
 
 #[cw_serde]
@@ -129,9 +301,65 @@ pub struct Token2ForToken1PriceResponse {
     pub token1_amount: Uint128,
 }
 
+#[cw_serde]
+pub struct ShareResponse {
+    pub token1_amount: Uint128,
+    pub token2_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct PositionResponse {
+    pub lp_balance: Uint128,
+    pub token1_amount: Uint128,
+    pub token2_amount: Uint128,
+    /// Share of the LP token supply, 1 = 100%
+    pub pool_share: Decimal,
+}
+
+#[cw_serde]
+pub struct ReferralEarningsResponse {
+    pub token1_denom: Denom,
+    pub token1_amount: Uint128,
+    pub token2_denom: Denom,
+    pub token2_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct IbcRefundsResponse {
+    pub refunds: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct HookInfo {
+    pub addr: String,
+    pub gas_limit: u64,
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub hooks: Vec<HookInfo>,
+}
+
 #[cw_serde]
 #[derive(Eq)]
 pub struct WalletInfo {
     pub address: String,
     pub ratio: Decimal,
 }
+
+#[cw_serde]
+pub struct LockInfo {
+    pub id: u64,
+    pub amount: Uint128,
+    pub unlock_time: Timestamp,
+}
+
+#[cw_serde]
+pub struct LocksResponse {
+    pub locks: Vec<LockInfo>,
+}
+
+#[cw_serde]
+pub struct TotalLockedResponse {
+    pub total_locked: Uint128,
+}
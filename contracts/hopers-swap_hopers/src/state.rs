@@ -1,9 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
 use cw20::Denom;
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 use crate::msg::WalletInfo;
 
@@ -29,3 +29,52 @@ pub struct Fees {
 }
 
 pub const FEES: Item<Fees> = Item::new("fees");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Hook {
+    pub addr: Addr,
+    /// Gas the hook can use before it fails, without reverting the pool action
+    pub gas_limit: u64,
+}
+
+pub const HOOKS: Item<Vec<Hook>> = Item::new("hooks");
+
+pub const MAX_REFERRAL_COMMISSION: Item<Decimal> = Item::new("max_referral_commission");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct ReferralEarnings {
+    pub token1_amount: Uint128,
+    pub token2_amount: Uint128,
+}
+
+pub const REFERRAL_EARNINGS: Map<&Addr, ReferralEarnings> = Map::new("referral_earnings");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Lock {
+    pub amount: Uint128,
+    pub unlock_time: Timestamp,
+}
+
+/// LP tokens held by the pool for their owner until `unlock_time`, keyed by owner and lock id
+pub const LOCKS: Map<(&Addr, u64), Lock> = Map::new("locks");
+pub const LOCK_COUNT: Item<u64> = Item::new("lock_count");
+pub const TOTAL_LOCKED: Item<Uint128> = Item::new("total_locked");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct IbcTransfer {
+    pub sender: Addr,
+    pub channel_id: String,
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+/// Transfers dispatched by the current transaction in order, until their reply returns the
+/// packet sequence
+pub const PENDING_IBC_TRANSFERS: Item<Vec<IbcTransfer>> = Item::new("pending_ibc_transfers");
+/// Transfers waiting for their acknowledgement, keyed by source channel and packet sequence
+pub const IBC_TRANSFERS: Map<(&str, u64), IbcTransfer> = Map::new("ibc_transfers");
+/// Outputs of failed or timed out transfers, claimable by the sender of the swap
+pub const IBC_REFUNDS: Map<(&Addr, &str), Uint128> = Map::new("ibc_refunds");
+
+/// LP token supply while a batch executes, its mints and burns only happen after it
+pub const BATCH_LP_SUPPLY: Item<Uint128> = Item::new("batch_lp_supply");
@@ -0,0 +1,941 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    attr, entry_point, to_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
+};
+use cw0::parse_reply_instantiate_data;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Denom, Expiration, MinterResponse};
+
+use crate::error::ContractError;
+use crate::msg::{
+    AssetAmount, AssetInfo, ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, SwapPriceResponse, WalletInfo,
+};
+use crate::state::{denom_key, Fees, Token, ASSETS, FEES, LP_TOKEN, OWNER};
+
+// Version info for migration info
+pub const CONTRACT_NAME: &str = "crates.io:hopers-swap-multi";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INSTANTIATE_LP_TOKEN_REPLY_ID: u64 = 0;
+
+const MAX_FEE_PERCENT: &str = "1";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if msg.assets.len() < 2 {
+        return Err(ContractError::InvalidAssets {});
+    }
+    for denom in msg.assets {
+        if let Denom::Cw20(addr) = &denom {
+            deps.api.addr_validate(addr.as_str())?;
+        }
+        let key = denom_key(&denom);
+        if ASSETS.has(deps.storage, &key) {
+            return Err(ContractError::DuplicateAsset { denom: key });
+        }
+        ASSETS.save(
+            deps.storage,
+            &key,
+            &Token {
+                reserve: Uint128::zero(),
+                denom,
+            },
+        )?;
+    }
+
+    let owner = msg.owner.map(|h| deps.api.addr_validate(&h)).transpose()?;
+    OWNER.save(deps.storage, &owner)?;
+
+    let mut total_ratio = Decimal::zero();
+    for dev_wallet in msg.dev_wallet_lists.iter() {
+        deps.api.addr_validate(&dev_wallet.address)?;
+        total_ratio += dev_wallet.ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(ContractError::WrongRatio {});
+    }
+
+    let total_fee_percent =
+        Decimal::from_ratio(msg.fee_percent_numerator, msg.fee_percent_denominator);
+    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
+    if total_fee_percent > max_fee_percent {
+        return Err(ContractError::FeesTooHigh {
+            max_fee_percent,
+            total_fee_percent,
+        });
+    }
+
+    let fees = Fees {
+        fee_percent_numerator: msg.fee_percent_numerator,
+        fee_percent_denominator: msg.fee_percent_denominator,
+        dev_wallet_lists: msg.dev_wallet_lists,
+    };
+    FEES.save(deps.storage, &fees)?;
+
+    let instantiate_lp_token_msg = WasmMsg::Instantiate {
+        code_id: msg.lp_token_code_id,
+        funds: vec![],
+        admin: Some(info.sender.to_string()),
+        label: msg.lp_token_name.clone(),
+        msg: to_binary(&cw20_base::msg::InstantiateMsg {
+            name: msg.lp_token_name,
+            symbol: msg.lp_token_symbol,
+            decimals: 6,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: env.contract.address.into(),
+                cap: None,
+            }),
+            marketing: None,
+        })?,
+    };
+
+    let reply_msg =
+        SubMsg::reply_on_success(instantiate_lp_token_msg, INSTANTIATE_LP_TOKEN_REPLY_ID);
+
+    Ok(Response::new().add_submessage(reply_msg))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::AddLiquidity {
+            liquidity,
+            max_amounts,
+            expiration,
+        } => execute_add_liquidity(deps, &info, env, liquidity, max_amounts, expiration),
+        ExecuteMsg::RemoveLiquidity {
+            amount,
+            min_amounts,
+            expiration,
+        } => execute_remove_liquidity(deps, info, env, amount, min_amounts, expiration),
+        ExecuteMsg::Swap {
+            input_denom,
+            output_denom,
+            input_amount,
+            min_output,
+            expiration,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_denom,
+            output_denom,
+            info.sender.to_string(),
+            min_output,
+            expiration,
+        ),
+        ExecuteMsg::SwapAndSendTo {
+            input_denom,
+            output_denom,
+            input_amount,
+            recipient,
+            min_token,
+            expiration,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_denom,
+            output_denom,
+            recipient,
+            min_token,
+            expiration,
+        ),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            dev_wallet_lists,
+            fee_percent_numerator,
+            fee_percent_denominator,
+        } => execute_update_config(
+            deps,
+            info,
+            owner,
+            fee_percent_numerator,
+            fee_percent_denominator,
+            dev_wallet_lists,
+        ),
+    }
+}
+
+fn check_expiration(
+    expiration: &Option<Expiration>,
+    block: &BlockInfo,
+) -> Result<(), ContractError> {
+    match expiration {
+        Some(e) => {
+            if e.is_expired(block) {
+                return Err(ContractError::MsgExpirationError {});
+            }
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+fn load_assets(storage: &dyn Storage) -> StdResult<Vec<(String, Token)>> {
+    ASSETS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, token) = item?;
+            Ok((String::from_utf8(key)?, token))
+        })
+        .collect()
+}
+
+fn load_asset(storage: &dyn Storage, denom: &Denom) -> Result<(String, Token), ContractError> {
+    let key = denom_key(denom);
+    let token = ASSETS
+        .may_load(storage, &key)?
+        .ok_or(ContractError::UnknownAsset { denom: key.clone() })?;
+    Ok((key, token))
+}
+
+// Matches the given amounts to the pool assets, assets that are not given get zero
+fn get_amounts_by_asset(
+    assets: &[(String, Token)],
+    amounts: &[AssetAmount],
+) -> Result<Vec<Uint128>, ContractError> {
+    let mut seen = HashSet::new();
+    for amount in amounts {
+        let key = denom_key(&amount.denom);
+        if !assets.iter().any(|(asset_key, _)| asset_key == &key) {
+            return Err(ContractError::UnknownAsset { denom: key });
+        }
+        if !seen.insert(key.clone()) {
+            return Err(ContractError::DuplicateAsset { denom: key });
+        }
+    }
+
+    Ok(assets
+        .iter()
+        .map(|(key, _)| {
+            amounts
+                .iter()
+                .find(|amount| &denom_key(&amount.denom) == key)
+                .map(|amount| amount.amount)
+                .unwrap_or_default()
+        })
+        .collect())
+}
+
+fn get_amount_required(
+    max_amount: Uint128,
+    liquidity: Uint128,
+    liquidity_supply: Uint128,
+    reserve: Uint128,
+) -> StdResult<Uint128> {
+    if liquidity_supply.is_zero() {
+        Ok(max_amount)
+    } else {
+        liquidity
+            .checked_mul(reserve)
+            .map_err(StdError::overflow)?
+            .checked_div(liquidity_supply)
+            .map_err(StdError::divide_by_zero)?
+            .checked_add(Uint128::new(1))
+            .map_err(StdError::overflow)
+    }
+}
+
+pub fn execute_add_liquidity(
+    deps: DepsMut,
+    info: &MessageInfo,
+    env: Env,
+    liquidity: Uint128,
+    max_amounts: Vec<AssetAmount>,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let assets = load_assets(deps.storage)?;
+    let max_amounts = get_amounts_by_asset(&assets, &max_amounts)?;
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+    let lp_token_supply = get_lp_token_supply(deps.as_ref(), &lp_token_addr)?;
+
+    if liquidity.is_zero() {
+        return Err(ContractError::MinLiquidityError {
+            min_liquidity: Uint128::new(1),
+            liquidity_available: liquidity,
+        });
+    }
+
+    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
+    let mut deposited = vec![];
+    for ((key, mut token), max_amount) in assets.into_iter().zip(max_amounts) {
+        // validate funds
+        validate_input_amount(&info.funds, max_amount, &token.denom)?;
+
+        let amount = get_amount_required(max_amount, liquidity, lp_token_supply, token.reserve)?;
+        if amount.is_zero() {
+            return Err(ContractError::EmptyDeposit { denom: key });
+        }
+        if amount > max_amount {
+            return Err(ContractError::MaxTokenError {
+                max_token: max_amount,
+                tokens_required: amount,
+            });
+        }
+
+        // Generate cw20 transfer messages if necessary, refund unused native tokens
+        match &token.denom {
+            Denom::Cw20(addr) => transfer_msgs.push(get_cw20_transfer_from_msg(
+                &info.sender,
+                &env.contract.address,
+                addr,
+                amount,
+            )?),
+            Denom::Native(denom) => {
+                if amount < max_amount {
+                    transfer_msgs.push(get_bank_transfer_to_msg(
+                        &info.sender,
+                        denom,
+                        max_amount - amount,
+                    ))
+                }
+            }
+        }
+
+        token.reserve = token
+            .reserve
+            .checked_add(amount)
+            .map_err(StdError::overflow)?;
+        ASSETS.save(deps.storage, &key, &token)?;
+        deposited.push(format!("{}{}", amount, key));
+    }
+
+    let mint_msg = mint_lp_tokens(&info.sender, liquidity, &lp_token_addr)?;
+    Ok(Response::new()
+        .add_messages(transfer_msgs)
+        .add_message(mint_msg)
+        .add_attributes(vec![
+            attr("action", "add_liquidity"),
+            attr("amounts", deposited.join(",")),
+            attr("liquidity_received", liquidity),
+        ]))
+}
+
+fn get_lp_token_supply(deps: Deps, lp_token_addr: &Addr) -> StdResult<Uint128> {
+    let resp: cw20::TokenInfoResponse = deps
+        .querier
+        .query_wasm_smart(lp_token_addr, &cw20_base::msg::QueryMsg::TokenInfo {})?;
+    Ok(resp.total_supply)
+}
+
+fn mint_lp_tokens(
+    recipient: &Addr,
+    liquidity_amount: Uint128,
+    lp_token_address: &Addr,
+) -> StdResult<CosmosMsg> {
+    let mint_msg = cw20_base::msg::ExecuteMsg::Mint {
+        recipient: recipient.into(),
+        amount: liquidity_amount,
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: lp_token_address.to_string(),
+        msg: to_binary(&mint_msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn get_token_balance(deps: Deps, contract: &Addr, addr: &Addr) -> StdResult<Uint128> {
+    let resp: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+        contract,
+        &cw20_base::msg::QueryMsg::Balance {
+            address: addr.to_string(),
+        },
+    )?;
+    Ok(resp.balance)
+}
+
+fn validate_input_amount(
+    actual_funds: &[Coin],
+    given_amount: Uint128,
+    given_denom: &Denom,
+) -> Result<(), ContractError> {
+    match given_denom {
+        Denom::Cw20(_) => Ok(()),
+        Denom::Native(denom) => {
+            let actual = get_amount_for_denom(actual_funds, denom);
+            if actual.amount != given_amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            if &actual.denom != denom {
+                return Err(ContractError::IncorrectNativeDenom {
+                    provided: actual.denom,
+                    required: denom.to_string(),
+                });
+            };
+            Ok(())
+        }
+    }
+}
+
+fn get_cw20_transfer_from_msg(
+    owner: &Addr,
+    recipient: &Addr,
+    token_addr: &Addr,
+    token_amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    // create transfer cw20 msg
+    let transfer_cw20_msg = Cw20ExecuteMsg::TransferFrom {
+        owner: owner.into(),
+        recipient: recipient.into(),
+        amount: token_amount,
+    };
+    let exec_cw20_transfer = WasmMsg::Execute {
+        contract_addr: token_addr.into(),
+        msg: to_binary(&transfer_cw20_msg)?,
+        funds: vec![],
+    };
+    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
+    Ok(cw20_transfer_cosmos_msg)
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: Option<String>,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+    dev_wallet_lists: Vec<WalletInfo>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner_addr = new_owner
+        .as_ref()
+        .map(|h| deps.api.addr_validate(h))
+        .transpose()?;
+    OWNER.save(deps.storage, &new_owner_addr)?;
+
+    let total_fee_percent = Decimal::from_ratio(fee_percent_numerator, fee_percent_denominator);
+    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
+    if total_fee_percent > max_fee_percent {
+        return Err(ContractError::FeesTooHigh {
+            max_fee_percent,
+            total_fee_percent,
+        });
+    }
+
+    let mut total_ratio = Decimal::zero();
+    for dev_wallet in dev_wallet_lists.iter() {
+        deps.api.addr_validate(&dev_wallet.address)?;
+        total_ratio += dev_wallet.ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(ContractError::WrongRatio {});
+    }
+
+    let updated_fees = Fees {
+        dev_wallet_lists,
+        fee_percent_numerator,
+        fee_percent_denominator,
+    };
+    FEES.save(deps.storage, &updated_fees)?;
+
+    let new_owner = new_owner.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("new_owner", new_owner),
+        attr("fee_percent", total_fee_percent.to_string()),
+    ]))
+}
+
+pub fn execute_remove_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    amount: Uint128,
+    min_amounts: Vec<AssetAmount>,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+    let balance = get_token_balance(deps.as_ref(), &lp_token_addr, &info.sender)?;
+    let lp_token_supply = get_lp_token_supply(deps.as_ref(), &lp_token_addr)?;
+    let assets = load_assets(deps.storage)?;
+    let min_amounts = get_amounts_by_asset(&assets, &min_amounts)?;
+
+    if amount > balance {
+        return Err(ContractError::InsufficientLiquidityError {
+            requested: amount,
+            available: balance,
+        });
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut returned = vec![];
+    for ((key, mut token), min_amount) in assets.into_iter().zip(min_amounts) {
+        let token_amount = amount
+            .checked_mul(token.reserve)
+            .map_err(StdError::overflow)?
+            .checked_div(lp_token_supply)
+            .map_err(StdError::divide_by_zero)?;
+        if token_amount < min_amount {
+            return Err(ContractError::MinTokenError {
+                denom: key,
+                requested: min_amount,
+                available: token_amount,
+            });
+        }
+
+        token.reserve = token
+            .reserve
+            .checked_sub(token_amount)
+            .map_err(StdError::overflow)?;
+        ASSETS.save(deps.storage, &key, &token)?;
+
+        if !token_amount.is_zero() {
+            msgs.push(get_transfer_to_msg(
+                &info.sender,
+                &token.denom,
+                token_amount,
+            )?);
+        }
+        returned.push(format!("{}{}", token_amount, key));
+    }
+
+    msgs.push(get_burn_msg(&lp_token_addr, &info.sender, amount)?);
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "remove_liquidity"),
+        attr("liquidity_burned", amount),
+        attr("amounts", returned.join(",")),
+    ]))
+}
+
+fn get_burn_msg(contract: &Addr, owner: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = cw20_base::msg::ExecuteMsg::BurnFrom {
+        owner: owner.to_string(),
+        amount,
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: contract.to_string(),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn get_cw20_transfer_to_msg(
+    recipient: &Addr,
+    token_addr: &Addr,
+    token_amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    // create transfer cw20 msg
+    let transfer_cw20_msg = Cw20ExecuteMsg::Transfer {
+        recipient: recipient.into(),
+        amount: token_amount,
+    };
+    let exec_cw20_transfer = WasmMsg::Execute {
+        contract_addr: token_addr.into(),
+        msg: to_binary(&transfer_cw20_msg)?,
+        funds: vec![],
+    };
+    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
+    Ok(cw20_transfer_cosmos_msg)
+}
+
+fn get_bank_transfer_to_msg(recipient: &Addr, denom: &str, native_amount: Uint128) -> CosmosMsg {
+    let transfer_bank_msg = cosmwasm_std::BankMsg::Send {
+        to_address: recipient.into(),
+        amount: vec![Coin {
+            denom: denom.to_string(),
+            amount: native_amount,
+        }],
+    };
+
+    let transfer_bank_cosmos_msg: CosmosMsg = transfer_bank_msg.into();
+    transfer_bank_cosmos_msg
+}
+
+fn get_transfer_to_msg(recipient: &Addr, denom: &Denom, amount: Uint128) -> StdResult<CosmosMsg> {
+    match denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(recipient, addr, amount),
+        Denom::Native(denom) => Ok(get_bank_transfer_to_msg(recipient, denom, amount)),
+    }
+}
+
+fn get_fee_transfer_msg(
+    sender: &Addr,
+    recipient: &Addr,
+    fee_denom: &Denom,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match fee_denom {
+        Denom::Cw20(addr) => get_cw20_transfer_from_msg(sender, recipient, addr, amount),
+        Denom::Native(denom) => Ok(get_bank_transfer_to_msg(recipient, denom, amount)),
+    }
+}
+
+/// Constant product price between two assets of the pool. With equal weights this keeps
+/// the product of all reserves constant, as the other reserves do not change.
+pub fn get_input_price(
+    input_amount: Uint128,
+    input_reserve: Uint128,
+    output_reserve: Uint128,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+) -> StdResult<Uint128> {
+    if input_reserve == Uint128::zero() || output_reserve == Uint128::zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    };
+
+    let input_amount_with_fee = input_amount
+        .checked_mul(fee_percent_denominator - fee_percent_numerator)
+        .map_err(StdError::overflow)?;
+    let numerator = input_amount_with_fee
+        .checked_mul(output_reserve)
+        .map_err(StdError::overflow)?;
+    let denominator = input_reserve
+        .checked_mul(fee_percent_denominator)
+        .map_err(StdError::overflow)?
+        .checked_add(input_amount_with_fee)
+        .map_err(StdError::overflow)?;
+
+    numerator
+        .checked_div(denominator)
+        .map_err(StdError::divide_by_zero)
+}
+
+pub fn get_protocol_fee_amount(
+    input_amount: Uint128,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+) -> StdResult<Uint128> {
+    if fee_percent_numerator.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    input_amount
+        .checked_mul(fee_percent_numerator)
+        .map_err(StdError::overflow)?
+        .checked_div(fee_percent_denominator)
+        .map_err(StdError::divide_by_zero)
+}
+
+fn get_amount_for_denom(coins: &[Coin], denom: &str) -> Coin {
+    let amount: Uint128 = coins
+        .iter()
+        .filter(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .sum();
+    Coin {
+        amount,
+        denom: denom.to_string(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap(
+    deps: DepsMut,
+    info: &MessageInfo,
+    input_amount: Uint128,
+    env: Env,
+    input_denom: Denom,
+    output_denom: Denom,
+    recipient: String,
+    min_token: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    if input_denom == output_denom {
+        return Err(ContractError::SameAsset {});
+    }
+    let (input_key, mut input_token) = load_asset(deps.storage, &input_denom)?;
+    let (output_key, mut output_token) = load_asset(deps.storage, &output_denom)?;
+
+    // validate input_amount if native input token
+    validate_input_amount(&info.funds, input_amount, &input_token.denom)?;
+
+    let fees = FEES.load(deps.storage)?;
+
+    let token_bought = get_input_price(
+        input_amount,
+        input_token.reserve,
+        output_token.reserve,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+
+    if min_token > token_bought {
+        return Err(ContractError::SwapMinError {
+            min: min_token,
+            available: token_bought,
+        });
+    }
+    // Calculate fees
+    let protocol_fee_amount = get_protocol_fee_amount(
+        input_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    let input_amount_minus_protocol_fee = input_amount - protocol_fee_amount;
+
+    let mut msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            input_amount_minus_protocol_fee,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    // Send protocol fee to protocol fee recipient
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if fee_amount > Uint128::zero() {
+            msgs.push(get_fee_transfer_msg(
+                &info.sender,
+                &deps.api.addr_validate(&dev_wallet.address)?,
+                &input_token.denom,
+                fee_amount,
+            )?)
+        }
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    msgs.push(get_transfer_to_msg(
+        &recipient,
+        &output_token.denom,
+        token_bought,
+    )?);
+
+    input_token.reserve = input_token
+        .reserve
+        .checked_add(input_amount_minus_protocol_fee)
+        .map_err(StdError::overflow)?;
+    ASSETS.save(deps.storage, &input_key, &input_token)?;
+
+    output_token.reserve = output_token
+        .reserve
+        .checked_sub(token_bought)
+        .map_err(StdError::overflow)?;
+    ASSETS.save(deps.storage, &output_key, &output_token)?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "swap"),
+        attr("input_denom", input_key),
+        attr("output_denom", output_key),
+        attr("input_amount", input_amount),
+        attr("token_bought", token_bought),
+        attr("protocol_fee_amount", protocol_fee_amount),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Info {} => to_binary(&query_info(deps)?),
+        QueryMsg::SwapPrice {
+            input_denom,
+            output_denom,
+            input_amount,
+        } => to_binary(&query_swap_price(
+            deps,
+            input_denom,
+            output_denom,
+            input_amount,
+        )?),
+        QueryMsg::Fee {} => to_binary(&query_fee(deps)?),
+    }
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let lp_token_address = LP_TOKEN.load(deps.storage)?;
+    let assets = load_assets(deps.storage)?
+        .into_iter()
+        .map(|(_, token)| AssetInfo {
+            denom: token.denom,
+            reserve: token.reserve,
+        })
+        .collect();
+
+    Ok(InfoResponse {
+        assets,
+        lp_token_supply: get_lp_token_supply(deps, &lp_token_address)?,
+        lp_token_address: lp_token_address.into_string(),
+    })
+}
+
+pub fn query_swap_price(
+    deps: Deps,
+    input_denom: Denom,
+    output_denom: Denom,
+    input_amount: Uint128,
+) -> StdResult<SwapPriceResponse> {
+    if input_denom == output_denom {
+        return Err(StdError::generic_err(
+            ContractError::SameAsset {}.to_string(),
+        ));
+    }
+    let (_, input_token) = load_asset(deps.storage, &input_denom)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let (_, output_token) = load_asset(deps.storage, &output_denom)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    let fees = FEES.load(deps.storage)?;
+    let output_amount = get_input_price(
+        input_amount,
+        input_token.reserve,
+        output_token.reserve,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    Ok(SwapPriceResponse { output_amount })
+}
+
+pub fn query_fee(deps: Deps) -> StdResult<FeeResponse> {
+    let fees = FEES.load(deps.storage)?;
+    let owner = OWNER.load(deps.storage)?.map(|o| o.into_string());
+
+    let total_fee_percent =
+        Decimal::from_ratio(fees.fee_percent_numerator, fees.fee_percent_denominator);
+
+    Ok(FeeResponse {
+        owner,
+        total_fee_percent,
+        dev_wallet_lists: fees.dev_wallet_lists,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != INSTANTIATE_LP_TOKEN_REPLY_ID {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    };
+    let res = parse_reply_instantiate_data(msg);
+    match res {
+        Ok(res) => {
+            // Validate contract address
+            let cw20_addr = deps.api.addr_validate(&res.contract_address)?;
+
+            // Save gov token
+            LP_TOKEN.save(deps.storage, &cw20_addr)?;
+
+            Ok(Response::new())
+        }
+        Err(_) => Err(ContractError::InstantiateLpTokenError {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.contract,
+        });
+    }
+
+    if version.version != CONTRACT_VERSION {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.version,
+        });
+    }
+
+    Ok(Response::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Addr;
+
+    #[test]
+    fn test_get_amount_required() {
+        let amount = get_amount_required(
+            Uint128::new(100),
+            Uint128::new(50),
+            Uint128::zero(),
+            Uint128::zero(),
+        )
+        .unwrap();
+        assert_eq!(amount, Uint128::new(100));
+
+        let amount = get_amount_required(
+            Uint128::new(200),
+            Uint128::new(50),
+            Uint128::new(100),
+            Uint128::new(300),
+        )
+        .unwrap();
+        assert_eq!(amount, Uint128::new(151));
+    }
+
+    #[test]
+    fn test_get_amounts_by_asset() {
+        let token = |denom: Denom| Token {
+            reserve: Uint128::zero(),
+            denom,
+        };
+        let assets = vec![
+            ("uatom".to_string(), token(Denom::Native("uatom".into()))),
+            ("ujuno".to_string(), token(Denom::Native("ujuno".into()))),
+            (
+                "usdc".to_string(),
+                token(Denom::Cw20(Addr::unchecked("usdc"))),
+            ),
+        ];
+
+        let amounts = get_amounts_by_asset(
+            &assets,
+            &[
+                AssetAmount {
+                    denom: Denom::Cw20(Addr::unchecked("usdc")),
+                    amount: Uint128::new(3),
+                },
+                AssetAmount {
+                    denom: Denom::Native("uatom".into()),
+                    amount: Uint128::new(1),
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            amounts,
+            vec![Uint128::new(1), Uint128::zero(), Uint128::new(3)]
+        );
+
+        let err = get_amounts_by_asset(
+            &assets,
+            &[AssetAmount {
+                denom: Denom::Native("uosmo".into()),
+                amount: Uint128::new(1),
+            }],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownAsset {
+                denom: "uosmo".to_string()
+            }
+        );
+    }
+}
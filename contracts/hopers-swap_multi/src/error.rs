@@ -0,0 +1,88 @@
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Cw20Error(#[from] cw20_base::ContractError),
+
+    #[error("None Error")]
+    NoneError {},
+
+    #[error("The sum of ratino is not the same as 1")]
+    WrongRatio {},
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+    // Add any other custom errors you like here.
+    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+    #[error("Min liquidity error: requested: {min_liquidity}, available: {liquidity_available}")]
+    MinLiquidityError {
+        min_liquidity: Uint128,
+        liquidity_available: Uint128,
+    },
+
+    #[error("Max token error: max_token: {max_token}, tokens_required: {tokens_required}")]
+    MaxTokenError {
+        max_token: Uint128,
+        tokens_required: Uint128,
+    },
+
+    #[error("Insufficient liquidity error: requested: {requested}, available: {available}")]
+    InsufficientLiquidityError {
+        requested: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Min token error: denom: {denom}, requested: {requested}, available: {available}")]
+    MinTokenError {
+        denom: String,
+        requested: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Incorrect native denom: provided: {provided}, required: {required}")]
+    IncorrectNativeDenom { provided: String, required: String },
+
+    #[error("Swap min error: min: {min}, available: {available}")]
+    SwapMinError { min: Uint128, available: Uint128 },
+
+    #[error("MsgExpirationError")]
+    MsgExpirationError {},
+
+    #[error("Total fee ({total_fee_percent}) percent is higher than max ({max_fee_percent})")]
+    FeesTooHigh {
+        max_fee_percent: Decimal,
+        total_fee_percent: Decimal,
+    },
+
+    #[error("InsufficientFunds")]
+    InsufficientFunds {},
+
+    #[error("Uknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Failed to instantiate lp token")]
+    InstantiateLpTokenError {},
+
+    #[error("A pool needs at least two distinct assets")]
+    InvalidAssets {},
+
+    #[error("Asset {denom} is not in the pool")]
+    UnknownAsset { denom: String },
+
+    #[error("Asset {denom} is listed more than once")]
+    DuplicateAsset { denom: String },
+
+    #[error("The first deposit needs every asset, {denom} is missing")]
+    EmptyDeposit { denom: String },
+
+    #[error("Input and output assets must be different")]
+    SameAsset {},
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+}
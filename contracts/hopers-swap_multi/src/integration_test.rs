@@ -0,0 +1,462 @@
+#![cfg(test)]
+
+use std::borrow::BorrowMut;
+
+use cosmwasm_std::{coins, Addr, Coin, Decimal, Empty, Uint128};
+use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Denom};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+use crate::contract::{get_input_price, get_protocol_fee_amount};
+use crate::error::ContractError;
+use crate::msg::{
+    AssetAmount, AssetInfo, ExecuteMsg, InfoResponse, InstantiateMsg, QueryMsg, SwapPriceResponse,
+    WalletInfo,
+};
+
+const USDC: &str = "uusdc";
+const USDT: &str = "uusdt";
+
+fn mock_app() -> App {
+    App::default()
+}
+
+pub fn contract_amm() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply)
+    .with_migrate(crate::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn get_info(router: &App, contract_addr: &Addr) -> InfoResponse {
+    router
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Info {})
+        .unwrap()
+}
+
+fn instantiate_msg(router: &mut App, assets: Vec<Denom>, owner: &Addr) -> InstantiateMsg {
+    let cw20_id = router.store_code(contract_cw20());
+    InstantiateMsg {
+        assets,
+        lp_token_code_id: cw20_id,
+        owner: Some(owner.to_string()),
+        fee_percent_numerator: Uint128::new(3),
+        fee_percent_denominator: Uint128::new(1000),
+        lp_token_name: "lp_token".to_string(),
+        lp_token_symbol: "lpsymbol".to_string(),
+        dev_wallet_lists: vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    }
+}
+
+// CreateCW20 create new cw20 with given initial balance belonging to owner
+fn create_cw20(
+    router: &mut App,
+    owner: &Addr,
+    name: String,
+    symbol: String,
+    balance: Uint128,
+) -> Cw20Contract {
+    // set up cw20 contract with some tokens
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name,
+        symbol,
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: balance,
+        }],
+        mint: None,
+        marketing: None,
+    };
+    let addr = router
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "CASH", None)
+        .unwrap();
+    Cw20Contract(addr)
+}
+
+fn bank_balance(router: &mut App, addr: &Addr, denom: String) -> Coin {
+    router
+        .wrap()
+        .query_balance(addr.to_string(), denom)
+        .unwrap()
+}
+
+fn asset_amount(denom: Denom, amount: u128) -> AssetAmount {
+    AssetAmount {
+        denom,
+        amount: Uint128::new(amount),
+    }
+}
+
+// Sets up a USDC/USDT/DAI pool with 1_000_000 of each asset
+fn setup() -> (App, Addr, Cw20Contract, Addr) {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = vec![Coin::new(10_000_000, USDC), Coin::new(10_000_000, USDT)];
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let dai = create_cw20(
+        &mut router,
+        &owner,
+        "dai".to_string(),
+        "DAI".to_string(),
+        Uint128::new(10_000_000),
+    );
+
+    let amm_id = router.store_code(contract_amm());
+    let msg = instantiate_msg(
+        &mut router,
+        vec![
+            Denom::Native(USDC.into()),
+            Denom::Native(USDT.into()),
+            Denom::Cw20(dai.addr()),
+        ],
+        &owner,
+    );
+    let amm_addr = router
+        .instantiate_contract(amm_id, owner.clone(), &msg, &[], "amm", None)
+        .unwrap();
+
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(10_000_000),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), dai.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        liquidity: Uint128::new(3_000_000),
+        max_amounts: vec![
+            asset_amount(Denom::Native(USDC.into()), 1_000_000),
+            asset_amount(Denom::Native(USDT.into()), 1_000_000),
+            asset_amount(Denom::Cw20(dai.addr()), 1_000_000),
+        ],
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[Coin::new(1_000_000, USDC), Coin::new(1_000_000, USDT)],
+        )
+        .unwrap();
+
+    (router, owner, dai, amm_addr)
+}
+
+#[test]
+fn test_instantiate() {
+    let (mut router, owner, dai, amm_addr) = setup();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.assets.len(), 3);
+    assert_eq!(info.lp_token_supply, Uint128::new(3_000_000));
+    assert_eq!(info.lp_token_address, "contract2".to_string());
+
+    let amm_id = router.store_code(contract_amm());
+    let msg = instantiate_msg(&mut router, vec![Denom::Native(USDC.into())], &owner);
+    let err = router
+        .instantiate_contract(amm_id, owner.clone(), &msg, &[], "amm", None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidAssets {}, err);
+
+    let msg = instantiate_msg(
+        &mut router,
+        vec![
+            Denom::Cw20(dai.addr()),
+            Denom::Native(USDC.into()),
+            Denom::Cw20(dai.addr()),
+        ],
+        &owner,
+    );
+    let err = router
+        .instantiate_contract(amm_id, owner, &msg, &[], "amm", None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::DuplicateAsset {
+            denom: dai.addr().to_string()
+        },
+        err
+    );
+}
+
+#[test]
+fn add_and_remove_liquidity() {
+    let (mut router, owner, dai, amm_addr) = setup();
+
+    // deposits follow the ratio of the reserves, unused native funds are refunded
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        liquidity: Uint128::new(300_000),
+        max_amounts: vec![
+            asset_amount(Denom::Native(USDC.into()), 150_000),
+            asset_amount(Denom::Native(USDT.into()), 150_000),
+            asset_amount(Denom::Cw20(dai.addr()), 150_000),
+        ],
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[Coin::new(150_000, USDC), Coin::new(150_000, USDT)],
+        )
+        .unwrap();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.lp_token_supply, Uint128::new(3_300_000));
+    for asset in info.assets.iter() {
+        assert_eq!(asset.reserve, Uint128::new(1_100_001));
+    }
+    assert_eq!(
+        bank_balance(&mut router, &owner, USDC.to_string()).amount,
+        Uint128::new(8_899_999)
+    );
+    assert_eq!(
+        dai.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(8_899_999)
+    );
+
+    // every asset is needed
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        liquidity: Uint128::new(300_000),
+        max_amounts: vec![
+            asset_amount(Denom::Native(USDC.into()), 150_000),
+            asset_amount(Denom::Native(USDT.into()), 150_000),
+        ],
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[Coin::new(150_000, USDC), Coin::new(150_000, USDT)],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::MaxTokenError {
+            max_token: Uint128::zero(),
+            tokens_required: Uint128::new(100_001),
+        },
+        err
+    );
+
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(1_650_000),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), lp_token.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    let remove_liquidity_msg = ExecuteMsg::RemoveLiquidity {
+        amount: Uint128::new(1_650_000),
+        min_amounts: vec![asset_amount(Denom::Native(USDT.into()), 600_000)],
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &remove_liquidity_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::MinTokenError {
+            denom: USDT.to_string(),
+            requested: Uint128::new(600_000),
+            available: Uint128::new(550_000),
+        },
+        err
+    );
+
+    let remove_liquidity_msg = ExecuteMsg::RemoveLiquidity {
+        amount: Uint128::new(1_650_000),
+        min_amounts: vec![asset_amount(Denom::Native(USDT.into()), 550_000)],
+        expiration: None,
+    };
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &remove_liquidity_msg, &[])
+        .unwrap();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.lp_token_supply, Uint128::new(1_650_000));
+    for asset in info.assets.iter() {
+        assert_eq!(asset.reserve, Uint128::new(550_001));
+    }
+    assert_eq!(
+        bank_balance(&mut router, &owner, USDT.to_string()).amount,
+        Uint128::new(9_449_999)
+    );
+    assert_eq!(
+        dai.balance(&router, owner).unwrap(),
+        Uint128::new(9_449_999)
+    );
+}
+
+#[test]
+fn swap_between_any_pair() {
+    let (mut router, owner, dai, amm_addr) = setup();
+
+    // native to cw20
+    let price: SwapPriceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::SwapPrice {
+                input_denom: Denom::Native(USDC.into()),
+                output_denom: Denom::Cw20(dai.addr()),
+                input_amount: Uint128::new(10_000),
+            },
+        )
+        .unwrap();
+    let expected = get_input_price(
+        Uint128::new(10_000),
+        Uint128::new(1_000_000),
+        Uint128::new(1_000_000),
+        Uint128::new(3),
+        Uint128::new(1000),
+    )
+    .unwrap();
+    assert_eq!(price.output_amount, expected);
+
+    let swap_msg = ExecuteMsg::Swap {
+        input_denom: Denom::Native(USDC.into()),
+        output_denom: Denom::Cw20(dai.addr()),
+        input_amount: Uint128::new(10_000),
+        min_output: expected,
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10_000, USDC),
+        )
+        .unwrap();
+    assert_eq!(
+        dai.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(9_000_000) + expected
+    );
+
+    let protocol_fee =
+        get_protocol_fee_amount(Uint128::new(10_000), Uint128::new(3), Uint128::new(1000)).unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &Addr::unchecked("dev"), USDC.to_string()).amount,
+        protocol_fee
+    );
+
+    // native to native, the third reserve is untouched
+    let swap_msg = ExecuteMsg::SwapAndSendTo {
+        input_denom: Denom::Native(USDT.into()),
+        output_denom: Denom::Native(USDC.into()),
+        input_amount: Uint128::new(5_000),
+        recipient: "bob".to_string(),
+        min_token: Uint128::zero(),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(5_000, USDT),
+        )
+        .unwrap();
+    let usdc_reserve = Uint128::new(1_010_000) - protocol_fee;
+    let expected_usdc = get_input_price(
+        Uint128::new(5_000),
+        Uint128::new(1_000_000),
+        usdc_reserve,
+        Uint128::new(3),
+        Uint128::new(1000),
+    )
+    .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &Addr::unchecked("bob"), USDC.to_string()).amount,
+        expected_usdc
+    );
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(
+        info.assets,
+        vec![
+            AssetInfo {
+                denom: Denom::Cw20(dai.addr()),
+                reserve: Uint128::new(1_000_000) - expected,
+            },
+            AssetInfo {
+                denom: Denom::Native(USDC.into()),
+                reserve: usdc_reserve - expected_usdc,
+            },
+            AssetInfo {
+                denom: Denom::Native(USDT.into()),
+                reserve: Uint128::new(1_005_000) - Uint128::new(15),
+            },
+        ]
+    );
+
+    let swap_msg = ExecuteMsg::Swap {
+        input_denom: Denom::Native("uosmo".into()),
+        output_denom: Denom::Native(USDC.into()),
+        input_amount: Uint128::new(5_000),
+        min_output: Uint128::zero(),
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &swap_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::UnknownAsset {
+            denom: "uosmo".to_string()
+        },
+        err
+    );
+
+    let swap_msg = ExecuteMsg::Swap {
+        input_denom: Denom::Native(USDC.into()),
+        output_denom: Denom::Native(USDC.into()),
+        input_amount: Uint128::new(5_000),
+        min_output: Uint128::zero(),
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(owner, amm_addr, &swap_msg, &coins(5_000, USDC))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::SameAsset {}, err);
+}
@@ -0,0 +1,5 @@
+pub mod contract;
+pub mod error;
+mod integration_test;
+pub mod msg;
+pub mod state;
@@ -0,0 +1,115 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::QueryResponses;
+
+use cosmwasm_std::{Decimal, Uint128};
+
+use cw20::{Denom, Expiration};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// At least two distinct assets
+    pub assets: Vec<Denom>,
+    pub lp_token_code_id: u64,
+    pub owner: Option<String>,
+    // NOTE: Fees percents are out of 100 e.g., 1 = 1%
+    pub fee_percent_numerator: Uint128,
+    pub fee_percent_denominator: Uint128,
+    pub lp_token_name: String,
+    pub lp_token_symbol: String,
+    pub dev_wallet_lists: Vec<WalletInfo>,
+}
+
+#[cw_serde]
+pub struct AssetAmount {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Mints `liquidity` LP tokens for a deposit of every asset in the ratio of the
+    /// reserves. The first deposit sets the ratio and mints `liquidity` as given.
+    AddLiquidity {
+        liquidity: Uint128,
+        max_amounts: Vec<AssetAmount>,
+        expiration: Option<Expiration>,
+    },
+    /// Burns LP tokens for their share of every asset. Assets missing from
+    /// `min_amounts` have no minimum.
+    RemoveLiquidity {
+        amount: Uint128,
+        min_amounts: Vec<AssetAmount>,
+        expiration: Option<Expiration>,
+    },
+    Swap {
+        input_denom: Denom,
+        output_denom: Denom,
+        input_amount: Uint128,
+        min_output: Uint128,
+        expiration: Option<Expiration>,
+    },
+    SwapAndSendTo {
+        input_denom: Denom,
+        output_denom: Denom,
+        input_amount: Uint128,
+        recipient: String,
+        min_token: Uint128,
+        expiration: Option<Expiration>,
+    },
+    UpdateConfig {
+        owner: Option<String>,
+        fee_percent_numerator: Uint128,
+        fee_percent_denominator: Uint128,
+        dev_wallet_lists: Vec<WalletInfo>,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(InfoResponse)]
+    Info {},
+    #[returns(SwapPriceResponse)]
+    SwapPrice {
+        input_denom: Denom,
+        output_denom: Denom,
+        input_amount: Uint128,
+    },
+    #[returns(FeeResponse)]
+    Fee {},
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub struct AssetInfo {
+    pub denom: Denom,
+    pub reserve: Uint128,
+}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub assets: Vec<AssetInfo>,
+    pub lp_token_supply: Uint128,
+    pub lp_token_address: String,
+}
+
+#[cw_serde]
+pub struct SwapPriceResponse {
+    pub output_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct FeeResponse {
+    pub owner: Option<String>,
+    pub total_fee_percent: Decimal,
+    pub dev_wallet_lists: Vec<WalletInfo>,
+}
+
+#[cw_serde]
+#[derive(Eq)]
+pub struct WalletInfo {
+    pub address: String,
+    pub ratio: Decimal,
+}
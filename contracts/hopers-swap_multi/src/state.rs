@@ -0,0 +1,38 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Item, Map};
+
+use crate::msg::WalletInfo;
+
+pub const LP_TOKEN: Item<Addr> = Item::new("lp_token");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Token {
+    pub reserve: Uint128,
+    pub denom: Denom,
+}
+
+/// Pool assets keyed by `denom_key`
+pub const ASSETS: Map<&str, Token> = Map::new("assets");
+
+pub const OWNER: Item<Option<Addr>> = Item::new("owner");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Fees {
+    pub dev_wallet_lists: Vec<WalletInfo>,
+    pub fee_percent_numerator: Uint128,
+    pub fee_percent_denominator: Uint128,
+}
+
+pub const FEES: Item<Fees> = Item::new("fees");
+
+/// Native denom or cw20 contract address
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => denom.clone(),
+        Denom::Cw20(addr) => addr.to_string(),
+    }
+}
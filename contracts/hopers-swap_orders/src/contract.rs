@@ -1,22 +1,42 @@
+use std::convert::{TryFrom, TryInto};
 use std::str::FromStr;
 
 use cosmwasm_std::{
-    attr, entry_point, to_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps, DepsMut,
-    Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128,
-    WasmMsg,
+    attr, entry_point, to_binary, Addr, Api, Binary, BlockInfo, Coin, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Env, Event, HexBinary, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, Uint256, WasmMsg,
 };
 use cw0::parse_reply_instantiate_data;
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Denom::Cw20;
 use cw20::{Cw20ExecuteMsg, Denom, Expiration, MinterResponse};
 use cw20_base::contract::query_balance;
+use cw_storage_plus::{Bound, PrimaryKey};
+use sha2::{Digest, Sha256};
+
+use hopers_allowlist::{
+    is_allowlisted, query_allowlist_config, query_is_allowlisted, update_allowlist,
+    update_allowlist_config,
+};
 
 use crate::error::ContractError;
 use crate::msg::{
-    ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, MigrateMsg, QueryMsg,
-    Token1ForToken2PriceResponse, Token2ForToken1PriceResponse, TokenSelect, WalletInfo,
+    BatchConfigResponse, BatchSettlement, CommitmentHashResponse, CommitmentInfo,
+    CommitmentsResponse, ConditionalOrderInfo, ConditionalOrdersResponse, DcaPositionInfo,
+    DcaPositionsResponse, ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, MakerInfoResponse,
+    MigrateMsg, OrderTrigger, OtcOfferInfo, OtcOffersResponse, QueryMsg, ScheduledSwapInfo,
+    ScheduledSwapsResponse, SettlementResponse, SignedOrder, SignedOrderHashResponse,
+    SignedOrderStatusResponse, SwapIntent, Token1ForToken2PriceResponse,
+    Token2ForToken1PriceResponse, TokenSelect, TwapResponse, WalletInfo,
+};
+use crate::state::{
+    conditional_orders, dca_positions, otc_offers, scheduled_swaps, BatchConfig, Commitment,
+    ConditionalOrder, DcaPosition, Fees, MakerBalance, OtcOffer, PriceAccumulator, RevealedSwap,
+    ScheduledSwap, Token, BATCH_CONFIG, COMMITMENTS, COMMITMENT_COUNT, CONDITIONAL_ORDER_COUNT,
+    DCA_POSITION_COUNT, DCA_SWAP_TIMES, EPOCH_COMMITMENTS, EPOCH_COMMITMENT_COUNTS, FEES, LP_TOKEN,
+    MAKER_BALANCES, MAKER_KEYS, OTC_OFFER_COUNT, OWNER, PRICE_ACCUMULATOR, PRICE_OBSERVATIONS,
+    SCHEDULED_SWAP_COUNT, SCHEDULED_SWAP_TIMES, SETTLEMENTS, SIGNED_ORDERS, TOKEN1, TOKEN2,
 };
-use crate::state::{Fees, Token, FEES, LP_TOKEN, OWNER, TOKEN1, TOKEN2};
 
 // Version info for migration info
 pub const CONTRACT_NAME: &str = "crates.io:wasmswap";
@@ -26,6 +46,16 @@ const INSTANTIATE_LP_TOKEN_REPLY_ID: u64 = 0;
 
 //const FEE_SCALE_FACTOR: Uint128 = Uint128::new(10_000);
 const MAX_FEE_PERCENT: &str = "1";
+
+// A batch is settled in a single transaction, so the commitments of an epoch are capped
+const MAX_EPOCH_COMMITMENTS: u64 = 50;
+
+// Conditional orders trigger on the average price over this window
+const TWAP_WINDOW_SECONDS: u64 = 600;
+
+// Pagination of the commitments and conditional orders queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 //const FEE_DECIMAL_PRECISION: Uint128 = Uint128::new(10u128.pow(20));
 
 // Note, you can use StdResult in some functions where you do not
@@ -115,6 +145,9 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Accumulate the price of the reserves before this message changes them
+    update_price_accumulator(deps.storage, &env.block)?;
+
     match msg {
         ExecuteMsg::AddLiquidity {
             token1_amount,
@@ -184,6 +217,145 @@ pub fn execute(
             min_token,
             expiration,
         ),
+        ExecuteMsg::UpdateAllowlistConfig { enabled, public_at } => {
+            execute_update_allowlist_config(deps, info, enabled, public_at)
+        }
+        ExecuteMsg::UpdateAllowlist { add, remove } => {
+            execute_update_allowlist(deps, info, add, remove)
+        }
+        ExecuteMsg::UpdateBatchConfig {
+            enabled,
+            epoch_seconds,
+        } => execute_update_batch_config(deps, info, env, enabled, epoch_seconds),
+        ExecuteMsg::CommitSwap {
+            commitment,
+            input_token,
+            deposit,
+        } => execute_commit_swap(deps, info, env, commitment, input_token, deposit),
+        ExecuteMsg::RevealSwap {
+            commitment_id,
+            input_amount,
+            min_output,
+            salt,
+        } => execute_reveal_swap(
+            deps,
+            info,
+            env,
+            commitment_id,
+            input_amount,
+            min_output,
+            salt,
+        ),
+        ExecuteMsg::SettleBatch { epoch } => execute_settle_batch(deps, env, epoch),
+        ExecuteMsg::ClaimRefund { commitment_id } => {
+            execute_claim_refund(deps, info, env, commitment_id)
+        }
+        ExecuteMsg::SubmitConditionalOrder {
+            input_token,
+            input_amount,
+            trigger,
+            min_output,
+        } => execute_submit_conditional_order(
+            deps,
+            info,
+            env,
+            input_token,
+            input_amount,
+            trigger,
+            min_output,
+        ),
+        ExecuteMsg::CancelConditionalOrder { order_id } => {
+            execute_cancel_conditional_order(deps, info, order_id)
+        }
+        ExecuteMsg::ExecuteTriggeredOrders { start_after, limit } => {
+            execute_triggered_orders(deps, env, start_after, limit)
+        }
+        ExecuteMsg::CreateDcaPosition {
+            input_token,
+            deposit,
+            amount_per_swap,
+            interval_seconds,
+            max_slippage,
+        } => execute_create_dca_position(
+            deps,
+            info,
+            env,
+            input_token,
+            deposit,
+            amount_per_swap,
+            interval_seconds,
+            max_slippage,
+        ),
+        ExecuteMsg::TopUpDcaPosition {
+            position_id,
+            amount,
+        } => execute_top_up_dca_position(deps, info, env, position_id, amount),
+        ExecuteMsg::CancelDcaPosition { position_id } => {
+            execute_cancel_dca_position(deps, info, position_id)
+        }
+        ExecuteMsg::ClaimDcaOutput { position_id } => {
+            execute_claim_dca_output(deps, info, position_id)
+        }
+        ExecuteMsg::ExecuteDca { limit } => execute_dca(deps, env, limit),
+        ExecuteMsg::ScheduleSwap {
+            input_token,
+            input_amount,
+            bounty,
+            min_output,
+            execute_at,
+            recipient,
+        } => execute_schedule_swap(
+            deps,
+            info,
+            env,
+            input_token,
+            input_amount,
+            bounty,
+            min_output,
+            execute_at,
+            recipient,
+        ),
+        ExecuteMsg::CancelScheduledSwap { swap_id } => {
+            execute_cancel_scheduled_swap(deps, info, swap_id)
+        }
+        ExecuteMsg::ExecuteScheduled { ids } => execute_scheduled(deps, info, env, ids),
+        ExecuteMsg::SetMakerKey { pubkey } => execute_set_maker_key(deps, info, pubkey),
+        ExecuteMsg::DepositMakerFunds { token, amount } => {
+            execute_deposit_maker_funds(deps, info, env, token, amount)
+        }
+        ExecuteMsg::WithdrawMakerFunds { token, amount } => {
+            execute_withdraw_maker_funds(deps, info, token, amount)
+        }
+        ExecuteMsg::FillSignedOrder {
+            order,
+            signature,
+            fill_amount,
+        } => execute_fill_signed_order(deps, info, env, order, signature, fill_amount),
+        ExecuteMsg::CancelSignedOrder { nonce } => execute_cancel_signed_order(deps, info, nonce),
+        ExecuteMsg::CreateOtcOffer {
+            offer_denom,
+            offer_amount,
+            counterparty,
+            ask_denom,
+            ask_amount,
+            expires,
+        } => execute_create_otc_offer(
+            deps,
+            info,
+            env,
+            offer_denom,
+            offer_amount,
+            counterparty,
+            ask_denom,
+            ask_amount,
+            expires,
+        ),
+        ExecuteMsg::AcceptOtcOffer { offer_id } => {
+            execute_accept_otc_offer(deps, info, env, offer_id)
+        }
+        ExecuteMsg::ReclaimOtcOffer { offer_id } => {
+            execute_reclaim_otc_offer(deps, info, env, offer_id)
+        }
         ExecuteMsg::UpdateConfig {
             owner,
             dev_wallet_lists,
@@ -200,6 +372,192 @@ pub fn execute(
     }
 }
 
+fn check_allowlist(deps: Deps, env: &Env, address: &Addr) -> Result<(), ContractError> {
+    if !is_allowlisted(deps, env, address)? {
+        return Err(ContractError::NotAllowlisted {});
+    }
+    Ok(())
+}
+
+fn check_batch_mode(deps: Deps) -> Result<(), ContractError> {
+    let batch_config = BATCH_CONFIG.may_load(deps.storage)?;
+    if matches!(batch_config, Some(config) if config.enabled) {
+        return Err(ContractError::BatchModeEnabled {});
+    }
+    Ok(())
+}
+
+fn get_current_epoch(config: &BatchConfig, block: &BlockInfo) -> u64 {
+    let elapsed = block.time.seconds() - config.epoch_start.seconds();
+    config.epoch_id + elapsed / config.epoch_seconds
+}
+
+fn get_spot_price(storage: &dyn Storage) -> StdResult<Option<Decimal256>> {
+    let token1 = TOKEN1.load(storage)?;
+    let token2 = TOKEN2.load(storage)?;
+    if token1.reserve.is_zero() || token2.reserve.is_zero() {
+        return Ok(None);
+    }
+    Ok(Some(Decimal256::from_ratio(token2.reserve, token1.reserve)))
+}
+
+// Cumulative price up to the current block, extrapolated with the current reserves
+fn get_price_cumulative(
+    storage: &dyn Storage,
+    accumulator: &PriceAccumulator,
+    block: &BlockInfo,
+) -> StdResult<Decimal256> {
+    let elapsed = block.time.seconds() - accumulator.last_update.seconds();
+    match get_spot_price(storage)? {
+        Some(price) if elapsed > 0 => Ok(accumulator
+            .price_cumulative
+            .checked_add(price.checked_mul(Decimal256::from_ratio(elapsed, 1u64))?)?),
+        _ => Ok(accumulator.price_cumulative),
+    }
+}
+
+fn update_price_accumulator(storage: &mut dyn Storage, block: &BlockInfo) -> StdResult<()> {
+    let accumulator = match PRICE_ACCUMULATOR.may_load(storage)? {
+        Some(accumulator) => accumulator,
+        None => {
+            let accumulator = PriceAccumulator {
+                price_cumulative: Decimal256::zero(),
+                last_update: block.time,
+            };
+            PRICE_OBSERVATIONS.save(
+                storage,
+                block.time.seconds(),
+                &accumulator.price_cumulative,
+            )?;
+            return PRICE_ACCUMULATOR.save(storage, &accumulator);
+        }
+    };
+    if block.time <= accumulator.last_update {
+        return Ok(());
+    }
+
+    let price_cumulative = get_price_cumulative(storage, &accumulator, block)?;
+    PRICE_ACCUMULATOR.save(
+        storage,
+        &PriceAccumulator {
+            price_cumulative,
+            last_update: block.time,
+        },
+    )?;
+    PRICE_OBSERVATIONS.save(storage, block.time.seconds(), &price_cumulative)?;
+
+    // Only the newest observation before the window is needed
+    let window_start = block.time.seconds().saturating_sub(TWAP_WINDOW_SECONDS);
+    let expired = PRICE_OBSERVATIONS
+        .keys_de(
+            storage,
+            None,
+            Some(Bound::inclusive_int(window_start)),
+            Order::Descending,
+        )
+        .skip(1)
+        .collect::<StdResult<Vec<_>>>()?;
+    for time in expired {
+        PRICE_OBSERVATIONS.remove(storage, time);
+    }
+    Ok(())
+}
+
+// Average token2 per token1 price since the newest observation before the window
+fn get_twap(storage: &dyn Storage, block: &BlockInfo) -> StdResult<Option<Decimal256>> {
+    let accumulator = match PRICE_ACCUMULATOR.may_load(storage)? {
+        Some(accumulator) => accumulator,
+        None => return Ok(None),
+    };
+    let window_start = block.time.seconds().saturating_sub(TWAP_WINDOW_SECONDS);
+    let observation = PRICE_OBSERVATIONS
+        .range_de(
+            storage,
+            None,
+            Some(Bound::inclusive_int(window_start)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?;
+    let (time, price_cumulative) = match observation {
+        Some(observation) if observation.0 < block.time.seconds() => observation,
+        _ => return Ok(None),
+    };
+
+    let elapsed = Decimal256::from_ratio(block.time.seconds() - time, 1u64);
+    let twap = get_price_cumulative(storage, &accumulator, block)?
+        .checked_sub(price_cumulative)?
+        .checked_div(elapsed)
+        .map_err(|_| StdError::generic_err("Invalid TWAP window"))?;
+    Ok(Some(twap))
+}
+
+fn is_triggered(order: &ConditionalOrder, twap: Decimal256) -> bool {
+    // Output tokens per input token
+    let price = match order.input_token {
+        TokenSelect::Token1 => twap,
+        TokenSelect::Token2 => match Decimal256::one().checked_div(twap) {
+            Ok(price) => price,
+            Err(_) => return false,
+        },
+    };
+    match &order.trigger {
+        OrderTrigger::StopLoss { price: trigger } => price <= Decimal256::from(*trigger),
+        OrderTrigger::TakeProfit { price: trigger } => price >= Decimal256::from(*trigger),
+    }
+}
+
+// Input and output token of a swap selling `input_token`
+fn load_swap_tokens(storage: &dyn Storage, input_token: &TokenSelect) -> StdResult<(Token, Token)> {
+    match input_token {
+        TokenSelect::Token1 => Ok((TOKEN1.load(storage)?, TOKEN2.load(storage)?)),
+        TokenSelect::Token2 => Ok((TOKEN2.load(storage)?, TOKEN1.load(storage)?)),
+    }
+}
+
+// Output at the TWAP after fees, less the maximum slippage of the position. The spot
+// price can be moved in the same block as the swap, the average can't
+fn get_dca_min_output(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    position: &DcaPosition,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let max_slippage = match position.max_slippage {
+        Some(max_slippage) => max_slippage,
+        None => return Ok(Uint128::zero()),
+    };
+    let twap = get_twap(storage, block)?.ok_or(ContractError::TwapUnavailable {})?;
+    // Output tokens per input token
+    let price = match position.input_token {
+        TokenSelect::Token1 => twap,
+        TokenSelect::Token2 => Decimal256::one()
+            .checked_div(twap)
+            .map_err(|_| ContractError::TwapUnavailable {})?,
+    };
+    let fees = FEES.load(storage)?;
+    let amount_after_fees = amount.multiply_ratio(
+        fees.fee_percent_denominator - fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    );
+    let twap_output =
+        Uint128::try_from(Uint256::from(amount_after_fees) * price).map_err(StdError::from)?;
+    Ok(twap_output * (Decimal::one() - max_slippage))
+}
+
+fn get_commitment_hash(intent: &SwapIntent) -> StdResult<HexBinary> {
+    let hash = Sha256::digest(to_binary(intent)?.as_slice());
+    Ok(HexBinary::from(hash.as_slice()))
+}
+
+fn get_signed_order_hash(contract: &Addr, order: &SignedOrder) -> StdResult<HexBinary> {
+    let hash = Sha256::new()
+        .chain_update(contract.as_bytes())
+        .chain_update(to_binary(order)?.as_slice())
+        .finalize();
+    Ok(HexBinary::from(hash.as_slice()))
+}
+
 fn check_expiration(
     expiration: &Option<Expiration>,
     block: &BlockInfo,
@@ -262,6 +620,7 @@ pub fn execute_add_liquidity(
 ) -> Result<Response, ContractError> {
     let action = "add_liquidity".to_string();
     check_expiration(&expiration, &env.block)?;
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
 
     let token1 = TOKEN1.load(deps.storage)?;
     let token2 = TOKEN2.load(deps.storage)?;
@@ -299,11 +658,11 @@ pub fn execute_add_liquidity(
 
     // Generate cw20 transfer messages if necessary
     let mut transfer_msgs: Vec<CosmosMsg> = vec![];
-    if let Cw20(addr) = token1.denom {
+    if let Cw20(addr) = &token1.denom {
         transfer_msgs.push(get_cw20_transfer_from_msg(
             &info.sender,
             &env.contract.address,
-            &addr,
+            addr,
             token1_amount,
         )?)
     }
@@ -317,11 +676,11 @@ pub fn execute_add_liquidity(
     }
 
     // Refund token 2 if is a native token and not all is spent
-    if let Denom::Native(denom) = token2.denom {
+    if let Denom::Native(denom) = &token2.denom {
         if token2_amount < max_token2 {
             transfer_msgs.push(get_bank_transfer_to_msg(
                 &info.sender,
-                &denom,
+                denom,
                 max_token2 - token2_amount,
             ))
         }
@@ -336,10 +695,21 @@ pub fn execute_add_liquidity(
         Ok(token2)
     })?;
 
+    let event = Event::new("hopers_add_liquidity")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", info.sender.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", token1_amount)
+        .add_attribute("token2_amount", token2_amount)
+        .add_attribute("liquidity_amount", liquidity_amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
     let mint_msg = mint_lp_tokens(&info.sender, liquidity_amount, &lp_token_addr)?;
     Ok(Response::new()
         .add_messages(transfer_msgs)
         .add_message(mint_msg)
+        .add_event(event)
         .add_attributes(vec![
             attr("action", action),
             attr("token1_amount", token1_amount),
@@ -405,99 +775,1466 @@ fn validate_input_amount(
     }
 }
 
-fn get_cw20_transfer_from_msg(
-    owner: &Addr,
-    recipient: &Addr,
-    token_addr: &Addr,
-    token_amount: Uint128,
-) -> StdResult<CosmosMsg> {
-    // create transfer cw20 msg
-    let transfer_cw20_msg = Cw20ExecuteMsg::TransferFrom {
-        owner: owner.into(),
-        recipient: recipient.into(),
-        amount: token_amount,
-    };
-    let exec_cw20_transfer = WasmMsg::Execute {
-        contract_addr: token_addr.into(),
-        msg: to_binary(&transfer_cw20_msg)?,
-        funds: vec![],
-    };
-    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
-    Ok(cw20_transfer_cosmos_msg)
+fn get_cw20_transfer_from_msg(
+    owner: &Addr,
+    recipient: &Addr,
+    token_addr: &Addr,
+    token_amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    // create transfer cw20 msg
+    let transfer_cw20_msg = Cw20ExecuteMsg::TransferFrom {
+        owner: owner.into(),
+        recipient: recipient.into(),
+        amount: token_amount,
+    };
+    let exec_cw20_transfer = WasmMsg::Execute {
+        contract_addr: token_addr.into(),
+        msg: to_binary(&transfer_cw20_msg)?,
+        funds: vec![],
+    };
+    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
+    Ok(cw20_transfer_cosmos_msg)
+}
+
+fn get_cw20_increase_allowance_msg(
+    token_addr: &Addr,
+    spender: &Addr,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> StdResult<CosmosMsg> {
+    // create transfer cw20 msg
+    let increase_allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: spender.to_string(),
+        amount,
+        expires,
+    };
+    let exec_allowance = WasmMsg::Execute {
+        contract_addr: token_addr.into(),
+        msg: to_binary(&increase_allowance_msg)?,
+        funds: vec![],
+    };
+    Ok(exec_allowance.into())
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: Option<String>,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+    dev_wallet_lists: Vec<WalletInfo>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner_addr = new_owner
+        .as_ref()
+        .map(|h| deps.api.addr_validate(h))
+        .transpose()?;
+    OWNER.save(deps.storage, &new_owner_addr)?;
+
+    let num = fee_percent_numerator.u128();
+    let den = fee_percent_denominator.u128();
+
+    let total_fee_percent = Decimal::from_ratio(num, den);
+    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
+    if total_fee_percent > max_fee_percent {
+        return Err(ContractError::FeesTooHigh {
+            max_fee_percent,
+            total_fee_percent,
+        });
+    }
+
+    let mut total_ratio = Decimal::zero();
+    for dev_wallet in dev_wallet_lists.clone() {
+        deps.api.addr_validate(&dev_wallet.address)?;
+        total_ratio = total_ratio + dev_wallet.ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(ContractError::WrongRatio {});
+    }
+
+    let updated_fees = Fees {
+        dev_wallet_lists,
+        fee_percent_numerator,
+        fee_percent_denominator,
+    };
+    FEES.save(deps.storage, &updated_fees)?;
+
+    let new_owner = new_owner.unwrap_or_else(|| "".to_string());
+    Ok(Response::new().add_attributes(vec![
+        attr("new_owner", new_owner),
+        attr("fee_percent", total_fee_percent.to_string()),
+    ]))
+}
+
+pub fn execute_update_allowlist_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+    public_at: Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(update_allowlist_config(deps, enabled, public_at)?)
+}
+
+pub fn execute_update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(update_allowlist(deps, add, remove)?)
+}
+
+pub fn execute_update_batch_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    enabled: bool,
+    epoch_seconds: u64,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if epoch_seconds == 0 {
+        return Err(ContractError::InvalidEpochDuration {});
+    }
+
+    // Keep the numbering of open batches, the new duration applies from the current epoch on
+    let (epoch_id, epoch_start) = match BATCH_CONFIG.may_load(deps.storage)? {
+        Some(config) => {
+            let epoch_id = get_current_epoch(&config, &env.block);
+            let epoch_start = config
+                .epoch_start
+                .plus_seconds((epoch_id - config.epoch_id) * config.epoch_seconds);
+            (epoch_id, epoch_start)
+        }
+        None => (0, env.block.time),
+    };
+    BATCH_CONFIG.save(
+        deps.storage,
+        &BatchConfig {
+            enabled,
+            epoch_seconds,
+            epoch_id,
+            epoch_start,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_batch_config"),
+        attr("enabled", enabled.to_string()),
+        attr("epoch_seconds", epoch_seconds.to_string()),
+    ]))
+}
+
+pub fn execute_commit_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    commitment: HexBinary,
+    input_token_enum: TokenSelect,
+    deposit: Uint128,
+) -> Result<Response, ContractError> {
+    let config = BATCH_CONFIG
+        .may_load(deps.storage)?
+        .filter(|config| config.enabled)
+        .ok_or(ContractError::BatchModeDisabled {})?;
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
+    if deposit.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let input_token = match input_token_enum {
+        TokenSelect::Token1 => TOKEN1,
+        TokenSelect::Token2 => TOKEN2,
+    }
+    .load(deps.storage)?;
+    validate_input_amount(&info.funds, deposit, &input_token.denom)?;
+
+    let epoch = get_current_epoch(&config, &env.block);
+    let epoch_commitments = EPOCH_COMMITMENT_COUNTS
+        .may_load(deps.storage, epoch)?
+        .unwrap_or_default();
+    if epoch_commitments >= MAX_EPOCH_COMMITMENTS {
+        return Err(ContractError::TooManyCommitments {
+            epoch,
+            max: MAX_EPOCH_COMMITMENTS,
+        });
+    }
+    EPOCH_COMMITMENT_COUNTS.save(deps.storage, epoch, &(epoch_commitments + 1))?;
+
+    let id = COMMITMENT_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    COMMITMENT_COUNT.save(deps.storage, &id)?;
+    COMMITMENTS.save(
+        deps.storage,
+        id,
+        &Commitment {
+            owner: info.sender.clone(),
+            epoch,
+            commitment,
+            input_token: input_token_enum,
+            deposit,
+            revealed: None,
+        },
+    )?;
+    EPOCH_COMMITMENTS.save(deps.storage, (epoch, id), &true)?;
+
+    let msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            deposit,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "commit_swap"),
+        attr("commitment_id", id.to_string()),
+        attr("epoch", epoch.to_string()),
+    ]))
+}
+
+pub fn execute_reveal_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    commitment_id: u64,
+    input_amount: Uint128,
+    min_output: Uint128,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let mut commitment = COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound { id: commitment_id })?;
+    if commitment.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if commitment.revealed.is_some() {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+
+    let config = BATCH_CONFIG.load(deps.storage)?;
+    let reveal_epoch = commitment.epoch + 1;
+    if get_current_epoch(&config, &env.block) != reveal_epoch {
+        return Err(ContractError::OutsideRevealWindow {
+            id: commitment_id,
+            reveal_epoch,
+        });
+    }
+
+    let intent = SwapIntent {
+        sender: info.sender.to_string(),
+        input_amount,
+        min_output,
+        salt,
+    };
+    if get_commitment_hash(&intent)? != commitment.commitment {
+        return Err(ContractError::InvalidReveal {});
+    }
+    if input_amount > commitment.deposit {
+        return Err(ContractError::RevealExceedsDeposit {
+            deposit: commitment.deposit,
+        });
+    }
+
+    commitment.revealed = Some(RevealedSwap {
+        input_amount,
+        min_output,
+    });
+    COMMITMENTS.save(deps.storage, commitment_id, &commitment)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "reveal_swap"),
+        attr("commitment_id", commitment_id.to_string()),
+        attr("input_amount", input_amount),
+        attr("min_output", min_output),
+    ]))
+}
+
+// A revealed swap taking part in the settlement of a batch
+struct BatchOrder {
+    id: u64,
+    input_token: TokenSelect,
+    input_amount: Uint128,
+    net_input: Uint128,
+    protocol_fee: Uint128,
+    min_output: Uint128,
+    output: Uint128,
+}
+
+fn sum_batch_inputs(orders: &[BatchOrder]) -> (Uint128, Uint128) {
+    orders.iter().fold(
+        (Uint128::zero(), Uint128::zero()),
+        |(token1_in, token2_in), order| match order.input_token {
+            TokenSelect::Token1 => (token1_in + order.net_input, token2_in),
+            TokenSelect::Token2 => (token1_in, token2_in + order.net_input),
+        },
+    )
+}
+
+pub fn execute_settle_batch(
+    deps: DepsMut,
+    env: Env,
+    epoch: u64,
+) -> Result<Response, ContractError> {
+    let config = BATCH_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::BatchModeDisabled {})?;
+    let settle_epoch = epoch + 2;
+    if get_current_epoch(&config, &env.block) < settle_epoch {
+        return Err(ContractError::BatchNotSettleable {
+            epoch,
+            settle_epoch,
+        });
+    }
+    if SETTLEMENTS.may_load(deps.storage, epoch)?.is_some() {
+        return Err(ContractError::BatchAlreadySettled { epoch });
+    }
+
+    let commitments = EPOCH_COMMITMENTS
+        .prefix_de(epoch)
+        .keys_de(deps.storage, None, None, Order::Ascending)
+        .map(|id| {
+            let id = id?;
+            Ok((id, COMMITMENTS.load(deps.storage, id)?))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut token1 = TOKEN1.load(deps.storage)?;
+    let mut token2 = TOKEN2.load(deps.storage)?;
+    let fees = FEES.load(deps.storage)?;
+
+    let mut orders = vec![];
+    for (id, commitment) in commitments.iter() {
+        if let Some(revealed) = &commitment.revealed {
+            let protocol_fee = get_protocol_fee_amount(
+                revealed.input_amount,
+                fees.fee_percent_numerator,
+                fees.fee_percent_denominator,
+            )?;
+            orders.push(BatchOrder {
+                id: *id,
+                input_token: commitment.input_token.clone(),
+                input_amount: revealed.input_amount,
+                net_input: revealed.input_amount - protocol_fee,
+                protocol_fee,
+                min_output: revealed.min_output,
+                output: Uint128::zero(),
+            });
+        }
+    }
+    if token1.reserve.is_zero() || token2.reserve.is_zero() {
+        orders.clear();
+    }
+
+    // All swaps of the batch trade at the price (token2_reserve + token2_in) / (token1_reserve
+    // + token1_in), which keeps the product of the reserves. Swaps below their minimum output
+    // are dropped until every remaining swap is satisfied.
+    let (token1_in, token2_in, clearing_price) = loop {
+        let (token1_in, token2_in) = sum_batch_inputs(&orders);
+        let token1_total = token1
+            .reserve
+            .checked_add(token1_in)
+            .map_err(StdError::overflow)?;
+        let token2_total = token2
+            .reserve
+            .checked_add(token2_in)
+            .map_err(StdError::overflow)?;
+        let order_count = orders.len();
+        for order in orders.iter_mut() {
+            order.output = match order.input_token {
+                TokenSelect::Token1 => order.net_input.multiply_ratio(token2_total, token1_total),
+                TokenSelect::Token2 => order.net_input.multiply_ratio(token1_total, token2_total),
+            };
+        }
+        orders.retain(|order| order.output >= order.min_output);
+        if orders.len() == order_count {
+            let clearing_price = if orders.is_empty() {
+                None
+            } else {
+                Some(Decimal::from_ratio(token2_total, token1_total))
+            };
+            break (token1_in, token2_in, clearing_price);
+        }
+    };
+
+    let mut msgs = vec![];
+    let (mut token1_out, mut token2_out) = (Uint128::zero(), Uint128::zero());
+    let (mut token1_fee, mut token2_fee) = (Uint128::zero(), Uint128::zero());
+    for (id, commitment) in commitments.iter() {
+        let (input_denom, output_denom) = match commitment.input_token {
+            TokenSelect::Token1 => (&token1.denom, &token2.denom),
+            TokenSelect::Token2 => (&token2.denom, &token1.denom),
+        };
+        let refund = match orders.iter().find(|order| order.id == *id) {
+            Some(order) => {
+                match order.input_token {
+                    TokenSelect::Token1 => {
+                        token2_out += order.output;
+                        token1_fee += order.protocol_fee;
+                    }
+                    TokenSelect::Token2 => {
+                        token1_out += order.output;
+                        token2_fee += order.protocol_fee;
+                    }
+                }
+                if !order.output.is_zero() {
+                    msgs.push(get_transfer_to_msg(
+                        &commitment.owner,
+                        output_denom,
+                        order.output,
+                    )?);
+                }
+                commitment.deposit - order.input_amount
+            }
+            None => commitment.deposit,
+        };
+        if !refund.is_zero() {
+            msgs.push(get_transfer_to_msg(&commitment.owner, input_denom, refund)?);
+        }
+
+        COMMITMENTS.remove(deps.storage, *id);
+        EPOCH_COMMITMENTS.remove(deps.storage, (epoch, *id));
+    }
+    EPOCH_COMMITMENT_COUNTS.remove(deps.storage, epoch);
+
+    for dev_wallet in fees.dev_wallet_lists {
+        let dev_wallet_addr = deps.api.addr_validate(&dev_wallet.address)?;
+        for (denom, fee) in [(&token1.denom, token1_fee), (&token2.denom, token2_fee)] {
+            let fee_amount = fee * dev_wallet.ratio;
+            if !fee_amount.is_zero() {
+                msgs.push(get_transfer_to_msg(&dev_wallet_addr, denom, fee_amount)?);
+            }
+        }
+    }
+
+    token1.reserve = token1
+        .reserve
+        .checked_add(token1_in)
+        .map_err(StdError::overflow)?
+        .checked_sub(token1_out)
+        .map_err(StdError::overflow)?;
+    token2.reserve = token2
+        .reserve
+        .checked_add(token2_in)
+        .map_err(StdError::overflow)?
+        .checked_sub(token2_out)
+        .map_err(StdError::overflow)?;
+    TOKEN1.save(deps.storage, &token1)?;
+    TOKEN2.save(deps.storage, &token2)?;
+
+    let settlement = BatchSettlement {
+        clearing_price,
+        token1_in,
+        token2_in,
+        token1_out,
+        token2_out,
+        filled: orders.len() as u64,
+        refunded: (commitments.len() - orders.len()) as u64,
+    };
+    SETTLEMENTS.save(deps.storage, epoch, &settlement)?;
+
+    let event = Event::new("hopers_batch_settlement")
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute(
+            "clearing_price",
+            clearing_price.map_or_else(|| "none".to_string(), |price| price.to_string()),
+        )
+        .add_attribute("token1_in", token1_in)
+        .add_attribute("token2_in", token2_in)
+        .add_attribute("token1_out", token1_out)
+        .add_attribute("token2_out", token2_out)
+        .add_attribute("filled", settlement.filled.to_string())
+        .add_attribute("refunded", settlement.refunded.to_string());
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", "settle_batch"),
+            attr("epoch", epoch.to_string()),
+        ]))
+}
+
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    commitment_id: u64,
+) -> Result<Response, ContractError> {
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound { id: commitment_id })?;
+    if commitment.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Revealed intents get one epoch after the batch becomes settleable, so they can't
+    // be pulled out of a batch once the others are known
+    let config = BATCH_CONFIG.load(deps.storage)?;
+    let refund_epoch = match commitment.revealed {
+        Some(_) => commitment.epoch + 3,
+        None => commitment.epoch + 2,
+    };
+    if get_current_epoch(&config, &env.block) < refund_epoch {
+        return Err(ContractError::RefundNotAvailable {
+            id: commitment_id,
+            refund_epoch,
+        });
+    }
+
+    COMMITMENTS.remove(deps.storage, commitment_id);
+    EPOCH_COMMITMENTS.remove(deps.storage, (commitment.epoch, commitment_id));
+
+    let input_token = match commitment.input_token {
+        TokenSelect::Token1 => TOKEN1,
+        TokenSelect::Token2 => TOKEN2,
+    }
+    .load(deps.storage)?;
+    let msg = get_transfer_to_msg(&commitment.owner, &input_token.denom, commitment.deposit)?;
+
+    Ok(Response::new().add_message(msg).add_attributes(vec![
+        attr("action", "claim_refund"),
+        attr("commitment_id", commitment_id.to_string()),
+        attr("refund", commitment.deposit),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_submit_conditional_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    input_token_enum: TokenSelect,
+    input_amount: Uint128,
+    trigger: OrderTrigger,
+    min_output: Uint128,
+) -> Result<Response, ContractError> {
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
+    if input_amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let input_token = match input_token_enum {
+        TokenSelect::Token1 => TOKEN1,
+        TokenSelect::Token2 => TOKEN2,
+    }
+    .load(deps.storage)?;
+    validate_input_amount(&info.funds, input_amount, &input_token.denom)?;
+
+    let id = CONDITIONAL_ORDER_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    CONDITIONAL_ORDER_COUNT.save(deps.storage, &id)?;
+    conditional_orders().save(
+        deps.storage,
+        id,
+        &ConditionalOrder {
+            owner: info.sender.clone(),
+            input_token: input_token_enum,
+            input_amount,
+            trigger,
+            min_output,
+        },
+    )?;
+
+    let msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            input_amount,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "submit_conditional_order"),
+        attr("order_id", id.to_string()),
+    ]))
+}
+
+pub fn execute_cancel_conditional_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_id: u64,
+) -> Result<Response, ContractError> {
+    let order = conditional_orders()
+        .may_load(deps.storage, order_id)?
+        .ok_or(ContractError::ConditionalOrderNotFound { id: order_id })?;
+    if order.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    conditional_orders().remove(deps.storage, order_id)?;
+
+    let input_token = match order.input_token {
+        TokenSelect::Token1 => TOKEN1,
+        TokenSelect::Token2 => TOKEN2,
+    }
+    .load(deps.storage)?;
+    let refund_msg = get_transfer_to_msg(&order.owner, &input_token.denom, order.input_amount)?;
+
+    Ok(Response::new().add_message(refund_msg).add_attributes(vec![
+        attr("action", "cancel_conditional_order"),
+        attr("order_id", order_id.to_string()),
+    ]))
+}
+
+pub fn execute_triggered_orders(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    check_batch_mode(deps.as_ref())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let twap = match get_twap(deps.storage, &env.block)? {
+        Some(twap) => twap,
+        None => {
+            return Ok(Response::new().add_attributes(vec![
+                attr("action", "execute_triggered_orders"),
+                attr("executed", "0"),
+            ]))
+        }
+    };
+
+    // Every call checks at most `limit` orders, keepers page through the rest with
+    // `start_after` from the `last_order_id` attribute
+    let orders = conditional_orders()
+        .range_de(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_order_id = orders.last().map(|(id, _)| *id);
+
+    let mut msgs = vec![];
+    let mut events = vec![];
+    let mut executed = 0;
+    for (id, order) in orders {
+        if !is_triggered(&order, twap) {
+            continue;
+        }
+        // Orders stay open while the pool can't pay their minimum output
+        match swap_escrowed(
+            deps.storage,
+            deps.api,
+            &order.owner,
+            &order.input_token,
+            order.input_amount,
+            order.min_output,
+            true,
+        ) {
+            Ok((_, swap_msgs, event)) => {
+                msgs.extend(swap_msgs);
+                events.push(event.add_attribute("order_id", id.to_string()));
+            }
+            Err(ContractError::SwapMinError { .. }) => continue,
+            Err(err) => return Err(err),
+        }
+        conditional_orders().remove(deps.storage, id)?;
+        executed += 1;
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_events(events)
+        .add_attributes(vec![
+            attr("action", "execute_triggered_orders"),
+            attr("executed", executed.to_string()),
+            attr(
+                "last_order_id",
+                last_order_id.map_or_else(|| "none".to_string(), |id| id.to_string()),
+            ),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_dca_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    input_token_enum: TokenSelect,
+    deposit: Uint128,
+    amount_per_swap: Uint128,
+    interval_seconds: u64,
+    max_slippage: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
+    if deposit.is_zero()
+        || amount_per_swap.is_zero()
+        || interval_seconds == 0
+        || matches!(max_slippage, Some(max_slippage) if max_slippage > Decimal::one())
+    {
+        return Err(ContractError::InvalidDcaPosition {});
+    }
+
+    let (input_token, _) = load_swap_tokens(deps.storage, &input_token_enum)?;
+    validate_input_amount(&info.funds, deposit, &input_token.denom)?;
+
+    let id = DCA_POSITION_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    DCA_POSITION_COUNT.save(deps.storage, &id)?;
+    dca_positions().save(
+        deps.storage,
+        id,
+        &DcaPosition {
+            owner: info.sender.clone(),
+            input_token: input_token_enum,
+            balance: deposit,
+            amount_per_swap,
+            interval_seconds,
+            max_slippage,
+            next_swap_at: env.block.time,
+            claimable: Uint128::zero(),
+        },
+    )?;
+    DCA_SWAP_TIMES.save(deps.storage, (env.block.time.seconds(), id), &true)?;
+
+    let msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            deposit,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "create_dca_position"),
+        attr("position_id", id.to_string()),
+    ]))
+}
+
+fn load_dca_position(
+    storage: &dyn Storage,
+    owner: &Addr,
+    position_id: u64,
+) -> Result<DcaPosition, ContractError> {
+    let position = dca_positions()
+        .may_load(storage, position_id)?
+        .ok_or(ContractError::DcaPositionNotFound { id: position_id })?;
+    if &position.owner != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(position)
+}
+
+pub fn execute_top_up_dca_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    position_id: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut position = load_dca_position(deps.storage, &info.sender, position_id)?;
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let (input_token, _) = load_swap_tokens(deps.storage, &position.input_token)?;
+    validate_input_amount(&info.funds, amount, &input_token.denom)?;
+    // A finished position is due again
+    if position.balance.is_zero() {
+        DCA_SWAP_TIMES.save(
+            deps.storage,
+            (position.next_swap_at.seconds(), position_id),
+            &true,
+        )?;
+    }
+    position.balance = position
+        .balance
+        .checked_add(amount)
+        .map_err(StdError::overflow)?;
+    dca_positions().save(deps.storage, position_id, &position)?;
+
+    let msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            amount,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "top_up_dca_position"),
+        attr("position_id", position_id.to_string()),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_cancel_dca_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let position = load_dca_position(deps.storage, &info.sender, position_id)?;
+    dca_positions().remove(deps.storage, position_id)?;
+    DCA_SWAP_TIMES.remove(deps.storage, (position.next_swap_at.seconds(), position_id));
+
+    let (input_token, output_token) = load_swap_tokens(deps.storage, &position.input_token)?;
+    let mut msgs = vec![];
+    if !position.balance.is_zero() {
+        msgs.push(get_transfer_to_msg(
+            &position.owner,
+            &input_token.denom,
+            position.balance,
+        )?);
+    }
+    if !position.claimable.is_zero() {
+        msgs.push(get_transfer_to_msg(
+            &position.owner,
+            &output_token.denom,
+            position.claimable,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "cancel_dca_position"),
+        attr("position_id", position_id.to_string()),
+        attr("refunded", position.balance),
+        attr("claimed", position.claimable),
+    ]))
+}
+
+pub fn execute_claim_dca_output(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let mut position = load_dca_position(deps.storage, &info.sender, position_id)?;
+    if position.claimable.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let (_, output_token) = load_swap_tokens(deps.storage, &position.input_token)?;
+    let claim_msg = get_transfer_to_msg(&position.owner, &output_token.denom, position.claimable)?;
+    let claimed = position.claimable;
+
+    // Finished positions are closed with their last claim
+    if position.balance.is_zero() {
+        dca_positions().remove(deps.storage, position_id)?;
+    } else {
+        position.claimable = Uint128::zero();
+        dca_positions().save(deps.storage, position_id, &position)?;
+    }
+
+    Ok(Response::new().add_message(claim_msg).add_attributes(vec![
+        attr("action", "claim_dca_output"),
+        attr("position_id", position_id.to_string()),
+        attr("claimed", claimed),
+    ]))
+}
+
+pub fn execute_dca(deps: DepsMut, env: Env, limit: Option<u32>) -> Result<Response, ContractError> {
+    check_batch_mode(deps.as_ref())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // Due positions are taken by next swap time, a skipped position moves behind the
+    // others until its next interval
+    let end = Bound::Exclusive((env.block.time.seconds() + 1, 0u64).joined_key());
+    let due = DCA_SWAP_TIMES
+        .keys_de(deps.storage, None, Some(end), Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs = vec![];
+    let mut events = vec![];
+    let (mut swapped, mut skipped) = (0u32, 0u32);
+    for (swap_time, id) in due {
+        let mut position = dca_positions().load(deps.storage, id)?;
+        let amount = position.amount_per_swap.min(position.balance);
+        let min_output = get_dca_min_output(deps.storage, &env.block, &position, amount)?;
+        position.next_swap_at = env.block.time.plus_seconds(position.interval_seconds);
+
+        match swap_escrowed(
+            deps.storage,
+            deps.api,
+            &position.owner,
+            &position.input_token,
+            amount,
+            min_output,
+            false,
+        ) {
+            Ok((token_bought, swap_msgs, event)) => {
+                position.balance -= amount;
+                position.claimable = position
+                    .claimable
+                    .checked_add(token_bought)
+                    .map_err(StdError::overflow)?;
+                msgs.extend(swap_msgs);
+                events.push(event.add_attribute("dca_position_id", id.to_string()));
+                swapped += 1;
+            }
+            // The interval is skipped while the price impact is above the cap
+            Err(ContractError::SwapMinError { .. }) => skipped += 1,
+            Err(err) => return Err(err),
+        }
+        dca_positions().save(deps.storage, id, &position)?;
+        DCA_SWAP_TIMES.remove(deps.storage, (swap_time, id));
+        if !position.balance.is_zero() {
+            DCA_SWAP_TIMES.save(deps.storage, (position.next_swap_at.seconds(), id), &true)?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_events(events)
+        .add_attributes(vec![
+            attr("action", "execute_dca"),
+            attr("swapped", swapped.to_string()),
+            attr("skipped", skipped.to_string()),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_schedule_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    input_token_enum: TokenSelect,
+    input_amount: Uint128,
+    bounty: Uint128,
+    min_output: Uint128,
+    execute_at: Timestamp,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
+    if input_amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let (input_token, _) = load_swap_tokens(deps.storage, &input_token_enum)?;
+    let escrow = input_amount
+        .checked_add(bounty)
+        .map_err(StdError::overflow)?;
+    validate_input_amount(&info.funds, escrow, &input_token.denom)?;
+
+    let id = SCHEDULED_SWAP_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    SCHEDULED_SWAP_COUNT.save(deps.storage, &id)?;
+    scheduled_swaps().save(
+        deps.storage,
+        id,
+        &ScheduledSwap {
+            owner: info.sender.clone(),
+            recipient,
+            input_token: input_token_enum,
+            input_amount,
+            bounty,
+            min_output,
+            execute_at,
+        },
+    )?;
+    SCHEDULED_SWAP_TIMES.save(deps.storage, (execute_at.seconds(), id), &true)?;
+
+    let msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            escrow,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "schedule_swap"),
+        attr("swap_id", id.to_string()),
+        attr("execute_at", execute_at.to_string()),
+    ]))
+}
+
+pub fn execute_cancel_scheduled_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let swap = scheduled_swaps()
+        .may_load(deps.storage, swap_id)?
+        .ok_or(ContractError::ScheduledSwapNotFound { id: swap_id })?;
+    if swap.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    scheduled_swaps().remove(deps.storage, swap_id)?;
+    SCHEDULED_SWAP_TIMES.remove(deps.storage, (swap.execute_at.seconds(), swap_id));
+
+    let (input_token, _) = load_swap_tokens(deps.storage, &swap.input_token)?;
+    let refund_msg = get_transfer_to_msg(
+        &swap.owner,
+        &input_token.denom,
+        swap.input_amount + swap.bounty,
+    )?;
+
+    Ok(Response::new().add_message(refund_msg).add_attributes(vec![
+        attr("action", "cancel_scheduled_swap"),
+        attr("swap_id", swap_id.to_string()),
+    ]))
+}
+
+pub fn execute_scheduled(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    check_batch_mode(deps.as_ref())?;
+
+    let mut msgs = vec![];
+    let mut events = vec![];
+    let (mut token1_bounty, mut token2_bounty) = (Uint128::zero(), Uint128::zero());
+    let (mut executed, mut skipped, mut failed) = (0u32, 0u32, 0u32);
+    for id in ids {
+        let swap = match scheduled_swaps().may_load(deps.storage, id)? {
+            Some(swap) if swap.execute_at <= env.block.time => swap,
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        match swap_escrowed(
+            deps.storage,
+            deps.api,
+            &swap.recipient,
+            &swap.input_token,
+            swap.input_amount,
+            swap.min_output,
+            true,
+        ) {
+            Ok((_, swap_msgs, event)) => {
+                msgs.extend(swap_msgs);
+                events.push(event.add_attribute("scheduled_swap_id", id.to_string()));
+            }
+            Err(ContractError::SwapMinError { .. }) => {
+                skipped += 1;
+                continue;
+            }
+            // A swap the pool can't execute stays scheduled, the rest of the batch goes on
+            Err(err) => {
+                failed += 1;
+                events.push(
+                    Event::new("hopers_scheduled_swap_failed")
+                        .add_attribute("scheduled_swap_id", id.to_string())
+                        .add_attribute("error", err.to_string()),
+                );
+                continue;
+            }
+        }
+
+        scheduled_swaps().remove(deps.storage, id)?;
+        SCHEDULED_SWAP_TIMES.remove(deps.storage, (swap.execute_at.seconds(), id));
+        match swap.input_token {
+            TokenSelect::Token1 => token1_bounty += swap.bounty,
+            TokenSelect::Token2 => token2_bounty += swap.bounty,
+        }
+        executed += 1;
+    }
+
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    for (denom, bounty) in [
+        (&token1.denom, token1_bounty),
+        (&token2.denom, token2_bounty),
+    ] {
+        if !bounty.is_zero() {
+            msgs.push(get_transfer_to_msg(&info.sender, denom, bounty)?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_events(events)
+        .add_attributes(vec![
+            attr("action", "execute_scheduled"),
+            attr("executed", executed.to_string()),
+            attr("skipped", skipped.to_string()),
+            attr("failed", failed.to_string()),
+        ]))
+}
+
+fn get_maker_balance<'a>(balance: &'a mut MakerBalance, token: &TokenSelect) -> &'a mut Uint128 {
+    match token {
+        TokenSelect::Token1 => &mut balance.token1,
+        TokenSelect::Token2 => &mut balance.token2,
+    }
+}
+
+pub fn execute_set_maker_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    MAKER_KEYS.save(deps.storage, &info.sender, &pubkey)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_maker_key"),
+        attr("maker", info.sender),
+    ]))
+}
+
+pub fn execute_deposit_maker_funds(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    token: TokenSelect,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    let (input_token, _) = load_swap_tokens(deps.storage, &token)?;
+    validate_input_amount(&info.funds, amount, &input_token.denom)?;
+
+    let mut balance = MAKER_BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    *get_maker_balance(&mut balance, &token) += amount;
+    MAKER_BALANCES.save(deps.storage, &info.sender, &balance)?;
+
+    let msgs = match &input_token.denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            amount,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "deposit_maker_funds"),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_withdraw_maker_funds(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: TokenSelect,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut balance = MAKER_BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let token_balance = get_maker_balance(&mut balance, &token);
+    *token_balance = token_balance
+        .checked_sub(amount)
+        .map_err(|_| ContractError::InsufficientFunds {})?;
+    MAKER_BALANCES.save(deps.storage, &info.sender, &balance)?;
+
+    let (output_token, _) = load_swap_tokens(deps.storage, &token)?;
+    let transfer_msg = get_transfer_to_msg(&info.sender, &output_token.denom, amount)?;
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attributes(vec![
+            attr("action", "withdraw_maker_funds"),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn execute_fill_signed_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    order: SignedOrder,
+    signature: Binary,
+    fill_amount: Uint128,
+) -> Result<Response, ContractError> {
+    check_allowlist(deps.as_ref(), &env, &info.sender)?;
+    if order.expires_at <= env.block.time {
+        return Err(ContractError::SignedOrderExpired {});
+    }
+    // A quote given to one counterparty can't be filled by anyone who saw it
+    if let Some(taker) = &order.taker {
+        if deps.api.addr_validate(taker)? != info.sender {
+            return Err(ContractError::NotOrderTaker {});
+        }
+    }
+
+    let maker = deps.api.addr_validate(&order.maker)?;
+    let pubkey = MAKER_KEYS
+        .may_load(deps.storage, &maker)?
+        .ok_or(ContractError::InvalidSignature {})?;
+    let hash = get_signed_order_hash(&env.contract.address, &order)?;
+    if !deps
+        .api
+        .secp256k1_verify(hash.as_slice(), &signature, &pubkey)
+        .map_err(StdError::from)?
+    {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let mut status = SIGNED_ORDERS
+        .may_load(deps.storage, (&maker, order.nonce))?
+        .unwrap_or_default();
+    let remaining = if status.cancelled {
+        Uint128::zero()
+    } else {
+        order.sell_amount.saturating_sub(status.filled)
+    };
+    if fill_amount.is_zero() || fill_amount > remaining {
+        return Err(ContractError::InvalidFillAmount { remaining });
+    }
+    status.filled += fill_amount;
+    SIGNED_ORDERS.save(deps.storage, (&maker, order.nonce), &status)?;
+
+    // The taker pays the buy amount proportional to the fill, rounded up in favor of the maker
+    let taker_amount = order
+        .buy_amount
+        .checked_mul(fill_amount)
+        .map_err(StdError::overflow)?
+        .checked_add(order.sell_amount - Uint128::new(1))
+        .map_err(StdError::overflow)?
+        / order.sell_amount;
+
+    let mut balance = MAKER_BALANCES
+        .may_load(deps.storage, &maker)?
+        .unwrap_or_default();
+    let maker_balance = get_maker_balance(&mut balance, &order.sell_token);
+    *maker_balance = maker_balance
+        .checked_sub(fill_amount)
+        .map_err(|_| ContractError::InsufficientFunds {})?;
+    MAKER_BALANCES.save(deps.storage, &maker, &balance)?;
+
+    let (sell_token, buy_token) = load_swap_tokens(deps.storage, &order.sell_token)?;
+    validate_input_amount(&info.funds, taker_amount, &buy_token.denom)?;
+
+    let mut msgs = vec![];
+    if let Denom::Cw20(addr) = &buy_token.denom {
+        msgs.push(get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            taker_amount,
+        )?);
+    }
+
+    let fees = FEES.load(deps.storage)?;
+    let protocol_fee_amount = get_protocol_fee_amount(
+        taker_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if !fee_amount.is_zero() {
+            msgs.push(get_transfer_to_msg(
+                &deps.api.addr_validate(&dev_wallet.address)?,
+                &buy_token.denom,
+                fee_amount,
+            )?);
+        }
+    }
+    msgs.push(get_transfer_to_msg(
+        &maker,
+        &buy_token.denom,
+        taker_amount - protocol_fee_amount,
+    )?);
+    msgs.push(get_transfer_to_msg(
+        &info.sender,
+        &sell_token.denom,
+        fill_amount,
+    )?);
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "fill_signed_order"),
+        attr("maker", maker),
+        attr("nonce", order.nonce.to_string()),
+        attr("fill_amount", fill_amount),
+        attr("taker_amount", taker_amount),
+        attr("protocol_fee_amount", protocol_fee_amount),
+    ]))
+}
+
+pub fn execute_cancel_signed_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    nonce: u64,
+) -> Result<Response, ContractError> {
+    let mut status = SIGNED_ORDERS
+        .may_load(deps.storage, (&info.sender, nonce))?
+        .unwrap_or_default();
+    status.cancelled = true;
+    SIGNED_ORDERS.save(deps.storage, (&info.sender, nonce), &status)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_signed_order"),
+        attr("nonce", nonce.to_string()),
+    ]))
 }
 
-fn get_cw20_increase_allowance_msg(
-    token_addr: &Addr,
-    spender: &Addr,
-    amount: Uint128,
-    expires: Option<Expiration>,
-) -> StdResult<CosmosMsg> {
-    // create transfer cw20 msg
-    let increase_allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
-        spender: spender.to_string(),
-        amount,
-        expires,
-    };
-    let exec_allowance = WasmMsg::Execute {
-        contract_addr: token_addr.into(),
-        msg: to_binary(&increase_allowance_msg)?,
-        funds: vec![],
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_otc_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    offer_denom: Denom,
+    offer_amount: Uint128,
+    counterparty: String,
+    ask_denom: Denom,
+    ask_amount: Uint128,
+    expires: Expiration,
+) -> Result<Response, ContractError> {
+    if offer_amount.is_zero() || ask_amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    // The escrow can only be reclaimed once the offer expired
+    if matches!(expires, Expiration::Never {}) {
+        return Err(ContractError::OtcOfferNeverExpires {});
+    }
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OtcOfferExpired {});
+    }
+    let counterparty = deps.api.addr_validate(&counterparty)?;
+    validate_input_amount(&info.funds, offer_amount, &offer_denom)?;
+
+    let id = OTC_OFFER_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    OTC_OFFER_COUNT.save(deps.storage, &id)?;
+
+    let msgs = match &offer_denom {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            offer_amount,
+        )?],
+        Denom::Native(_) => vec![],
     };
-    Ok(exec_allowance.into())
+    otc_offers().save(
+        deps.storage,
+        id,
+        &OtcOffer {
+            maker: info.sender,
+            counterparty,
+            offer_denom,
+            offer_amount,
+            ask_denom,
+            ask_amount,
+            expires,
+        },
+    )?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "create_otc_offer"),
+        attr("offer_id", id.to_string()),
+    ]))
 }
 
-pub fn execute_update_config(
+pub fn execute_accept_otc_offer(
     deps: DepsMut,
     info: MessageInfo,
-    new_owner: Option<String>,
-    fee_percent_numerator: Uint128,
-    fee_percent_denominator: Uint128,
-    dev_wallet_lists: Vec<WalletInfo>,
+    env: Env,
+    offer_id: u64,
 ) -> Result<Response, ContractError> {
-    let owner = OWNER.load(deps.storage)?;
-    if Some(info.sender) != owner {
+    let offer = otc_offers()
+        .may_load(deps.storage, offer_id)?
+        .ok_or(ContractError::OtcOfferNotFound { id: offer_id })?;
+    if offer.counterparty != info.sender {
         return Err(ContractError::Unauthorized {});
     }
+    if offer.expires.is_expired(&env.block) {
+        return Err(ContractError::OtcOfferExpired {});
+    }
+    validate_input_amount(&info.funds, offer.ask_amount, &offer.ask_denom)?;
+    otc_offers().remove(deps.storage, offer_id)?;
 
-    let new_owner_addr = new_owner
-        .as_ref()
-        .map(|h| deps.api.addr_validate(h))
-        .transpose()?;
-    OWNER.save(deps.storage, &new_owner_addr)?;
-
-    let num = fee_percent_numerator.u128();
-    let den = fee_percent_denominator.u128();
-
-    let total_fee_percent = Decimal::from_ratio(num, den);
-    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
-    if total_fee_percent > max_fee_percent {
-        return Err(ContractError::FeesTooHigh {
-            max_fee_percent,
-            total_fee_percent,
-        });
+    let mut msgs = vec![];
+    if let Denom::Cw20(addr) = &offer.ask_denom {
+        msgs.push(get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            addr,
+            offer.ask_amount,
+        )?);
     }
 
-    let mut total_ratio = Decimal::zero();
-    for dev_wallet in dev_wallet_lists.clone() {
-        deps.api.addr_validate(&dev_wallet.address)?;
-        total_ratio = total_ratio + dev_wallet.ratio;
+    // The pool's protocol fee is taken from the ask paid by the counterparty
+    let fees = FEES.load(deps.storage)?;
+    let protocol_fee_amount = get_protocol_fee_amount(
+        offer.ask_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if !fee_amount.is_zero() {
+            msgs.push(get_transfer_to_msg(
+                &deps.api.addr_validate(&dev_wallet.address)?,
+                &offer.ask_denom,
+                fee_amount,
+            )?);
+        }
     }
+    msgs.push(get_transfer_to_msg(
+        &offer.maker,
+        &offer.ask_denom,
+        offer.ask_amount - protocol_fee_amount,
+    )?);
+    msgs.push(get_transfer_to_msg(
+        &info.sender,
+        &offer.offer_denom,
+        offer.offer_amount,
+    )?);
 
-    if total_ratio != Decimal::one() {
-        return Err(ContractError::WrongRatio {});
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "accept_otc_offer"),
+        attr("offer_id", offer_id.to_string()),
+        attr("protocol_fee_amount", protocol_fee_amount),
+    ]))
+}
+
+pub fn execute_reclaim_otc_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    offer_id: u64,
+) -> Result<Response, ContractError> {
+    let offer = otc_offers()
+        .may_load(deps.storage, offer_id)?
+        .ok_or(ContractError::OtcOfferNotFound { id: offer_id })?;
+    if offer.maker != info.sender {
+        return Err(ContractError::Unauthorized {});
     }
+    if !offer.expires.is_expired(&env.block) {
+        return Err(ContractError::OtcOfferNotExpired {});
+    }
+    otc_offers().remove(deps.storage, offer_id)?;
 
-    let updated_fees = Fees {
-        dev_wallet_lists,
-        fee_percent_numerator,
-        fee_percent_denominator,
-    };
-    FEES.save(deps.storage, &updated_fees)?;
+    let refund_msg = get_transfer_to_msg(&offer.maker, &offer.offer_denom, offer.offer_amount)?;
 
-    let new_owner = new_owner.unwrap_or_else(|| "".to_string());
-    Ok(Response::new().add_attributes(vec![
-        attr("new_owner", new_owner),
-        attr("fee_percent", total_fee_percent.to_string()),
+    Ok(Response::new().add_message(refund_msg).add_attributes(vec![
+        attr("action", "reclaim_otc_offer"),
+        attr("offer_id", offer_id.to_string()),
     ]))
 }
 
@@ -566,23 +2303,34 @@ pub fn execute_remove_liquidity(
         Ok(token2)
     })?;
 
-    let token1_transfer_msg = match token1.denom {
-        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, &addr, token1_amount)?,
-        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, &denom, token1_amount),
+    let token1_transfer_msg = match &token1.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, addr, token1_amount)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, denom, token1_amount),
     };
-    let token2_transfer_msg = match token2.denom {
-        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, &addr, token2_amount)?,
-        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, &denom, token2_amount),
+    let token2_transfer_msg = match &token2.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, addr, token2_amount)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, denom, token2_amount),
     };
 
     let lp_token_burn_msg = get_burn_msg(&lp_token_addr, &info.sender, amount)?;
 
+    let event = Event::new("hopers_remove_liquidity")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", info.sender.as_str())
+        .add_attribute("token1_denom", denom_to_string(&token1.denom))
+        .add_attribute("token2_denom", denom_to_string(&token2.denom))
+        .add_attribute("token1_amount", token1_amount)
+        .add_attribute("token2_amount", token2_amount)
+        .add_attribute("liquidity_amount", amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
     Ok(Response::new()
         .add_messages(vec![
             token1_transfer_msg,
             token2_transfer_msg,
             lp_token_burn_msg,
         ])
+        .add_event(event)
         .add_attributes(vec![
             attr("action", action),
             attr("liquidity_burned", amount),
@@ -648,6 +2396,110 @@ fn get_fee_transfer_msg(
     }
 }
 
+fn get_transfer_to_msg(recipient: &Addr, denom: &Denom, amount: Uint128) -> StdResult<CosmosMsg> {
+    match denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(recipient, addr, amount),
+        Denom::Native(denom) => Ok(get_bank_transfer_to_msg(recipient, denom, amount)),
+    }
+}
+
+// Swaps input held by the contract, e.g. escrowed for an order, and sends the output to
+// `owner` unless it is kept for a later claim. Nothing is changed if the output is below
+// `min_output`
+fn swap_escrowed(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    owner: &Addr,
+    input_token_enum: &TokenSelect,
+    input_amount: Uint128,
+    min_output: Uint128,
+    send_output: bool,
+) -> Result<(Uint128, Vec<CosmosMsg>, Event), ContractError> {
+    let (input_token_item, output_token_item) = match input_token_enum {
+        TokenSelect::Token1 => (TOKEN1, TOKEN2),
+        TokenSelect::Token2 => (TOKEN2, TOKEN1),
+    };
+    let mut input_token = input_token_item.load(storage)?;
+    let mut output_token = output_token_item.load(storage)?;
+    let fees = FEES.load(storage)?;
+
+    let token_bought = get_input_price(
+        input_amount,
+        input_token.reserve,
+        output_token.reserve,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    if min_output > token_bought {
+        return Err(ContractError::SwapMinError {
+            min: min_output,
+            available: token_bought,
+        });
+    }
+    let protocol_fee_amount = get_protocol_fee_amount(
+        input_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+
+    let mut msgs = vec![];
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if !fee_amount.is_zero() {
+            msgs.push(get_transfer_to_msg(
+                &api.addr_validate(&dev_wallet.address)?,
+                &input_token.denom,
+                fee_amount,
+            )?);
+        }
+    }
+    if send_output {
+        msgs.push(get_transfer_to_msg(
+            owner,
+            &output_token.denom,
+            token_bought,
+        )?);
+    }
+
+    input_token.reserve = input_token
+        .reserve
+        .checked_add(input_amount - protocol_fee_amount)
+        .map_err(StdError::overflow)?;
+    output_token.reserve = output_token
+        .reserve
+        .checked_sub(token_bought)
+        .map_err(StdError::overflow)?;
+    input_token_item.save(storage, &input_token)?;
+    output_token_item.save(storage, &output_token)?;
+
+    let event = Event::new("hopers_swap")
+        .add_attribute("sender", owner.as_str())
+        .add_attribute("recipient", owner.as_str())
+        .add_attribute("input_denom", denom_to_string(&input_token.denom))
+        .add_attribute("output_denom", denom_to_string(&output_token.denom))
+        .add_attribute("input_amount", input_amount)
+        .add_attribute("output_amount", token_bought)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount);
+    let event = add_reserve_attributes(storage, event)?;
+    Ok((token_bought, msgs, event))
+}
+
+fn denom_to_string(denom: &Denom) -> String {
+    match denom {
+        Denom::Cw20(addr) => addr.to_string(),
+        Denom::Native(denom) => denom.clone(),
+    }
+}
+
+// Adds both reserves after the action, so the pool state can be rebuilt from events alone
+fn add_reserve_attributes(storage: &dyn Storage, event: Event) -> StdResult<Event> {
+    let token1 = TOKEN1.load(storage)?;
+    let token2 = TOKEN2.load(storage)?;
+    Ok(event
+        .add_attribute("token1_reserve", token1.reserve)
+        .add_attribute("token2_reserve", token2.reserve))
+}
+
 /*fn fee_decimal_to_uint128(decimal: Decimal) -> StdResult<Uint128> {
     let result: Uint128 = decimal
         .atomics()
@@ -725,6 +2577,8 @@ pub fn execute_swap(
 ) -> Result<Response, ContractError> {
     let action = "swap".to_string();
     check_expiration(&expiration, &_env.block)?;
+    check_allowlist(deps.as_ref(), &_env, &info.sender)?;
+    check_batch_mode(deps.as_ref())?;
 
     let input_token_item = match input_token_enum {
         TokenSelect::Token1 => TOKEN1,
@@ -790,9 +2644,9 @@ pub fn execute_swap(
 
     let recipient = deps.api.addr_validate(&recipient)?;
     // Create transfer to message
-    msgs.push(match output_token.denom {
-        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&recipient, &addr, token_bought)?,
-        Denom::Native(denom) => get_bank_transfer_to_msg(&recipient, &denom, token_bought),
+    msgs.push(match &output_token.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&recipient, addr, token_bought)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&recipient, denom, token_bought),
     });
 
     input_token_item.update(
@@ -817,12 +2671,25 @@ pub fn execute_swap(
         },
     )?;
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", action),
-        attr("native_sold", input_amount),
-        attr("token_bought", token_bought),
-        attr("protocol_fee_amount", protocol_fee_amount),
-    ]))
+    let event = Event::new("hopers_swap")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("input_denom", denom_to_string(&input_token.denom))
+        .add_attribute("output_denom", denom_to_string(&output_token.denom))
+        .add_attribute("input_amount", input_amount)
+        .add_attribute("output_amount", token_bought)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("action", action),
+            attr("native_sold", input_amount),
+            attr("token_bought", token_bought),
+            attr("protocol_fee_amount", protocol_fee_amount),
+        ]))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -837,6 +2704,8 @@ pub fn execute_pass_through_swap(
     expiration: Option<Expiration>,
 ) -> Result<Response, ContractError> {
     check_expiration(&expiration, &_env.block)?;
+    check_allowlist(deps.as_ref(), &_env, &info.sender)?;
+    check_batch_mode(deps.as_ref())?;
 
     let input_token_state = match input_token_enum {
         TokenSelect::Token1 => TOKEN1,
@@ -927,12 +2796,12 @@ pub fn execute_pass_through_swap(
 
     msgs.push(
         WasmMsg::Execute {
-            contract_addr: output_amm_address.into(),
+            contract_addr: output_amm_address.to_string(),
             msg: to_binary(&swap_msg)?,
-            funds: match transfer_token.denom {
+            funds: match &transfer_token.denom {
                 Denom::Cw20(_) => vec![],
                 Denom::Native(denom) => vec![Coin {
-                    denom,
+                    denom: denom.clone(),
                     amount: amount_to_transfer,
                 }],
             },
@@ -957,14 +2826,27 @@ pub fn execute_pass_through_swap(
         Ok(token)
     })?;
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("input_token_amount", input_token_amount),
-        attr("native_transferred", amount_to_transfer),
-    ]))
+    let event = Event::new("hopers_swap")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", output_amm_address.as_str())
+        .add_attribute("input_denom", denom_to_string(&input_token.denom))
+        .add_attribute("output_denom", denom_to_string(&transfer_token.denom))
+        .add_attribute("input_amount", input_token_amount)
+        .add_attribute("output_amount", amount_to_transfer)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount);
+    let event = add_reserve_attributes(deps.storage, event)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attributes(vec![
+            attr("input_token_amount", input_token_amount),
+            attr("native_transferred", amount_to_transfer),
+        ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
         QueryMsg::Info {} => to_binary(&query_info(deps)?),
@@ -975,6 +2857,51 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_token2_for_token1_price(deps, token2_amount)?)
         }
         QueryMsg::Fee {} => to_binary(&query_fee(deps)?),
+        QueryMsg::BatchConfig {} => to_binary(&query_batch_config(deps, env)?),
+        QueryMsg::Commitments {
+            epoch,
+            start_after,
+            limit,
+        } => to_binary(&query_commitments(deps, epoch, start_after, limit)?),
+        QueryMsg::Settlement { epoch } => to_binary(&query_settlement(deps, epoch)?),
+        QueryMsg::CommitmentHash { intent } => to_binary(&query_commitment_hash(intent)?),
+        QueryMsg::Twap {} => to_binary(&query_twap(deps, env)?),
+        QueryMsg::ConditionalOrders {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_conditional_orders(deps, owner, start_after, limit)?),
+        QueryMsg::DcaPosition { position_id } => to_binary(&query_dca_position(deps, position_id)?),
+        QueryMsg::DcaPositions {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_dca_positions(deps, owner, start_after, limit)?),
+        QueryMsg::ExecutableScheduled { start_after, limit } => {
+            to_binary(&query_executable_scheduled(deps, env, start_after, limit)?)
+        }
+        QueryMsg::ScheduledSwaps {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_scheduled_swaps(deps, owner, start_after, limit)?),
+        QueryMsg::SignedOrderHash { order } => to_binary(&SignedOrderHashResponse {
+            hash: get_signed_order_hash(&env.contract.address, &order)?,
+        }),
+        QueryMsg::SignedOrderStatus { maker, nonce } => {
+            to_binary(&query_signed_order_status(deps, maker, nonce)?)
+        }
+        QueryMsg::MakerInfo { maker } => to_binary(&query_maker_info(deps, maker)?),
+        QueryMsg::OtcOffer { offer_id } => to_binary(&query_otc_offer(deps, offer_id)?),
+        QueryMsg::OtcOffers {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_otc_offers(deps, address, start_after, limit)?),
+        QueryMsg::AllowlistConfig {} => to_binary(&query_allowlist_config(deps)?),
+        QueryMsg::IsAllowlisted { address } => {
+            to_binary(&query_is_allowlisted(deps, env, address)?)
+        }
     }
 }
 
@@ -1046,6 +2973,316 @@ pub fn query_fee(deps: Deps) -> StdResult<FeeResponse> {
     })
 }
 
+pub fn query_batch_config(deps: Deps, env: Env) -> StdResult<BatchConfigResponse> {
+    let config = match BATCH_CONFIG.may_load(deps.storage)? {
+        Some(config) => config,
+        None => {
+            return Ok(BatchConfigResponse {
+                enabled: false,
+                epoch_seconds: 0,
+                current_epoch: 0,
+                next_epoch_at: None,
+            })
+        }
+    };
+    let current_epoch = get_current_epoch(&config, &env.block);
+    let next_epoch_at = config
+        .epoch_start
+        .plus_seconds((current_epoch - config.epoch_id + 1) * config.epoch_seconds);
+    Ok(BatchConfigResponse {
+        enabled: config.enabled,
+        epoch_seconds: config.epoch_seconds,
+        current_epoch,
+        next_epoch_at: Some(next_epoch_at),
+    })
+}
+
+pub fn query_commitments(
+    deps: Deps,
+    epoch: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<CommitmentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let commitments = EPOCH_COMMITMENTS
+        .prefix_de(epoch)
+        .keys_de(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| {
+            let id = id?;
+            let commitment = COMMITMENTS.load(deps.storage, id)?;
+            Ok(CommitmentInfo {
+                id,
+                owner: commitment.owner.into_string(),
+                commitment: commitment.commitment,
+                input_token: commitment.input_token,
+                deposit: commitment.deposit,
+                input_amount: commitment
+                    .revealed
+                    .as_ref()
+                    .map(|revealed| revealed.input_amount),
+                min_output: commitment.revealed.map(|revealed| revealed.min_output),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(CommitmentsResponse { commitments })
+}
+
+pub fn query_settlement(deps: Deps, epoch: u64) -> StdResult<SettlementResponse> {
+    Ok(SettlementResponse {
+        settlement: SETTLEMENTS.may_load(deps.storage, epoch)?,
+    })
+}
+
+pub fn query_commitment_hash(intent: SwapIntent) -> StdResult<CommitmentHashResponse> {
+    Ok(CommitmentHashResponse {
+        commitment: get_commitment_hash(&intent)?,
+    })
+}
+
+pub fn query_twap(deps: Deps, env: Env) -> StdResult<TwapResponse> {
+    Ok(TwapResponse {
+        twap: get_twap(deps.storage, &env.block)?,
+        window_seconds: TWAP_WINDOW_SECONDS,
+    })
+}
+
+pub fn query_conditional_orders(
+    deps: Deps,
+    owner: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ConditionalOrdersResponse> {
+    let owner = owner
+        .map(|owner| deps.api.addr_validate(&owner))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let orders = match owner {
+        Some(owner) => conditional_orders()
+            .idx
+            .owner
+            .prefix(owner.as_bytes().to_vec())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (pk, order) = item?;
+                Ok((parse_index_pk(&pk)?, order))
+            })
+            .collect::<StdResult<Vec<_>>>()?,
+        None => conditional_orders()
+            .range_de(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+    let orders = orders
+        .into_iter()
+        .map(|(id, order)| ConditionalOrderInfo {
+            id,
+            owner: order.owner.into_string(),
+            input_token: order.input_token,
+            input_amount: order.input_amount,
+            trigger: order.trigger,
+            min_output: order.min_output,
+        })
+        .collect();
+    Ok(ConditionalOrdersResponse { orders })
+}
+
+// Primary keys of the owner indexes are the big endian ids
+fn parse_index_pk(pk: &[u8]) -> StdResult<u64> {
+    pk.try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| StdError::generic_err("Invalid index key"))
+}
+
+fn to_dca_position_info(id: u64, position: DcaPosition) -> DcaPositionInfo {
+    DcaPositionInfo {
+        id,
+        owner: position.owner.into_string(),
+        input_token: position.input_token,
+        balance: position.balance,
+        amount_per_swap: position.amount_per_swap,
+        interval_seconds: position.interval_seconds,
+        max_slippage: position.max_slippage,
+        next_swap_at: position.next_swap_at,
+        claimable: position.claimable,
+    }
+}
+
+pub fn query_dca_position(deps: Deps, position_id: u64) -> StdResult<DcaPositionInfo> {
+    let position = dca_positions().load(deps.storage, position_id)?;
+    Ok(to_dca_position_info(position_id, position))
+}
+
+pub fn query_dca_positions(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DcaPositionsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let positions = dca_positions()
+        .idx
+        .owner
+        .prefix(owner.as_bytes().to_vec())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (pk, position) = item?;
+            Ok(to_dca_position_info(parse_index_pk(&pk)?, position))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(DcaPositionsResponse { positions })
+}
+
+fn to_scheduled_swap_info(id: u64, swap: ScheduledSwap) -> ScheduledSwapInfo {
+    ScheduledSwapInfo {
+        id,
+        owner: swap.owner.into_string(),
+        recipient: swap.recipient.into_string(),
+        input_token: swap.input_token,
+        input_amount: swap.input_amount,
+        bounty: swap.bounty,
+        min_output: swap.min_output,
+        execute_at: swap.execute_at,
+    }
+}
+
+pub fn query_executable_scheduled(
+    deps: Deps,
+    env: Env,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> StdResult<ScheduledSwapsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|key| Bound::Exclusive(key.joined_key()));
+
+    // Keys are ordered by execution time, so everything before the first
+    // entry of the next second is due.
+    let end = Bound::Exclusive((env.block.time.seconds() + 1, 0u64).joined_key());
+
+    let swaps = SCHEDULED_SWAP_TIMES
+        .range_de(deps.storage, start, Some(end), Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let ((_, id), _) = item?;
+            let swap = scheduled_swaps().load(deps.storage, id)?;
+            Ok(to_scheduled_swap_info(id, swap))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ScheduledSwapsResponse { swaps })
+}
+
+pub fn query_scheduled_swaps(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ScheduledSwapsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let swaps = scheduled_swaps()
+        .idx
+        .owner
+        .prefix(owner.as_bytes().to_vec())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (pk, swap) = item?;
+            Ok(to_scheduled_swap_info(parse_index_pk(&pk)?, swap))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ScheduledSwapsResponse { swaps })
+}
+
+pub fn query_signed_order_status(
+    deps: Deps,
+    maker: String,
+    nonce: u64,
+) -> StdResult<SignedOrderStatusResponse> {
+    let maker = deps.api.addr_validate(&maker)?;
+    let status = SIGNED_ORDERS
+        .may_load(deps.storage, (&maker, nonce))?
+        .unwrap_or_default();
+    Ok(SignedOrderStatusResponse {
+        filled: status.filled,
+        cancelled: status.cancelled,
+    })
+}
+
+pub fn query_maker_info(deps: Deps, maker: String) -> StdResult<MakerInfoResponse> {
+    let maker = deps.api.addr_validate(&maker)?;
+    let balance = MAKER_BALANCES
+        .may_load(deps.storage, &maker)?
+        .unwrap_or_default();
+    Ok(MakerInfoResponse {
+        pubkey: MAKER_KEYS.may_load(deps.storage, &maker)?,
+        token1_balance: balance.token1,
+        token2_balance: balance.token2,
+    })
+}
+
+fn to_otc_offer_info(id: u64, offer: OtcOffer) -> OtcOfferInfo {
+    OtcOfferInfo {
+        id,
+        maker: offer.maker.into_string(),
+        counterparty: offer.counterparty.into_string(),
+        offer_denom: offer.offer_denom,
+        offer_amount: offer.offer_amount,
+        ask_denom: offer.ask_denom,
+        ask_amount: offer.ask_amount,
+        expires: offer.expires,
+    }
+}
+
+pub fn query_otc_offer(deps: Deps, offer_id: u64) -> StdResult<OtcOfferInfo> {
+    let offer = otc_offers().load(deps.storage, offer_id)?;
+    Ok(to_otc_offer_info(offer_id, offer))
+}
+
+pub fn query_otc_offers(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<OtcOffersResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let offers = otc_offers();
+
+    // A page of each index covers the page of both, merged by id
+    let mut page = vec![];
+    for index in [&offers.idx.maker, &offers.idx.counterparty] {
+        let start = start_after.map(Bound::exclusive_int);
+        for item in index
+            .prefix(address.as_bytes().to_vec())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+        {
+            let (pk, offer) = item?;
+            page.push((parse_index_pk(&pk)?, offer));
+        }
+    }
+    page.sort_by_key(|(id, _)| *id);
+    page.dedup_by_key(|(id, _)| *id);
+
+    let offers = page
+        .into_iter()
+        .take(limit)
+        .map(|(id, offer)| to_otc_offer_info(id, offer))
+        .collect();
+    Ok(OtcOffersResponse { offers })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     if msg.id != INSTANTIATE_LP_TOKEN_REPLY_ID {
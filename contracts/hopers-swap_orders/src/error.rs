@@ -76,6 +76,87 @@ pub enum ContractError {
     #[error("The output amm provided is invalid")]
     InvalidOutputPool {},
 
+    #[error("Address is not allowlisted")]
+    NotAllowlisted {},
+
+    #[error("Epoch duration must be positive")]
+    InvalidEpochDuration {},
+
+    #[error("Batch mode is disabled")]
+    BatchModeDisabled {},
+
+    #[error("Direct swaps are disabled while batch mode is enabled")]
+    BatchModeEnabled {},
+
+    #[error("Commitment {id} not found")]
+    CommitmentNotFound { id: u64 },
+
+    #[error("Commitment {id} can only be revealed in epoch {reveal_epoch}")]
+    OutsideRevealWindow { id: u64, reveal_epoch: u64 },
+
+    #[error("Commitment already revealed")]
+    AlreadyRevealed {},
+
+    #[error("Revealed intent does not match the commitment")]
+    InvalidReveal {},
+
+    #[error("Input amount exceeds the deposit: {deposit}")]
+    RevealExceedsDeposit { deposit: Uint128 },
+
+    #[error("Batch of epoch {epoch} cannot be settled before epoch {settle_epoch}")]
+    BatchNotSettleable { epoch: u64, settle_epoch: u64 },
+
+    #[error("Batch of epoch {epoch} is already settled")]
+    BatchAlreadySettled { epoch: u64 },
+
+    #[error("Epoch {epoch} already has the maximum of {max} commitments")]
+    TooManyCommitments { epoch: u64, max: u64 },
+
+    #[error("Commitment {id} can only be refunded from epoch {refund_epoch}")]
+    RefundNotAvailable { id: u64, refund_epoch: u64 },
+
+    #[error("Conditional order {id} not found")]
+    ConditionalOrderNotFound { id: u64 },
+
+    #[error("DCA amounts and interval must be positive and the slippage at most 1")]
+    InvalidDcaPosition {},
+
+    #[error("DCA position {id} not found")]
+    DcaPositionNotFound { id: u64 },
+
+    #[error("The TWAP window is not available yet")]
+    TwapUnavailable {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Scheduled swap {id} not found")]
+    ScheduledSwapNotFound { id: u64 },
+
+    #[error("Invalid order signature")]
+    InvalidSignature {},
+
+    #[error("Signed order expired")]
+    SignedOrderExpired {},
+
+    #[error("Signed order can only be filled by its taker")]
+    NotOrderTaker {},
+
+    #[error("Fill amount must be positive and at most the remaining {remaining}")]
+    InvalidFillAmount { remaining: Uint128 },
+
+    #[error("OTC offer {id} not found")]
+    OtcOfferNotFound { id: u64 },
+
+    #[error("OTC offer expired")]
+    OtcOfferExpired {},
+
+    #[error("OTC offer can only be reclaimed after it expired")]
+    OtcOfferNotExpired {},
+
+    #[error("OTC offers must expire")]
+    OtcOfferNeverExpires {},
+
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 }
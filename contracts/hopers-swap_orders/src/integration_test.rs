@@ -4,7 +4,8 @@ use std::borrow::BorrowMut;
 
 use cosmwasm_std::{
     coins,
-    to_binary, Addr, Coin, CosmosMsg, Decimal, Empty, Uint128, WasmMsg,
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Decimal256, Empty, Event, Uint128,
+    WasmMsg,
 };
 use cw0::Expiration;
 
@@ -14,10 +15,21 @@ use crate::{
     msg::{MigrateMsg, WalletInfo},
 };
 use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Denom};
-use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use k256::ecdsa::signature::Signer;
+use k256::ecdsa::{Signature, SigningKey};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use sha2::{Digest, Sha256};
 use std::str::FromStr;
 
-use crate::msg::{ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, QueryMsg, TokenSelect};
+use crate::msg::{
+    AllowlistConfigResponse, BatchConfigResponse, BatchSettlement, CommitmentHashResponse,
+    CommitmentsResponse, ConditionalOrdersResponse, DcaPositionInfo, DcaPositionsResponse,
+    ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, IsAllowlistedResponse,
+    MakerInfoResponse, OrderTrigger, OtcOffersResponse, QueryMsg, ScheduledSwapsResponse,
+    SettlementResponse, SignedOrder, SignedOrderHashResponse, SignedOrderStatusResponse,
+    SwapIntent, TokenSelect, TwapResponse,
+};
 
 fn mock_app() -> App {
     App::default()
@@ -1992,3 +2004,2135 @@ fn test_swap() {
         get_protocol_fee_amount(Uint128::new(1000), Uint128::new(5), Uint128::new(1000)).unwrap();
     println!("fee_amount {:?}", fee_amount)
 }
+
+fn get_event(res: &AppResponse, ty: &str) -> Event {
+    res.events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{}", ty))
+        .cloned()
+        .unwrap()
+}
+
+fn get_event_attribute(event: &Event, key: &str) -> String {
+    event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+        .unwrap()
+}
+
+#[test]
+fn swap_and_liquidity_events() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let funds = vec![
+        Coin {
+            denom: NATIVE_TOKEN_DENOM.into(),
+            amount: Uint128::new(2000),
+        },
+        Coin {
+            denom: IBC_TOKEN_DENOM.into(),
+            amount: Uint128::new(5000),
+        },
+    ];
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(100),
+        min_liquidity: Uint128::new(100),
+        max_token2: Uint128::new(100),
+        expiration: None,
+    };
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &[
+                Coin {
+                    denom: NATIVE_TOKEN_DENOM.into(),
+                    amount: Uint128::new(100),
+                },
+                Coin {
+                    denom: IBC_TOKEN_DENOM.into(),
+                    amount: Uint128::new(100),
+                },
+            ],
+        )
+        .unwrap();
+    let event = get_event(&res, "hopers_add_liquidity");
+    assert_eq!(get_event_attribute(&event, "sender"), owner.to_string());
+    assert_eq!(
+        get_event_attribute(&event, "token1_denom"),
+        NATIVE_TOKEN_DENOM
+    );
+    assert_eq!(get_event_attribute(&event, "token2_denom"), IBC_TOKEN_DENOM);
+    assert_eq!(get_event_attribute(&event, "token1_amount"), "100");
+    assert_eq!(get_event_attribute(&event, "token2_amount"), "100");
+    assert_eq!(get_event_attribute(&event, "liquidity_amount"), "100");
+    assert_eq!(get_event_attribute(&event, "token1_reserve"), "100");
+    assert_eq!(get_event_attribute(&event, "token2_reserve"), "100");
+
+    let swap_msg = ExecuteMsg::SwapAndSendTo {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(10),
+        recipient: "bob".to_string(),
+        min_token: Uint128::new(7),
+        expiration: None,
+    };
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let event = get_event(&res, "hopers_swap");
+    assert_eq!(get_event_attribute(&event, "sender"), owner.to_string());
+    assert_eq!(get_event_attribute(&event, "recipient"), "bob");
+    assert_eq!(
+        get_event_attribute(&event, "input_denom"),
+        NATIVE_TOKEN_DENOM
+    );
+    assert_eq!(get_event_attribute(&event, "output_denom"), IBC_TOKEN_DENOM);
+    assert_eq!(get_event_attribute(&event, "input_amount"), "10");
+    assert_eq!(get_event_attribute(&event, "output_amount"), "7");
+    assert_eq!(get_event_attribute(&event, "protocol_fee_amount"), "2");
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(
+        get_event_attribute(&event, "token1_reserve"),
+        info.token1_reserve.to_string()
+    );
+    assert_eq!(
+        get_event_attribute(&event, "token2_reserve"),
+        info.token2_reserve.to_string()
+    );
+
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(50),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), lp_token.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    let remove_liquidity_msg = ExecuteMsg::RemoveLiquidity {
+        amount: Uint128::new(50),
+        min_token1: Uint128::zero(),
+        min_token2: Uint128::zero(),
+        expiration: None,
+    };
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &remove_liquidity_msg, &[])
+        .unwrap();
+    let event = get_event(&res, "hopers_remove_liquidity");
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(get_event_attribute(&event, "recipient"), owner.to_string());
+    assert_eq!(get_event_attribute(&event, "liquidity_amount"), "50");
+    assert_eq!(
+        get_event_attribute(&event, "token1_reserve"),
+        info.token1_reserve.to_string()
+    );
+    assert_eq!(
+        get_event_attribute(&event, "token2_reserve"),
+        info.token2_reserve.to_string()
+    );
+}
+
+#[test]
+fn allowlisted_pool() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let trader = Addr::unchecked("trader");
+    let outsider = Addr::unchecked("outsider");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &trader, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &outsider, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let public_at = router.block_info().time.plus_seconds(1000);
+    let config_msg = ExecuteMsg::UpdateAllowlistConfig {
+        enabled: true,
+        public_at: Some(public_at),
+    };
+    let err = router
+        .execute_contract(trader.clone(), amm_addr.clone(), &config_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &config_msg, &[])
+        .unwrap();
+    let config: AllowlistConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::AllowlistConfig {})
+        .unwrap();
+    assert_eq!(
+        config,
+        AllowlistConfigResponse {
+            enabled: true,
+            public_at: Some(public_at),
+        }
+    );
+
+    // the owner has to be allowlisted to provide liquidity as well
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(1000),
+        min_liquidity: Uint128::new(1000),
+        max_token2: Uint128::new(1000),
+        expiration: None,
+    };
+    let liquidity_funds = [
+        Coin::new(1000, NATIVE_TOKEN_DENOM),
+        Coin::new(1000, IBC_TOKEN_DENOM),
+    ];
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &liquidity_funds,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::NotAllowlisted {}, err);
+
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::UpdateAllowlist {
+                add: vec![owner.to_string(), trader.to_string()],
+                remove: vec![],
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &liquidity_funds,
+        )
+        .unwrap();
+
+    let swap_msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token1,
+        input_amount: Uint128::new(10),
+        min_output: Uint128::zero(),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            trader.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let err = router
+        .execute_contract(
+            outsider.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::NotAllowlisted {}, err);
+
+    let is_allowlisted = |router: &App, address: &Addr| -> bool {
+        let res: IsAllowlistedResponse = router
+            .wrap()
+            .query_wasm_smart(
+                &amm_addr,
+                &QueryMsg::IsAllowlisted {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.allowed
+    };
+    assert!(is_allowlisted(&router, &trader));
+    assert!(!is_allowlisted(&router, &outsider));
+
+    // the pool is public after the launch window
+    router.update_block(|block| block.time = block.time.plus_seconds(1000));
+    assert!(is_allowlisted(&router, &outsider));
+    router
+        .execute_contract(
+            outsider,
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+}
+
+fn get_commitment(router: &App, amm_addr: &Addr, intent: SwapIntent) -> CommitmentHashResponse {
+    router
+        .wrap()
+        .query_wasm_smart(amm_addr, &QueryMsg::CommitmentHash { intent })
+        .unwrap()
+}
+
+#[test]
+fn batch_auction() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    let dev = Addr::unchecked("dev");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+        for (user, denom) in [
+            (&alice, NATIVE_TOKEN_DENOM),
+            (&bob, IBC_TOKEN_DENOM),
+            (&carol, NATIVE_TOKEN_DENOM),
+            (&dave, NATIVE_TOKEN_DENOM),
+        ] {
+            router
+                .bank
+                .init_balance(storage, user, coins(200, denom))
+                .unwrap();
+        }
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: dev.to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidity {
+                token1_amount: Uint128::new(1000),
+                min_liquidity: Uint128::new(1000),
+                max_token2: Uint128::new(1000),
+                expiration: None,
+            },
+            &[
+                Coin::new(1000, NATIVE_TOKEN_DENOM),
+                Coin::new(1000, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap();
+
+    // batches need batch mode
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CommitSwap {
+                commitment: Default::default(),
+                input_token: TokenSelect::Token1,
+                deposit: Uint128::new(150),
+            },
+            &coins(150, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::BatchModeDisabled {}, err);
+
+    let batch_config_msg = ExecuteMsg::UpdateBatchConfig {
+        enabled: true,
+        epoch_seconds: 100,
+    };
+    let err = router
+        .execute_contract(alice.clone(), amm_addr.clone(), &batch_config_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &batch_config_msg, &[])
+        .unwrap();
+    let batch_config: BatchConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::BatchConfig {})
+        .unwrap();
+    assert_eq!(
+        batch_config,
+        BatchConfigResponse {
+            enabled: true,
+            epoch_seconds: 100,
+            current_epoch: 0,
+            next_epoch_at: Some(router.block_info().time.plus_seconds(100)),
+        }
+    );
+
+    // direct swaps are disabled in batch mode
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Swap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::new(10),
+                min_output: Uint128::zero(),
+                expiration: None,
+            },
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::BatchModeEnabled {}, err);
+
+    // alice and bob trade in opposite directions, carol never reveals and dave's
+    // minimum output can't be met
+    let intents = [
+        (&alice, TokenSelect::Token1, 150, 100, 0),
+        (&bob, TokenSelect::Token2, 50, 50, 0),
+        (&carol, TokenSelect::Token1, 30, 30, 0),
+        (&dave, TokenSelect::Token1, 100, 100, 1000),
+    ];
+    for (user, input_token, deposit, input_amount, min_output) in intents.iter() {
+        let commitment = get_commitment(
+            &router,
+            &amm_addr,
+            SwapIntent {
+                sender: user.to_string(),
+                input_amount: Uint128::new(*input_amount),
+                min_output: Uint128::new(*min_output),
+                salt: "salt".to_string(),
+            },
+        );
+        let denom = match input_token {
+            TokenSelect::Token1 => NATIVE_TOKEN_DENOM,
+            TokenSelect::Token2 => IBC_TOKEN_DENOM,
+        };
+        router
+            .execute_contract(
+                (*user).clone(),
+                amm_addr.clone(),
+                &ExecuteMsg::CommitSwap {
+                    commitment: commitment.commitment,
+                    input_token: input_token.clone(),
+                    deposit: Uint128::new(*deposit),
+                },
+                &coins(*deposit, denom),
+            )
+            .unwrap();
+    }
+    let commitments: CommitmentsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Commitments {
+                epoch: 0,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(commitments.commitments.len(), 4);
+    assert_eq!(commitments.commitments[0].owner, alice.to_string());
+    assert_eq!(commitments.commitments[0].input_amount, None);
+
+    let reveal_msg =
+        |input_amount: u128, min_output: u128, id: u64, salt: &str| ExecuteMsg::RevealSwap {
+            commitment_id: id,
+            input_amount: Uint128::new(input_amount),
+            min_output: Uint128::new(min_output),
+            salt: salt.to_string(),
+        };
+    // intents are revealed in the next epoch
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &reveal_msg(100, 0, 1, "salt"),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::OutsideRevealWindow {
+            id: 1,
+            reveal_epoch: 1
+        },
+        err
+    );
+
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &reveal_msg(100, 0, 1, "other salt"),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidReveal {}, err);
+    let err = router
+        .execute_contract(
+            bob.clone(),
+            amm_addr.clone(),
+            &reveal_msg(100, 0, 1, "salt"),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+
+    for (id, user) in [(1, &alice), (2, &bob), (4, &dave)] {
+        let (_, _, _, input_amount, min_output) = intents[id as usize - 1];
+        router
+            .execute_contract(
+                user.clone(),
+                amm_addr.clone(),
+                &reveal_msg(input_amount, min_output, id, "salt"),
+                &[],
+            )
+            .unwrap();
+    }
+
+    let err = router
+        .execute_contract(
+            carol.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::SettleBatch { epoch: 0 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::BatchNotSettleable {
+            epoch: 0,
+            settle_epoch: 2
+        },
+        err
+    );
+
+    // unrevealed intents are refundable after the reveal epoch, revealed ones an epoch
+    // after the batch became settleable
+    let claim_refund = |router: &mut App, user: &Addr, id: u64| {
+        router.execute_contract(
+            user.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ClaimRefund { commitment_id: id },
+            &[],
+        )
+    };
+    let err = claim_refund(&mut router, &carol, 3)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::RefundNotAvailable {
+            id: 3,
+            refund_epoch: 2
+        },
+        err
+    );
+
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    let err = claim_refund(&mut router, &dave, 4)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::RefundNotAvailable {
+            id: 4,
+            refund_epoch: 3
+        },
+        err
+    );
+    let err = claim_refund(&mut router, &alice, 3)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    claim_refund(&mut router, &carol, 3).unwrap();
+    router
+        .execute_contract(
+            carol.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::SettleBatch { epoch: 0 },
+            &[],
+        )
+        .unwrap();
+
+    // after fees alice sells 80 token1 and bob 40 token2, both at 1040 / 1080
+    let settlement: SettlementResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::Settlement { epoch: 0 })
+        .unwrap();
+    assert_eq!(
+        settlement.settlement,
+        Some(BatchSettlement {
+            clearing_price: Some(Decimal::from_ratio(1040u128, 1080u128)),
+            token1_in: Uint128::new(80),
+            token2_in: Uint128::new(40),
+            token1_out: Uint128::new(41),
+            token2_out: Uint128::new(77),
+            filled: 2,
+            refunded: 1,
+        })
+    );
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(1039));
+    assert_eq!(info.token2_reserve, Uint128::new(963));
+
+    let balance = |router: &mut App, user: &Addr, denom: &str| {
+        bank_balance(router, user, denom.to_string()).amount.u128()
+    };
+    assert_eq!(balance(&mut router, &alice, NATIVE_TOKEN_DENOM), 100);
+    assert_eq!(balance(&mut router, &alice, IBC_TOKEN_DENOM), 77);
+    assert_eq!(balance(&mut router, &bob, IBC_TOKEN_DENOM), 150);
+    assert_eq!(balance(&mut router, &bob, NATIVE_TOKEN_DENOM), 41);
+    assert_eq!(balance(&mut router, &carol, NATIVE_TOKEN_DENOM), 200);
+    assert_eq!(balance(&mut router, &dave, NATIVE_TOKEN_DENOM), 200);
+    assert_eq!(balance(&mut router, &dev, NATIVE_TOKEN_DENOM), 20);
+    assert_eq!(balance(&mut router, &dev, IBC_TOKEN_DENOM), 10);
+
+    let err = router
+        .execute_contract(
+            carol.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::SettleBatch { epoch: 0 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::BatchAlreadySettled { epoch: 0 }, err);
+    let err = claim_refund(&mut router, &dave, 4)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::CommitmentNotFound { id: 4 }, err);
+
+    // the commitments of an epoch are capped
+    let commit_msg = ExecuteMsg::CommitSwap {
+        commitment: Default::default(),
+        input_token: TokenSelect::Token1,
+        deposit: Uint128::new(1),
+    };
+    for _ in 0..50 {
+        router
+            .execute_contract(
+                alice.clone(),
+                amm_addr.clone(),
+                &commit_msg,
+                &coins(1, NATIVE_TOKEN_DENOM),
+            )
+            .unwrap();
+    }
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &commit_msg,
+            &coins(1, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::TooManyCommitments { epoch: 2, max: 50 }, err);
+}
+
+#[test]
+fn conditional_orders() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let whale = Addr::unchecked("whale");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &whale, coins(1000, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        for (user, denom) in [
+            (&alice, NATIVE_TOKEN_DENOM),
+            (&bob, NATIVE_TOKEN_DENOM),
+            (&carol, IBC_TOKEN_DENOM),
+            (&dave, NATIVE_TOKEN_DENOM),
+        ] {
+            router
+                .bank
+                .init_balance(storage, user, coins(200, denom))
+                .unwrap();
+        }
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidity {
+                token1_amount: Uint128::new(1000),
+                min_liquidity: Uint128::new(1000),
+                max_token2: Uint128::new(1000),
+                expiration: None,
+            },
+            &[
+                Coin::new(1000, NATIVE_TOKEN_DENOM),
+                Coin::new(1000, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap();
+
+    // alice and dave stop their losses below 0.8, dave with a minimum output the pool
+    // can't pay, bob takes profit above 1.2 and carol sells token2 below 0.9
+    let orders = [
+        (
+            &alice,
+            TokenSelect::Token1,
+            NATIVE_TOKEN_DENOM,
+            OrderTrigger::StopLoss {
+                price: Decimal::percent(80),
+            },
+            1,
+        ),
+        (
+            &bob,
+            TokenSelect::Token1,
+            NATIVE_TOKEN_DENOM,
+            OrderTrigger::TakeProfit {
+                price: Decimal::percent(120),
+            },
+            1,
+        ),
+        (
+            &carol,
+            TokenSelect::Token2,
+            IBC_TOKEN_DENOM,
+            OrderTrigger::StopLoss {
+                price: Decimal::percent(90),
+            },
+            1,
+        ),
+        (
+            &dave,
+            TokenSelect::Token1,
+            NATIVE_TOKEN_DENOM,
+            OrderTrigger::StopLoss {
+                price: Decimal::percent(80),
+            },
+            1000,
+        ),
+    ];
+    for (user, input_token, denom, trigger, min_output) in orders.iter() {
+        router
+            .execute_contract(
+                (*user).clone(),
+                amm_addr.clone(),
+                &ExecuteMsg::SubmitConditionalOrder {
+                    input_token: input_token.clone(),
+                    input_amount: Uint128::new(100),
+                    trigger: trigger.clone(),
+                    min_output: Uint128::new(*min_output),
+                },
+                &coins(100, *denom),
+            )
+            .unwrap();
+    }
+
+    let execute_msg = ExecuteMsg::ExecuteTriggeredOrders {
+        start_after: None,
+        limit: None,
+    };
+    let get_executed = |res: AppResponse| {
+        res.custom_attrs(1)
+            .iter()
+            .find(|attr| attr.key == "executed")
+            .unwrap()
+            .value
+            .clone()
+    };
+    let get_twap = |router: &App| -> TwapResponse {
+        router
+            .wrap()
+            .query_wasm_smart(&amm_addr, &QueryMsg::Twap {})
+            .unwrap()
+    };
+
+    // no trigger fires without a full window of price history
+    assert_eq!(get_twap(&router).twap, None);
+    let res = router
+        .execute_contract(carol.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_executed(res), "0");
+
+    router.update_block(|b| b.time = b.time.plus_seconds(600));
+    assert_eq!(get_twap(&router).twap, Some(Decimal256::one()));
+
+    // a large swap moves the spot price, but not the average
+    router
+        .execute_contract(
+            whale.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Swap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::new(1000),
+                min_output: Uint128::zero(),
+                expiration: None,
+            },
+            &coins(1000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let res = router
+        .execute_contract(carol.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_executed(res), "0");
+
+    // the lower price has to hold for a while before the stop loss fires
+    router.update_block(|b| b.time = b.time.plus_seconds(300));
+    assert_eq!(
+        get_twap(&router).twap,
+        Some(Decimal256::from_ratio(
+            600u128 * 1800 + 556 * 300,
+            1800u128 * 900
+        ))
+    );
+
+    // orders are checked in pages, dave's order can't be filled
+    let res = router
+        .execute_contract(
+            carol.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ExecuteTriggeredOrders {
+                start_after: Some(2),
+                limit: Some(2),
+            },
+            &[],
+        )
+        .unwrap();
+    let last_order_id = res
+        .custom_attrs(1)
+        .iter()
+        .find(|attr| attr.key == "last_order_id")
+        .unwrap()
+        .value
+        .clone();
+    assert_eq!(last_order_id, "4");
+    assert_eq!(get_executed(res), "0");
+
+    let res = router
+        .execute_contract(carol.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_executed(res), "1");
+
+    // alice swaps 100 token1 at the spot price
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(1880));
+    assert_eq!(info.token2_reserve, Uint128::new(533));
+    assert_eq!(
+        bank_balance(&mut router, &alice, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(23)
+    );
+
+    let open_orders: ConditionalOrdersResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ConditionalOrders {
+                owner: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        open_orders.orders.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![2, 3, 4]
+    );
+    let dave_orders: ConditionalOrdersResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ConditionalOrders {
+                owner: Some(dave.to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(dave_orders.orders.len(), 1);
+    assert_eq!(dave_orders.orders[0].id, 4);
+    let dave_orders: ConditionalOrdersResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ConditionalOrders {
+                owner: Some(dave.to_string()),
+                start_after: Some(4),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(dave_orders.orders.is_empty());
+
+    let cancel_msg = ExecuteMsg::CancelConditionalOrder { order_id: 4 };
+    let err = router
+        .execute_contract(alice.clone(), amm_addr.clone(), &cancel_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(dave.clone(), amm_addr.clone(), &cancel_msg, &[])
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &dave, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(200)
+    );
+    let err = router
+        .execute_contract(dave.clone(), amm_addr.clone(), &cancel_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::ConditionalOrderNotFound { id: 4 }, err);
+}
+
+#[test]
+fn dca_positions() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &alice, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(100, IBC_TOKEN_DENOM))
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidity {
+                token1_amount: Uint128::new(1000),
+                min_liquidity: Uint128::new(1000),
+                max_token2: Uint128::new(1000),
+                expiration: None,
+            },
+            &[
+                Coin::new(1000, NATIVE_TOKEN_DENOM),
+                Coin::new(1000, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap();
+
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateDcaPosition {
+                input_token: TokenSelect::Token1,
+                deposit: Uint128::new(30),
+                amount_per_swap: Uint128::new(10),
+                interval_seconds: 0,
+                max_slippage: None,
+            },
+            &coins(30, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidDcaPosition {}, err);
+
+    // alice swaps 10 token1 every 100 seconds, bob all of his token2 at once with a
+    // slippage cap the swap exceeds
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateDcaPosition {
+                input_token: TokenSelect::Token1,
+                deposit: Uint128::new(30),
+                amount_per_swap: Uint128::new(10),
+                interval_seconds: 100,
+                max_slippage: Some(Decimal::percent(5)),
+            },
+            &coins(30, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            bob.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateDcaPosition {
+                input_token: TokenSelect::Token2,
+                deposit: Uint128::new(100),
+                amount_per_swap: Uint128::new(100),
+                interval_seconds: 50,
+                max_slippage: Some(Decimal::percent(1)),
+            },
+            &coins(100, IBC_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    let execute_msg = ExecuteMsg::ExecuteDca { limit: None };
+    let get_attr = |res: &AppResponse, key: &str| {
+        res.custom_attrs(1)
+            .iter()
+            .find(|attr| attr.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    let get_position = |router: &App, position_id: u64| -> DcaPositionInfo {
+        router
+            .wrap()
+            .query_wasm_smart(&amm_addr, &QueryMsg::DcaPosition { position_id })
+            .unwrap()
+    };
+
+    // the slippage cap needs the average price over the whole TWAP window
+    let err = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::TwapUnavailable {}, err);
+    router.update_block(|b| b.time = b.time.plus_seconds(600));
+
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "swapped"), "1");
+    assert_eq!(get_attr(&res, "skipped"), "1");
+    let position = get_position(&router, 1);
+    assert_eq!(position.balance, Uint128::new(20));
+    assert_eq!(position.claimable, Uint128::new(7));
+    assert_eq!(
+        position.next_swap_at,
+        router.block_info().time.plus_seconds(100)
+    );
+
+    // nothing is due before the next interval
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "swapped"), "0");
+
+    // bob's position is due first, after it is skipped it waits behind alice's
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    let execute_one_msg = ExecuteMsg::ExecuteDca { limit: Some(1) };
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &execute_one_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "swapped"), "0");
+    assert_eq!(get_attr(&res, "skipped"), "1");
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &execute_one_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "swapped"), "1");
+    assert_eq!(get_attr(&res, "skipped"), "0");
+    let position = get_position(&router, 1);
+    assert_eq!(position.balance, Uint128::new(10));
+    assert_eq!(position.claimable, Uint128::new(14));
+
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ClaimDcaOutput { position_id: 1 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &alice, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(14)
+    );
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ClaimDcaOutput { position_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::NothingToClaim {}, err);
+
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelDcaPosition { position_id: 2 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(
+            bob.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelDcaPosition { position_id: 2 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &bob, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(100)
+    );
+
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::TopUpDcaPosition {
+                position_id: 1,
+                amount: Uint128::new(5),
+            },
+            &coins(5, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let positions: DcaPositionsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::DcaPositions {
+                owner: alice.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(positions.positions.len(), 1);
+    assert_eq!(positions.positions[0].balance, Uint128::new(15));
+
+    // the last swap only uses what is left of the deposit
+    for _ in 0..2 {
+        router.update_block(|b| b.time = b.time.plus_seconds(100));
+        router
+            .execute_contract(owner.clone(), amm_addr.clone(), &execute_msg, &[])
+            .unwrap();
+    }
+    assert_eq!(get_position(&router, 1).balance, Uint128::zero());
+
+    // the position is closed with the last claim
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ClaimDcaOutput { position_id: 1 },
+            &[],
+        )
+        .unwrap();
+    router
+        .wrap()
+        .query_wasm_smart::<DcaPositionInfo>(&amm_addr, &QueryMsg::DcaPosition { position_id: 1 })
+        .unwrap_err();
+
+    // moving the spot price right before the swap doesn't move the slippage cap
+    router.update_block(|b| b.time = b.time.plus_seconds(600));
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateDcaPosition {
+                input_token: TokenSelect::Token1,
+                deposit: Uint128::new(10),
+                amount_per_swap: Uint128::new(10),
+                interval_seconds: 100,
+                max_slippage: Some(Decimal::percent(5)),
+            },
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::Swap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::new(500),
+                min_output: Uint128::zero(),
+                expiration: None,
+            },
+            &coins(500, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let res = router
+        .execute_contract(owner.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "swapped"), "0");
+    assert_eq!(get_attr(&res, "skipped"), "1");
+}
+
+#[test]
+fn scheduled_swaps() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let keeper = Addr::unchecked("keeper");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![
+                    Coin::new(2000, NATIVE_TOKEN_DENOM),
+                    Coin::new(2000, IBC_TOKEN_DENOM),
+                ],
+            )
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &alice, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(105, IBC_TOKEN_DENOM))
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AddLiquidity {
+                token1_amount: Uint128::new(1000),
+                min_liquidity: Uint128::new(1000),
+                max_token2: Uint128::new(1000),
+                expiration: None,
+            },
+            &[
+                Coin::new(1000, NATIVE_TOKEN_DENOM),
+                Coin::new(1000, IBC_TOKEN_DENOM),
+            ],
+        )
+        .unwrap();
+
+    let now = router.block_info().time;
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ScheduleSwap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::zero(),
+                bounty: Uint128::new(2),
+                min_output: Uint128::zero(),
+                execute_at: now,
+                recipient: None,
+            },
+            &coins(2, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InsufficientFunds {}, err);
+
+    // alice pays carol, bob asks for more than the pool can give
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ScheduleSwap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::new(10),
+                bounty: Uint128::new(2),
+                min_output: Uint128::new(7),
+                execute_at: now.plus_seconds(100),
+                recipient: Some(carol.to_string()),
+            },
+            &coins(12, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            bob.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ScheduleSwap {
+                input_token: TokenSelect::Token2,
+                input_amount: Uint128::new(100),
+                bounty: Uint128::new(5),
+                min_output: Uint128::new(200),
+                execute_at: now.plus_seconds(50),
+                recipient: None,
+            },
+            &coins(105, IBC_TOKEN_DENOM),
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ScheduleSwap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::new(10),
+                bounty: Uint128::zero(),
+                min_output: Uint128::zero(),
+                execute_at: now.plus_seconds(10),
+                recipient: None,
+            },
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    let err = router
+        .execute_contract(
+            bob.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelScheduledSwap { swap_id: 3 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelScheduledSwap { swap_id: 3 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &alice, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(88)
+    );
+
+    let executable = |router: &App| -> Vec<u64> {
+        let res: ScheduledSwapsResponse = router
+            .wrap()
+            .query_wasm_smart(
+                &amm_addr,
+                &QueryMsg::ExecutableScheduled {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        res.swaps.iter().map(|swap| swap.id).collect()
+    };
+    assert!(executable(&router).is_empty());
+
+    // nothing is executed before it is due
+    let execute_msg = ExecuteMsg::ExecuteScheduled { ids: vec![1, 2, 9] };
+    let get_attr = |res: &AppResponse, key: &str| {
+        res.custom_attrs(1)
+            .iter()
+            .find(|attr| attr.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    let res = router
+        .execute_contract(keeper.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "executed"), "0");
+    assert_eq!(get_attr(&res, "skipped"), "3");
+
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    assert_eq!(executable(&router), vec![2, 1]);
+
+    // keepers page past swaps they can't execute
+    let first_page: ScheduledSwapsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ExecutableScheduled {
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    let last = &first_page.swaps[0];
+    let next_page: ScheduledSwapsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ExecutableScheduled {
+                start_after: Some((last.execute_at.seconds(), last.id)),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(next_page.swaps.len(), 1);
+    assert_eq!(next_page.swaps[0].id, 1);
+
+    let res = router
+        .execute_contract(keeper.clone(), amm_addr.clone(), &execute_msg, &[])
+        .unwrap();
+    assert_eq!(get_attr(&res, "executed"), "1");
+    assert_eq!(get_attr(&res, "skipped"), "2");
+    assert_eq!(
+        bank_balance(&mut router, &carol, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(7)
+    );
+    assert_eq!(
+        bank_balance(&mut router, &keeper, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(2)
+    );
+    assert_eq!(executable(&router), vec![2]);
+
+    let swaps: ScheduledSwapsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::ScheduledSwaps {
+                owner: bob.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(swaps.swaps.len(), 1);
+    assert_eq!(swaps.swaps[0].bounty, Uint128::new(5));
+
+    // the bounty is refunded together with the input on cancel
+    router
+        .execute_contract(
+            bob.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelScheduledSwap { swap_id: 2 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &bob, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(105)
+    );
+
+    let err = router
+        .execute_contract(
+            alice.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelScheduledSwap { swap_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::ScheduledSwapNotFound { id: 1 }, err);
+
+    // a swap against an empty pool fails without reverting the batch
+    let empty_amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+    router
+        .execute_contract(
+            alice.clone(),
+            empty_amm_addr.clone(),
+            &ExecuteMsg::ScheduleSwap {
+                input_token: TokenSelect::Token1,
+                input_amount: Uint128::new(10),
+                bounty: Uint128::zero(),
+                min_output: Uint128::zero(),
+                execute_at: router.block_info().time,
+                recipient: None,
+            },
+            &coins(10, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let res = router
+        .execute_contract(
+            keeper.clone(),
+            empty_amm_addr.clone(),
+            &ExecuteMsg::ExecuteScheduled { ids: vec![1, 2] },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(get_attr(&res, "executed"), "0");
+    assert_eq!(get_attr(&res, "skipped"), "1");
+    assert_eq!(get_attr(&res, "failed"), "1");
+    let swaps: ScheduledSwapsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &empty_amm_addr,
+            &QueryMsg::ScheduledSwaps {
+                owner: alice.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(swaps.swaps.len(), 1);
+}
+
+#[test]
+fn signed_orders() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let maker = Addr::unchecked("maker");
+    let taker = Addr::unchecked("taker");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &maker, coins(100, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &taker, coins(50, IBC_TOKEN_DENOM))
+            .unwrap();
+    });
+
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let pubkey = signing_key.verifying_key().to_encoded_point(true);
+    router
+        .execute_contract(
+            maker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::SetMakerKey {
+                pubkey: Binary::from(pubkey.as_bytes()),
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            maker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::DepositMakerFunds {
+                token: TokenSelect::Token1,
+                amount: Uint128::new(100),
+            },
+            &coins(100, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    // the maker quotes the taker 100 token1 for 50 token2
+    let order = SignedOrder {
+        maker: maker.to_string(),
+        sell_token: TokenSelect::Token1,
+        sell_amount: Uint128::new(100),
+        buy_amount: Uint128::new(50),
+        nonce: 1,
+        expires_at: router.block_info().time.plus_seconds(100),
+        taker: Some(taker.to_string()),
+    };
+    let sign = |order: &SignedOrder| -> Binary {
+        let mut preimage = amm_addr.as_bytes().to_vec();
+        preimage.extend(to_binary(order).unwrap().as_slice());
+        let signature: Signature = signing_key.sign(&preimage);
+        Binary::from(signature.as_ref())
+    };
+    let signature = sign(&order);
+
+    let hash: SignedOrderHashResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::SignedOrderHash {
+                order: order.clone(),
+            },
+        )
+        .unwrap();
+    let mut preimage = amm_addr.as_bytes().to_vec();
+    preimage.extend(to_binary(&order).unwrap().as_slice());
+    assert_eq!(hash.hash.as_slice(), Sha256::digest(&preimage).as_slice());
+
+    // the signature doesn't cover a better price
+    let err = router
+        .execute_contract(
+            taker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                order: SignedOrder {
+                    buy_amount: Uint128::new(25),
+                    ..order.clone()
+                },
+                signature: signature.clone(),
+                fill_amount: Uint128::new(40),
+            },
+            &coins(10, IBC_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidSignature {}, err);
+
+    // the signature doesn't cover another taker either
+    let err = router
+        .execute_contract(
+            taker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                order: SignedOrder {
+                    taker: None,
+                    ..order.clone()
+                },
+                signature: signature.clone(),
+                fill_amount: Uint128::new(40),
+            },
+            &coins(20, IBC_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::InvalidSignature {}, err);
+
+    // only the quoted taker can fill the order
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                order: order.clone(),
+                signature: signature.clone(),
+                fill_amount: Uint128::new(40),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::NotOrderTaker {}, err);
+
+    router
+        .execute_contract(
+            taker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                order: order.clone(),
+                signature: signature.clone(),
+                fill_amount: Uint128::new(40),
+            },
+            &coins(20, IBC_TOKEN_DENOM),
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &taker, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(40)
+    );
+    // 20% protocol fee goes to the dev wallet
+    assert_eq!(
+        bank_balance(&mut router, &maker, IBC_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(16)
+    );
+    assert_eq!(
+        bank_balance(
+            &mut router,
+            &Addr::unchecked("dev"),
+            IBC_TOKEN_DENOM.to_string()
+        )
+        .amount,
+        Uint128::new(4)
+    );
+
+    let err = router
+        .execute_contract(
+            taker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                order: order.clone(),
+                signature: signature.clone(),
+                fill_amount: Uint128::new(70),
+            },
+            &coins(30, IBC_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::InvalidFillAmount {
+            remaining: Uint128::new(60)
+        },
+        err
+    );
+
+    router
+        .execute_contract(
+            maker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CancelSignedOrder { nonce: 1 },
+            &[],
+        )
+        .unwrap();
+    let status: SignedOrderStatusResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::SignedOrderStatus {
+                maker: maker.to_string(),
+                nonce: 1,
+            },
+        )
+        .unwrap();
+    assert_eq!(status.filled, Uint128::new(40));
+    assert!(status.cancelled);
+    let err = router
+        .execute_contract(
+            taker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                order: order.clone(),
+                signature,
+                fill_amount: Uint128::new(10),
+            },
+            &coins(5, IBC_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::InvalidFillAmount {
+            remaining: Uint128::zero()
+        },
+        err
+    );
+
+    let order = SignedOrder { nonce: 2, ..order };
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    let err = router
+        .execute_contract(
+            taker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::FillSignedOrder {
+                signature: sign(&order),
+                order,
+                fill_amount: Uint128::new(10),
+            },
+            &coins(5, IBC_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::SignedOrderExpired {}, err);
+
+    router
+        .execute_contract(
+            maker.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::WithdrawMakerFunds {
+                token: TokenSelect::Token1,
+                amount: Uint128::new(60),
+            },
+            &[],
+        )
+        .unwrap();
+    let info: MakerInfoResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::MakerInfo {
+                maker: maker.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(info.token1_balance, Uint128::zero());
+    assert_eq!(
+        bank_balance(&mut router, &maker, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(60)
+    );
+}
+
+#[test]
+fn otc_offers() {
+    let mut router = mock_app();
+
+    const NATIVE_TOKEN_DENOM: &str = "juno";
+    const IBC_TOKEN_DENOM: &str = "atom";
+
+    let owner = Addr::unchecked("owner");
+    let whale = Addr::unchecked("whale");
+    let dao = Addr::unchecked("dao");
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &whale, coins(1000, NATIVE_TOKEN_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &dao, coins(100, IBC_TOKEN_DENOM))
+            .unwrap();
+    });
+
+    let dao_token = create_cw20(
+        &mut router,
+        &dao,
+        "dao".to_string(),
+        "DAO".to_string(),
+        Uint128::new(1000),
+    );
+    let amm_addr = create_amm(
+        &mut router,
+        &owner,
+        Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        Denom::Native(IBC_TOKEN_DENOM.into()),
+        Decimal::zero(),
+        Decimal::zero(),
+        vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    );
+
+    // the whale sells 500 juno to the dao for 100 of its token
+    let expires = Expiration::AtTime(router.block_info().time.plus_seconds(100));
+    router
+        .execute_contract(
+            whale.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateOtcOffer {
+                offer_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+                offer_amount: Uint128::new(500),
+                counterparty: dao.to_string(),
+                ask_denom: Denom::Cw20(dao_token.addr()),
+                ask_amount: Uint128::new(100),
+                expires,
+            },
+            &coins(500, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    let offers: OtcOffersResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::OtcOffers {
+                address: dao.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(offers.offers.len(), 1);
+    assert_eq!(offers.offers[0].maker, whale.to_string());
+    // the maker is indexed too, pages continue after the last id
+    let query_offers = |router: &App, start_after: Option<u64>| -> Vec<u64> {
+        let offers: OtcOffersResponse = router
+            .wrap()
+            .query_wasm_smart(
+                &amm_addr,
+                &QueryMsg::OtcOffers {
+                    address: whale.to_string(),
+                    start_after,
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        offers.offers.iter().map(|offer| offer.id).collect()
+    };
+    assert_eq!(query_offers(&router, None), vec![1]);
+    assert!(query_offers(&router, Some(1)).is_empty());
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AcceptOtcOffer { offer_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::Unauthorized {}, err);
+    let err = router
+        .execute_contract(
+            whale.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ReclaimOtcOffer { offer_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::OtcOfferNotExpired {}, err);
+
+    router
+        .execute_contract(
+            dao.clone(),
+            dao_token.addr(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: amm_addr.to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            dao.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AcceptOtcOffer { offer_id: 1 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &dao, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(500)
+    );
+    // 20% protocol fee goes to the dev wallet
+    assert_eq!(
+        dao_token.balance(&router, whale.clone()).unwrap(),
+        Uint128::new(80)
+    );
+    assert_eq!(
+        dao_token.balance(&router, Addr::unchecked("dev")).unwrap(),
+        Uint128::new(20)
+    );
+    let err = router
+        .execute_contract(
+            dao.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AcceptOtcOffer { offer_id: 1 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::OtcOfferNotFound { id: 1 }, err);
+
+    // offers that never expire could lock the escrow for good
+    let err = router
+        .execute_contract(
+            whale.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateOtcOffer {
+                offer_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+                offer_amount: Uint128::new(500),
+                counterparty: dao.to_string(),
+                ask_denom: Denom::Native(IBC_TOKEN_DENOM.into()),
+                ask_amount: Uint128::new(100),
+                expires: Expiration::Never {},
+            },
+            &coins(500, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::OtcOfferNeverExpires {}, err);
+
+    // an expired offer can't be accepted and goes back to the maker
+    router
+        .execute_contract(
+            whale.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::CreateOtcOffer {
+                offer_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+                offer_amount: Uint128::new(500),
+                counterparty: dao.to_string(),
+                ask_denom: Denom::Native(IBC_TOKEN_DENOM.into()),
+                ask_amount: Uint128::new(100),
+                expires,
+            },
+            &coins(500, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    router.update_block(|b| b.time = b.time.plus_seconds(100));
+    let err = router
+        .execute_contract(
+            dao.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::AcceptOtcOffer { offer_id: 2 },
+            &coins(100, IBC_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(ContractError::OtcOfferExpired {}, err);
+    router
+        .execute_contract(
+            whale.clone(),
+            amm_addr.clone(),
+            &ExecuteMsg::ReclaimOtcOffer { offer_id: 2 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        bank_balance(&mut router, &whale, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(500)
+    );
+}
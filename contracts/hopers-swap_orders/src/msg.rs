@@ -2,10 +2,12 @@ use cw20::BalanceResponse;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_schema::QueryResponses;
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Binary, Decimal, Decimal256, HexBinary, Timestamp, Uint128};
 
 use cw20::{Denom, Expiration};
 
+pub use hopers_allowlist::{AllowlistConfigResponse, IsAllowlistedResponse};
+
 #[cw_serde]
 pub struct InstantiateMsg {
     pub token1_denom: Denom,
@@ -61,6 +63,149 @@ pub enum ExecuteMsg {
         min_token: Uint128,
         expiration: Option<Expiration>,
     },
+    /// Restricts trading and providing liquidity to allowlisted addresses until
+    /// `public_at`. Owner only
+    UpdateAllowlistConfig {
+        enabled: bool,
+        public_at: Option<Timestamp>,
+    },
+    /// Adds and removes addresses of the allowlist in bulk. Owner only
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Enables batch auctions, which replace direct swaps while enabled. Owner only
+    UpdateBatchConfig {
+        enabled: bool,
+        epoch_seconds: u64,
+    },
+    /// Commits the sha256 hash of a `SwapIntent` for the current epoch, escrowing
+    /// `deposit` of the input token
+    CommitSwap {
+        commitment: HexBinary,
+        input_token: TokenSelect,
+        deposit: Uint128,
+    },
+    /// Reveals a committed intent during the epoch following the commit
+    RevealSwap {
+        commitment_id: u64,
+        input_amount: Uint128,
+        min_output: Uint128,
+        salt: String,
+    },
+    /// Settles all revealed swaps of an epoch at a single clearing price and refunds
+    /// the rest, once its reveal epoch is over
+    SettleBatch {
+        epoch: u64,
+    },
+    /// Refunds the deposit of an unsettled commitment, from the epoch after its reveal
+    /// epoch if it was not revealed and one epoch later if it was
+    ClaimRefund {
+        commitment_id: u64,
+    },
+    /// Escrows `input_amount` of the input token until the time-averaged price
+    /// crosses the trigger
+    SubmitConditionalOrder {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        trigger: OrderTrigger,
+        min_output: Uint128,
+    },
+    CancelConditionalOrder {
+        order_id: u64,
+    },
+    /// Checks up to `limit` orders after `start_after` and swaps the ones whose trigger
+    /// has fired, callable by anyone
+    ExecuteTriggeredOrders {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Deposits the input token to swap `amount_per_swap` of it every `interval_seconds`.
+    /// `max_slippage` caps the output below the TWAP of every swap, swaps above it are
+    /// skipped
+    CreateDcaPosition {
+        input_token: TokenSelect,
+        deposit: Uint128,
+        amount_per_swap: Uint128,
+        interval_seconds: u64,
+        max_slippage: Option<Decimal>,
+    },
+    TopUpDcaPosition {
+        position_id: u64,
+        amount: Uint128,
+    },
+    /// Refunds the remaining deposit together with the claimable output
+    CancelDcaPosition {
+        position_id: u64,
+    },
+    ClaimDcaOutput {
+        position_id: u64,
+    },
+    /// Performs up to `limit` due DCA swaps, callable by anyone
+    ExecuteDca {
+        limit: Option<u32>,
+    },
+    /// Escrows `input_amount` plus the keeper `bounty` of the input token for a swap
+    /// executable from `execute_at` on. The output goes to `recipient`, the sender by default
+    ScheduleSwap {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        bounty: Uint128,
+        min_output: Uint128,
+        execute_at: Timestamp,
+        recipient: Option<String>,
+    },
+    CancelScheduledSwap {
+        swap_id: u64,
+    },
+    /// Executes the given scheduled swaps and pays their bounties to the sender. Ids that
+    /// are unknown, not yet due or below their minimum output are skipped, swaps the pool
+    /// can't execute are reported as failed, neither reverts the batch
+    ExecuteScheduled {
+        ids: Vec<u64>,
+    },
+    /// Registers the compressed secp256k1 public key the sender signs RFQ orders with
+    SetMakerKey {
+        pubkey: Binary,
+    },
+    /// Deposits funds RFQ orders of the sender are filled from
+    DepositMakerFunds {
+        token: TokenSelect,
+        amount: Uint128,
+    },
+    WithdrawMakerFunds {
+        token: TokenSelect,
+        amount: Uint128,
+    },
+    /// Buys `fill_amount` of the maker's sell token at the price of a signed order,
+    /// paying the proportional buy amount, rounded up, in the other token
+    FillSignedOrder {
+        order: SignedOrder,
+        signature: Binary,
+        fill_amount: Uint128,
+    },
+    /// Invalidates the order of the sender with the given nonce
+    CancelSignedOrder {
+        nonce: u64,
+    },
+    /// Escrows `offer_amount` of `offer_denom` for `counterparty` in exchange for
+    /// `ask_amount` of `ask_denom`, outside of the pool. `expires` can't be `Never`
+    CreateOtcOffer {
+        offer_denom: Denom,
+        offer_amount: Uint128,
+        counterparty: String,
+        ask_denom: Denom,
+        ask_amount: Uint128,
+        expires: Expiration,
+    },
+    /// Pays the ask of an offer and receives the escrow. Counterparty only
+    AcceptOtcOffer {
+        offer_id: u64,
+    },
+    /// Returns the escrow of an expired offer to its maker
+    ReclaimOtcOffer {
+        offer_id: u64,
+    },
     UpdateConfig {
         owner: Option<String>,
         fee_percent_numerator: Uint128,
@@ -89,10 +234,99 @@ pub enum QueryMsg {
     },
     #[returns(FeeResponse)]
     Fee {},
+    #[returns(BatchConfigResponse)]
+    BatchConfig {},
+    #[returns(CommitmentsResponse)]
+    Commitments {
+        epoch: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(SettlementResponse)]
+    Settlement {
+        epoch: u64,
+    },
+    /// Returns the commitment to submit for an intent
+    #[returns(CommitmentHashResponse)]
+    CommitmentHash {
+        intent: SwapIntent,
+    },
+    /// Returns the time-averaged price conditional orders are triggered by
+    #[returns(TwapResponse)]
+    Twap {},
+    #[returns(ConditionalOrdersResponse)]
+    ConditionalOrders {
+        owner: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(DcaPositionInfo)]
+    DcaPosition {
+        position_id: u64,
+    },
+    #[returns(DcaPositionsResponse)]
+    DcaPositions {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the scheduled swaps that are due, oldest first. `start_after` is the
+    /// execution time in seconds and the id of the last swap of the previous page
+    #[returns(ScheduledSwapsResponse)]
+    ExecutableScheduled {
+        start_after: Option<(u64, u64)>,
+        limit: Option<u32>,
+    },
+    #[returns(ScheduledSwapsResponse)]
+    ScheduledSwaps {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the hash a maker signs for an order
+    #[returns(SignedOrderHashResponse)]
+    SignedOrderHash {
+        order: SignedOrder,
+    },
+    #[returns(SignedOrderStatusResponse)]
+    SignedOrderStatus {
+        maker: String,
+        nonce: u64,
+    },
+    #[returns(MakerInfoResponse)]
+    MakerInfo {
+        maker: String,
+    },
+    #[returns(OtcOfferInfo)]
+    OtcOffer {
+        offer_id: u64,
+    },
+    /// Returns the offers the address is the maker or the counterparty of
+    #[returns(OtcOffersResponse)]
+    OtcOffers {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(AllowlistConfigResponse)]
+    AllowlistConfig {},
+    #[returns(IsAllowlistedResponse)]
+    IsAllowlisted {
+        address: String,
+    },
 }
 #[cw_serde]
 pub struct MigrateMsg {}
 
+/// Prices are given in output tokens per input token
+#[cw_serde]
+pub enum OrderTrigger {
+    /// Fires once the price drops to `price` or below
+    StopLoss { price: Decimal },
+    /// Fires once the price rises to `price` or above
+    TakeProfit { price: Decimal },
+}
+
 #[cw_serde]
 pub struct InfoResponse {
     pub token1_reserve: Uint128,
@@ -126,3 +360,169 @@ pub struct WalletInfo {
     pub address: String,
     pub ratio: Decimal,
 }
+
+/// Committed as the sha256 hash of its JSON encoding
+#[cw_serde]
+pub struct SwapIntent {
+    pub sender: String,
+    pub input_amount: Uint128,
+    pub min_output: Uint128,
+    pub salt: String,
+}
+
+#[cw_serde]
+pub struct BatchConfigResponse {
+    pub enabled: bool,
+    pub epoch_seconds: u64,
+    pub current_epoch: u64,
+    pub next_epoch_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct CommitmentInfo {
+    pub id: u64,
+    pub owner: String,
+    pub commitment: HexBinary,
+    pub input_token: TokenSelect,
+    pub deposit: Uint128,
+    /// Set once the intent is revealed
+    pub input_amount: Option<Uint128>,
+    pub min_output: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct CommitmentsResponse {
+    pub commitments: Vec<CommitmentInfo>,
+}
+
+#[cw_serde]
+pub struct BatchSettlement {
+    /// Token2 paid per token1, none if no swap was filled
+    pub clearing_price: Option<Decimal>,
+    /// Inputs of the filled swaps after protocol fees
+    pub token1_in: Uint128,
+    pub token2_in: Uint128,
+    pub token1_out: Uint128,
+    pub token2_out: Uint128,
+    pub filled: u64,
+    pub refunded: u64,
+}
+
+#[cw_serde]
+pub struct SettlementResponse {
+    pub settlement: Option<BatchSettlement>,
+}
+
+#[cw_serde]
+pub struct CommitmentHashResponse {
+    pub commitment: HexBinary,
+}
+
+#[cw_serde]
+pub struct TwapResponse {
+    /// Token2 per token1, none until the pool has a full window of price history
+    pub twap: Option<Decimal256>,
+    pub window_seconds: u64,
+}
+
+#[cw_serde]
+pub struct ConditionalOrderInfo {
+    pub id: u64,
+    pub owner: String,
+    pub input_token: TokenSelect,
+    pub input_amount: Uint128,
+    pub trigger: OrderTrigger,
+    pub min_output: Uint128,
+}
+
+#[cw_serde]
+pub struct ConditionalOrdersResponse {
+    pub orders: Vec<ConditionalOrderInfo>,
+}
+
+#[cw_serde]
+pub struct DcaPositionInfo {
+    pub id: u64,
+    pub owner: String,
+    pub input_token: TokenSelect,
+    /// Input left to swap
+    pub balance: Uint128,
+    pub amount_per_swap: Uint128,
+    pub interval_seconds: u64,
+    pub max_slippage: Option<Decimal>,
+    pub next_swap_at: Timestamp,
+    /// Output of the swaps so far, not yet claimed
+    pub claimable: Uint128,
+}
+
+#[cw_serde]
+pub struct DcaPositionsResponse {
+    pub positions: Vec<DcaPositionInfo>,
+}
+
+#[cw_serde]
+pub struct ScheduledSwapInfo {
+    pub id: u64,
+    pub owner: String,
+    pub recipient: String,
+    pub input_token: TokenSelect,
+    pub input_amount: Uint128,
+    pub bounty: Uint128,
+    pub min_output: Uint128,
+    pub execute_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct ScheduledSwapsResponse {
+    pub swaps: Vec<ScheduledSwapInfo>,
+}
+
+/// An RFQ order signed off-chain by its maker. The signature is over the sha256 hash of
+/// the contract address followed by the JSON encoding of the order
+#[cw_serde]
+pub struct SignedOrder {
+    pub maker: String,
+    pub sell_token: TokenSelect,
+    pub sell_amount: Uint128,
+    pub buy_amount: Uint128,
+    pub nonce: u64,
+    pub expires_at: Timestamp,
+    /// Only this address can fill the order, anyone when not set
+    pub taker: Option<String>,
+}
+
+#[cw_serde]
+pub struct SignedOrderHashResponse {
+    pub hash: HexBinary,
+}
+
+#[cw_serde]
+pub struct SignedOrderStatusResponse {
+    /// Amount of the sell token filled so far
+    pub filled: Uint128,
+    pub cancelled: bool,
+}
+
+#[cw_serde]
+pub struct MakerInfoResponse {
+    pub pubkey: Option<Binary>,
+    pub token1_balance: Uint128,
+    pub token2_balance: Uint128,
+}
+
+#[cw_serde]
+pub struct OtcOfferInfo {
+    pub id: u64,
+    pub maker: String,
+    pub counterparty: String,
+    pub offer_denom: Denom,
+    pub offer_amount: Uint128,
+    pub ask_denom: Denom,
+    pub ask_amount: Uint128,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct OtcOffersResponse {
+    pub offers: Vec<OtcOfferInfo>,
+}
@@ -1,11 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw20::Denom;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, HexBinary, Timestamp, Uint128};
+use cw20::{Denom, Expiration};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
-use crate::msg::WalletInfo;
+use crate::msg::{BatchSettlement, OrderTrigger, TokenSelect, WalletInfo};
 
 pub const LP_TOKEN: Item<Addr> = Item::new("lp_token");
 
@@ -28,3 +28,221 @@ pub struct Fees {
 }
 
 pub const FEES: Item<Fees> = Item::new("fees");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BatchConfig {
+    pub enabled: bool,
+    pub epoch_seconds: u64,
+    /// Epoch started at `epoch_start`, later epochs follow every `epoch_seconds`
+    pub epoch_id: u64,
+    pub epoch_start: Timestamp,
+}
+
+pub const BATCH_CONFIG: Item<BatchConfig> = Item::new("batch_config");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RevealedSwap {
+    pub input_amount: Uint128,
+    pub min_output: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Commitment {
+    pub owner: Addr,
+    pub epoch: u64,
+    pub commitment: HexBinary,
+    pub input_token: TokenSelect,
+    pub deposit: Uint128,
+    pub revealed: Option<RevealedSwap>,
+}
+
+pub const COMMITMENT_COUNT: Item<u64> = Item::new("commitment_count");
+pub const COMMITMENTS: Map<u64, Commitment> = Map::new("commitments");
+/// Ids of the commitments of every epoch, keyed by epoch and id
+pub const EPOCH_COMMITMENTS: Map<(u64, u64), bool> = Map::new("epoch_commitments");
+/// Number of commitments made in every epoch
+pub const EPOCH_COMMITMENT_COUNTS: Map<u64, u64> = Map::new("epoch_commitment_counts");
+pub const SETTLEMENTS: Map<u64, BatchSettlement> = Map::new("settlements");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceAccumulator {
+    /// Token2 per token1 price summed over every second of the pool's life
+    pub price_cumulative: Decimal256,
+    pub last_update: Timestamp,
+}
+
+pub const PRICE_ACCUMULATOR: Item<PriceAccumulator> = Item::new("price_accumulator");
+/// Cumulative price by time in seconds, kept for one TWAP window
+pub const PRICE_OBSERVATIONS: Map<u64, Decimal256> = Map::new("price_observations");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConditionalOrder {
+    pub owner: Addr,
+    pub input_token: TokenSelect,
+    pub input_amount: Uint128,
+    pub trigger: OrderTrigger,
+    pub min_output: Uint128,
+}
+
+pub const CONDITIONAL_ORDER_COUNT: Item<u64> = Item::new("conditional_order_count");
+
+// Owner indexes are keyed by the owner address and the primary key, so the entries
+// of one owner are paged by id without scanning the others
+
+pub struct ConditionalOrderIndexes<'a> {
+    pub owner: MultiIndex<'a, (Vec<u8>, Vec<u8>), ConditionalOrder>,
+}
+
+impl<'a> IndexList<ConditionalOrder> for ConditionalOrderIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ConditionalOrder>> + '_> {
+        let v: Vec<&dyn Index<ConditionalOrder>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn conditional_orders<'a>() -> IndexedMap<'a, u64, ConditionalOrder, ConditionalOrderIndexes<'a>>
+{
+    let indexes = ConditionalOrderIndexes {
+        owner: MultiIndex::new(
+            |d: &ConditionalOrder, k| (d.owner.as_bytes().to_vec(), k),
+            "conditional_orders",
+            "conditional_orders__owner",
+        ),
+    };
+    IndexedMap::new("conditional_orders", indexes)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DcaPosition {
+    pub owner: Addr,
+    pub input_token: TokenSelect,
+    pub balance: Uint128,
+    pub amount_per_swap: Uint128,
+    pub interval_seconds: u64,
+    pub max_slippage: Option<Decimal>,
+    pub next_swap_at: Timestamp,
+    pub claimable: Uint128,
+}
+
+pub const DCA_POSITION_COUNT: Item<u64> = Item::new("dca_position_count");
+
+pub struct DcaPositionIndexes<'a> {
+    pub owner: MultiIndex<'a, (Vec<u8>, Vec<u8>), DcaPosition>,
+}
+
+impl<'a> IndexList<DcaPosition> for DcaPositionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<DcaPosition>> + '_> {
+        let v: Vec<&dyn Index<DcaPosition>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn dca_positions<'a>() -> IndexedMap<'a, u64, DcaPosition, DcaPositionIndexes<'a>> {
+    let indexes = DcaPositionIndexes {
+        owner: MultiIndex::new(
+            |d: &DcaPosition, k| (d.owner.as_bytes().to_vec(), k),
+            "dca_positions",
+            "dca_positions__owner",
+        ),
+    };
+    IndexedMap::new("dca_positions", indexes)
+}
+/// Ids of the positions with a balance left, keyed by next swap time in seconds and id
+pub const DCA_SWAP_TIMES: Map<(u64, u64), bool> = Map::new("dca_swap_times");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledSwap {
+    pub owner: Addr,
+    pub recipient: Addr,
+    pub input_token: TokenSelect,
+    pub input_amount: Uint128,
+    pub bounty: Uint128,
+    pub min_output: Uint128,
+    pub execute_at: Timestamp,
+}
+
+pub const SCHEDULED_SWAP_COUNT: Item<u64> = Item::new("scheduled_swap_count");
+
+pub struct ScheduledSwapIndexes<'a> {
+    pub owner: MultiIndex<'a, (Vec<u8>, Vec<u8>), ScheduledSwap>,
+}
+
+impl<'a> IndexList<ScheduledSwap> for ScheduledSwapIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ScheduledSwap>> + '_> {
+        let v: Vec<&dyn Index<ScheduledSwap>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn scheduled_swaps<'a>() -> IndexedMap<'a, u64, ScheduledSwap, ScheduledSwapIndexes<'a>> {
+    let indexes = ScheduledSwapIndexes {
+        owner: MultiIndex::new(
+            |d: &ScheduledSwap, k| (d.owner.as_bytes().to_vec(), k),
+            "scheduled_swaps",
+            "scheduled_swaps__owner",
+        ),
+    };
+    IndexedMap::new("scheduled_swaps", indexes)
+}
+/// Ids of the scheduled swaps keyed by execution time in seconds and id
+pub const SCHEDULED_SWAP_TIMES: Map<(u64, u64), bool> = Map::new("scheduled_swap_times");
+
+pub const MAKER_KEYS: Map<&Addr, Binary> = Map::new("maker_keys");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct MakerBalance {
+    pub token1: Uint128,
+    pub token2: Uint128,
+}
+
+pub const MAKER_BALANCES: Map<&Addr, MakerBalance> = Map::new("maker_balances");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct SignedOrderStatus {
+    pub filled: Uint128,
+    pub cancelled: bool,
+}
+
+/// Fill state of the signed orders keyed by maker and nonce
+pub const SIGNED_ORDERS: Map<(&Addr, u64), SignedOrderStatus> = Map::new("signed_orders");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OtcOffer {
+    pub maker: Addr,
+    pub counterparty: Addr,
+    pub offer_denom: Denom,
+    pub offer_amount: Uint128,
+    pub ask_denom: Denom,
+    pub ask_amount: Uint128,
+    pub expires: Expiration,
+}
+
+pub const OTC_OFFER_COUNT: Item<u64> = Item::new("otc_offer_count");
+
+pub struct OtcOfferIndexes<'a> {
+    pub maker: MultiIndex<'a, (Vec<u8>, Vec<u8>), OtcOffer>,
+    pub counterparty: MultiIndex<'a, (Vec<u8>, Vec<u8>), OtcOffer>,
+}
+
+impl<'a> IndexList<OtcOffer> for OtcOfferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<OtcOffer>> + '_> {
+        let v: Vec<&dyn Index<OtcOffer>> = vec![&self.maker, &self.counterparty];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn otc_offers<'a>() -> IndexedMap<'a, u64, OtcOffer, OtcOfferIndexes<'a>> {
+    let indexes = OtcOfferIndexes {
+        maker: MultiIndex::new(
+            |d: &OtcOffer, k| (d.maker.as_bytes().to_vec(), k),
+            "otc_offers",
+            "otc_offers__maker",
+        ),
+        counterparty: MultiIndex::new(
+            |d: &OtcOffer, k| (d.counterparty.as_bytes().to_vec(), k),
+            "otc_offers",
+            "otc_offers__counterparty",
+        ),
+    };
+    IndexedMap::new("otc_offers", indexes)
+}
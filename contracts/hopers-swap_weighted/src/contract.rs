@@ -0,0 +1,1088 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    attr, entry_point, to_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+};
+use cw0::parse_reply_instantiate_data;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Denom::Cw20;
+use cw20::{Cw20ExecuteMsg, Denom, Expiration, MinterResponse};
+use cw_storage_plus::Item;
+
+use crate::error::ContractError;
+use crate::math::{get_lp_for_single_token_in, get_out_given_in, get_spot_price};
+use crate::msg::{
+    ExecuteMsg, FeeResponse, InfoResponse, InstantiateMsg, MigrateMsg, QueryMsg, SpotPriceResponse,
+    Token1ForToken2PriceResponse, Token2ForToken1PriceResponse, TokenSelect, WalletInfo,
+};
+use crate::state::{Fees, Token, Weights, FEES, LP_TOKEN, OWNER, TOKEN1, TOKEN2, WEIGHTS};
+
+// Version info for migration info
+pub const CONTRACT_NAME: &str = "crates.io:hopers-swap-weighted";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INSTANTIATE_LP_TOKEN_REPLY_ID: u64 = 0;
+
+const MAX_FEE_PERCENT: &str = "1";
+const MIN_WEIGHT: &str = "0.01";
+// Inputs are limited to a share of the reserve to keep the power approximation accurate
+const MAX_IN_RATIO: &str = "0.5";
+
+// Note, you can use StdResult in some functions where you do not
+// make use of the custom errors
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let token1 = Token {
+        reserve: Uint128::zero(),
+        denom: msg.token1_denom,
+    };
+    TOKEN1.save(deps.storage, &token1)?;
+
+    let token2 = Token {
+        denom: msg.token2_denom,
+        reserve: Uint128::zero(),
+    };
+    TOKEN2.save(deps.storage, &token2)?;
+
+    let owner = msg.owner.map(|h| deps.api.addr_validate(&h)).transpose()?;
+    OWNER.save(deps.storage, &owner)?;
+
+    let mut total_ratio = Decimal::zero();
+    for dev_wallet in msg.dev_wallet_lists.iter() {
+        deps.api.addr_validate(&dev_wallet.address)?;
+        total_ratio += dev_wallet.ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(ContractError::WrongRatio {});
+    }
+
+    let min_weight = Decimal::from_str(MIN_WEIGHT)?;
+    if msg.token1_weight < min_weight
+        || msg.token2_weight < min_weight
+        || msg.token1_weight + msg.token2_weight != Decimal::one()
+    {
+        return Err(ContractError::InvalidWeights { min_weight });
+    }
+    WEIGHTS.save(
+        deps.storage,
+        &Weights {
+            token1_weight: msg.token1_weight,
+            token2_weight: msg.token2_weight,
+        },
+    )?;
+
+    let num = msg.fee_percent_numerator.u128();
+    let den = msg.fee_percent_denominator.u128();
+    let total_fee_percent = Decimal::from_ratio(num, den);
+    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
+
+    if total_fee_percent > max_fee_percent {
+        return Err(ContractError::FeesTooHigh {
+            max_fee_percent,
+            total_fee_percent,
+        });
+    }
+
+    let fees = Fees {
+        fee_percent_numerator: msg.fee_percent_numerator,
+        fee_percent_denominator: msg.fee_percent_denominator,
+        dev_wallet_lists: msg.dev_wallet_lists,
+    };
+    FEES.save(deps.storage, &fees)?;
+
+    let instantiate_lp_token_msg = WasmMsg::Instantiate {
+        code_id: msg.lp_token_code_id,
+        funds: vec![],
+        admin: Some(info.sender.to_string()),
+        label: msg.lp_token_name.clone(),
+        msg: to_binary(&cw20_base::msg::InstantiateMsg {
+            name: msg.lp_token_name,
+            symbol: msg.lp_token_symbol,
+            decimals: 6,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: env.contract.address.into(),
+                cap: None,
+            }),
+            marketing: None,
+        })?,
+    };
+
+    let reply_msg =
+        SubMsg::reply_on_success(instantiate_lp_token_msg, INSTANTIATE_LP_TOKEN_REPLY_ID);
+
+    Ok(Response::new().add_submessage(reply_msg))
+}
+
+// And declare a custom Error variant for the ones where you will want to make use of it
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::AddLiquidity {
+            token1_amount,
+            min_liquidity,
+            max_token2,
+            expiration,
+        } => execute_add_liquidity(
+            deps,
+            &info,
+            env,
+            min_liquidity,
+            token1_amount,
+            max_token2,
+            expiration,
+        ),
+        ExecuteMsg::AddSingleSidedLiquidity {
+            input_token,
+            input_amount,
+            min_liquidity,
+            expiration,
+        } => execute_add_single_sided_liquidity(
+            deps,
+            &info,
+            env,
+            input_token,
+            input_amount,
+            min_liquidity,
+            expiration,
+        ),
+        ExecuteMsg::RemoveLiquidity {
+            amount,
+            min_token1,
+            min_token2,
+            expiration,
+        } => execute_remove_liquidity(deps, info, env, amount, min_token1, min_token2, expiration),
+        ExecuteMsg::Swap {
+            input_token,
+            input_amount,
+            min_output,
+            expiration,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_token,
+            info.sender.to_string(),
+            min_output,
+            expiration,
+        ),
+        ExecuteMsg::SwapAndSendTo {
+            input_token,
+            input_amount,
+            recipient,
+            min_token,
+            expiration,
+        } => execute_swap(
+            deps,
+            &info,
+            input_amount,
+            env,
+            input_token,
+            recipient,
+            min_token,
+            expiration,
+        ),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            dev_wallet_lists,
+            fee_percent_numerator,
+            fee_percent_denominator,
+        } => execute_update_config(
+            deps,
+            info,
+            owner,
+            fee_percent_numerator,
+            fee_percent_denominator,
+            dev_wallet_lists,
+        ),
+    }
+}
+
+fn check_expiration(
+    expiration: &Option<Expiration>,
+    block: &BlockInfo,
+) -> Result<(), ContractError> {
+    match expiration {
+        Some(e) => {
+            if e.is_expired(block) {
+                return Err(ContractError::MsgExpirationError {});
+            }
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+fn get_lp_token_amount_to_mint(
+    token1_amount: Uint128,
+    liquidity_supply: Uint128,
+    token1_reserve: Uint128,
+) -> Result<Uint128, ContractError> {
+    if liquidity_supply == Uint128::zero() {
+        Ok(token1_amount)
+    } else {
+        Ok(token1_amount
+            .checked_mul(liquidity_supply)
+            .map_err(StdError::overflow)?
+            .checked_div(token1_reserve)
+            .map_err(StdError::divide_by_zero)?)
+    }
+}
+
+fn get_token2_amount_required(
+    max_token: Uint128,
+    token1_amount: Uint128,
+    liquidity_supply: Uint128,
+    token2_reserve: Uint128,
+    token1_reserve: Uint128,
+) -> Result<Uint128, StdError> {
+    if liquidity_supply == Uint128::zero() {
+        Ok(max_token)
+    } else {
+        Ok(token1_amount
+            .checked_mul(token2_reserve)
+            .map_err(StdError::overflow)?
+            .checked_div(token1_reserve)
+            .map_err(StdError::divide_by_zero)?
+            .checked_add(Uint128::new(1))
+            .map_err(StdError::overflow)?)
+    }
+}
+
+pub fn execute_add_liquidity(
+    deps: DepsMut,
+    info: &MessageInfo,
+    env: Env,
+    min_liquidity: Uint128,
+    token1_amount: Uint128,
+    max_token2: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let action = "add_liquidity".to_string();
+    check_expiration(&expiration, &env.block)?;
+
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+
+    // validate funds
+    validate_input_amount(&info.funds, token1_amount, &token1.denom)?;
+    validate_input_amount(&info.funds, max_token2, &token2.denom)?;
+
+    let lp_token_supply = get_lp_token_supply(deps.as_ref(), &lp_token_addr)?;
+    let liquidity_amount =
+        get_lp_token_amount_to_mint(token1_amount, lp_token_supply, token1.reserve)?;
+
+    let token2_amount = get_token2_amount_required(
+        max_token2,
+        token1_amount,
+        lp_token_supply,
+        token2.reserve,
+        token1.reserve,
+    )?;
+
+    if liquidity_amount < min_liquidity {
+        return Err(ContractError::MinLiquidityError {
+            min_liquidity,
+            liquidity_available: liquidity_amount,
+        });
+    }
+
+    if token2_amount > max_token2 {
+        return Err(ContractError::MaxTokenError {
+            max_token: max_token2,
+            tokens_required: token2_amount,
+        });
+    }
+
+    // Generate cw20 transfer messages if necessary
+    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
+    if let Cw20(addr) = token1.denom {
+        transfer_msgs.push(get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            &addr,
+            token1_amount,
+        )?)
+    }
+    if let Cw20(addr) = token2.denom.clone() {
+        transfer_msgs.push(get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            &addr,
+            token2_amount,
+        )?)
+    }
+
+    // Refund token 2 if is a native token and not all is spent
+    if let Denom::Native(denom) = token2.denom {
+        if token2_amount < max_token2 {
+            transfer_msgs.push(get_bank_transfer_to_msg(
+                &info.sender,
+                &denom,
+                max_token2 - token2_amount,
+            ))
+        }
+    }
+
+    TOKEN1.update(deps.storage, |mut token1| -> Result<_, ContractError> {
+        token1.reserve += token1_amount;
+        Ok(token1)
+    })?;
+    TOKEN2.update(deps.storage, |mut token2| -> Result<_, ContractError> {
+        token2.reserve += token2_amount;
+        Ok(token2)
+    })?;
+
+    let mint_msg = mint_lp_tokens(&info.sender, liquidity_amount, &lp_token_addr)?;
+    Ok(Response::new()
+        .add_messages(transfer_msgs)
+        .add_message(mint_msg)
+        .add_attributes(vec![
+            attr("action", action),
+            attr("token1_amount", token1_amount),
+            attr("token2_amount", token2_amount),
+            attr("liquidity_received", liquidity_amount),
+        ]))
+}
+
+// Returns the input and output token items with their weights
+fn select_tokens(
+    deps: Deps,
+    input_token_enum: &TokenSelect,
+) -> StdResult<(Item<'static, Token>, Decimal, Item<'static, Token>, Decimal)> {
+    let weights = WEIGHTS.load(deps.storage)?;
+    Ok(match input_token_enum {
+        TokenSelect::Token1 => (TOKEN1, weights.token1_weight, TOKEN2, weights.token2_weight),
+        TokenSelect::Token2 => (TOKEN2, weights.token2_weight, TOKEN1, weights.token1_weight),
+    })
+}
+
+fn check_max_in_ratio(input_amount: Uint128, input_reserve: Uint128) -> Result<(), ContractError> {
+    let max_in_ratio = Decimal::from_str(MAX_IN_RATIO)?;
+    if input_amount > input_reserve * max_in_ratio {
+        return Err(ContractError::MaxInRatio { max_in_ratio });
+    }
+    Ok(())
+}
+
+pub fn execute_add_single_sided_liquidity(
+    deps: DepsMut,
+    info: &MessageInfo,
+    env: Env,
+    input_token_enum: TokenSelect,
+    input_amount: Uint128,
+    min_liquidity: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let (input_token_item, input_weight, _, _) = select_tokens(deps.as_ref(), &input_token_enum)?;
+    let input_token = input_token_item.load(deps.storage)?;
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+
+    // validate input_amount if native input token
+    validate_input_amount(&info.funds, input_amount, &input_token.denom)?;
+    check_max_in_ratio(input_amount, input_token.reserve)?;
+
+    // Only the share of the deposit implicitly swapped to the other token pays the fee
+    let fees = FEES.load(deps.storage)?;
+    let protocol_fee_amount = get_protocol_fee_amount(
+        input_amount * (Decimal::one() - input_weight),
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    let input_amount_minus_protocol_fee = input_amount - protocol_fee_amount;
+
+    let lp_token_supply = get_lp_token_supply(deps.as_ref(), &lp_token_addr)?;
+    let liquidity_amount = get_lp_for_single_token_in(
+        input_token.reserve,
+        input_weight,
+        lp_token_supply,
+        input_amount_minus_protocol_fee,
+    )?;
+    if liquidity_amount.is_zero() || liquidity_amount < min_liquidity {
+        return Err(ContractError::MinLiquidityError {
+            min_liquidity,
+            liquidity_available: liquidity_amount,
+        });
+    }
+
+    let mut msgs = match input_token.denom.clone() {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            &addr,
+            input_amount_minus_protocol_fee,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    // Send protocol fee to protocol fee recipient
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if fee_amount > Uint128::zero() {
+            msgs.push(get_fee_transfer_msg(
+                &info.sender,
+                &deps.api.addr_validate(&dev_wallet.address)?,
+                &input_token.denom,
+                fee_amount,
+            )?)
+        }
+    }
+
+    msgs.push(mint_lp_tokens(
+        &info.sender,
+        liquidity_amount,
+        &lp_token_addr,
+    )?);
+
+    input_token_item.update(
+        deps.storage,
+        |mut input_token| -> Result<_, ContractError> {
+            input_token.reserve = input_token
+                .reserve
+                .checked_add(input_amount_minus_protocol_fee)
+                .map_err(StdError::overflow)?;
+            Ok(input_token)
+        },
+    )?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "add_single_sided_liquidity"),
+        attr("input_amount", input_amount),
+        attr("protocol_fee_amount", protocol_fee_amount),
+        attr("liquidity_received", liquidity_amount),
+    ]))
+}
+
+fn get_lp_token_supply(deps: Deps, lp_token_addr: &Addr) -> StdResult<Uint128> {
+    let resp: cw20::TokenInfoResponse = deps
+        .querier
+        .query_wasm_smart(lp_token_addr, &cw20_base::msg::QueryMsg::TokenInfo {})?;
+    Ok(resp.total_supply)
+}
+
+fn mint_lp_tokens(
+    recipient: &Addr,
+    liquidity_amount: Uint128,
+    lp_token_address: &Addr,
+) -> StdResult<CosmosMsg> {
+    let mint_msg = cw20_base::msg::ExecuteMsg::Mint {
+        recipient: recipient.into(),
+        amount: liquidity_amount,
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: lp_token_address.to_string(),
+        msg: to_binary(&mint_msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn get_token_balance(deps: Deps, contract: &Addr, addr: &Addr) -> StdResult<Uint128> {
+    let resp: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+        contract,
+        &cw20_base::msg::QueryMsg::Balance {
+            address: addr.to_string(),
+        },
+    )?;
+    Ok(resp.balance)
+}
+
+fn validate_input_amount(
+    actual_funds: &[Coin],
+    given_amount: Uint128,
+    given_denom: &Denom,
+) -> Result<(), ContractError> {
+    match given_denom {
+        Denom::Cw20(_) => Ok(()),
+        Denom::Native(denom) => {
+            let actual = get_amount_for_denom(actual_funds, denom);
+            if actual.amount != given_amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            if &actual.denom != denom {
+                return Err(ContractError::IncorrectNativeDenom {
+                    provided: actual.denom,
+                    required: denom.to_string(),
+                });
+            };
+            Ok(())
+        }
+    }
+}
+
+fn get_cw20_transfer_from_msg(
+    owner: &Addr,
+    recipient: &Addr,
+    token_addr: &Addr,
+    token_amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    // create transfer cw20 msg
+    let transfer_cw20_msg = Cw20ExecuteMsg::TransferFrom {
+        owner: owner.into(),
+        recipient: recipient.into(),
+        amount: token_amount,
+    };
+    let exec_cw20_transfer = WasmMsg::Execute {
+        contract_addr: token_addr.into(),
+        msg: to_binary(&transfer_cw20_msg)?,
+        funds: vec![],
+    };
+    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
+    Ok(cw20_transfer_cosmos_msg)
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: Option<String>,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+    dev_wallet_lists: Vec<WalletInfo>,
+) -> Result<Response, ContractError> {
+    let owner = OWNER.load(deps.storage)?;
+    if Some(info.sender) != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner_addr = new_owner
+        .as_ref()
+        .map(|h| deps.api.addr_validate(h))
+        .transpose()?;
+    OWNER.save(deps.storage, &new_owner_addr)?;
+
+    let num = fee_percent_numerator.u128();
+    let den = fee_percent_denominator.u128();
+
+    let total_fee_percent = Decimal::from_ratio(num, den);
+    let max_fee_percent = Decimal::from_str(MAX_FEE_PERCENT)?;
+    if total_fee_percent > max_fee_percent {
+        return Err(ContractError::FeesTooHigh {
+            max_fee_percent,
+            total_fee_percent,
+        });
+    }
+
+    let mut total_ratio = Decimal::zero();
+    for dev_wallet in dev_wallet_lists.clone() {
+        deps.api.addr_validate(&dev_wallet.address)?;
+        total_ratio += dev_wallet.ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(ContractError::WrongRatio {});
+    }
+
+    let updated_fees = Fees {
+        dev_wallet_lists,
+        fee_percent_numerator,
+        fee_percent_denominator,
+    };
+    FEES.save(deps.storage, &updated_fees)?;
+
+    let new_owner = new_owner.unwrap_or_default();
+    Ok(Response::new().add_attributes(vec![
+        attr("new_owner", new_owner),
+        attr("fee_percent", total_fee_percent.to_string()),
+    ]))
+}
+
+pub fn execute_remove_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    amount: Uint128,
+    min_token1: Uint128,
+    min_token2: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let action = "remove_liquidity".to_string();
+    check_expiration(&expiration, &env.block)?;
+
+    let lp_token_addr = LP_TOKEN.load(deps.storage)?;
+    let balance = get_token_balance(deps.as_ref(), &lp_token_addr, &info.sender)?;
+    let lp_token_supply = get_lp_token_supply(deps.as_ref(), &lp_token_addr)?;
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+
+    if amount > balance {
+        return Err(ContractError::InsufficientLiquidityError {
+            requested: amount,
+            available: balance,
+        });
+    }
+
+    let token1_amount = amount
+        .checked_mul(token1.reserve)
+        .map_err(StdError::overflow)?
+        .checked_div(lp_token_supply)
+        .map_err(StdError::divide_by_zero)?;
+    if token1_amount < min_token1 {
+        return Err(ContractError::MinToken1Error {
+            requested: min_token1,
+            available: token1_amount,
+        });
+    }
+
+    let token2_amount = amount
+        .checked_mul(token2.reserve)
+        .map_err(StdError::overflow)?
+        .checked_div(lp_token_supply)
+        .map_err(StdError::divide_by_zero)?;
+    if token2_amount < min_token2 {
+        return Err(ContractError::MinToken2Error {
+            requested: min_token2,
+            available: token2_amount,
+        });
+    }
+
+    TOKEN1.update(deps.storage, |mut token1| -> Result<_, ContractError> {
+        token1.reserve = token1
+            .reserve
+            .checked_sub(token1_amount)
+            .map_err(StdError::overflow)?;
+        Ok(token1)
+    })?;
+
+    TOKEN2.update(deps.storage, |mut token2| -> Result<_, ContractError> {
+        token2.reserve = token2
+            .reserve
+            .checked_sub(token2_amount)
+            .map_err(StdError::overflow)?;
+        Ok(token2)
+    })?;
+
+    let token1_transfer_msg = match token1.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, &addr, token1_amount)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, &denom, token1_amount),
+    };
+    let token2_transfer_msg = match token2.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&info.sender, &addr, token2_amount)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&info.sender, &denom, token2_amount),
+    };
+
+    let lp_token_burn_msg = get_burn_msg(&lp_token_addr, &info.sender, amount)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            token1_transfer_msg,
+            token2_transfer_msg,
+            lp_token_burn_msg,
+        ])
+        .add_attributes(vec![
+            attr("action", action),
+            attr("liquidity_burned", amount),
+            attr("token1_returned", token1_amount),
+            attr("token2_returned", token2_amount),
+        ]))
+}
+
+fn get_burn_msg(contract: &Addr, owner: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = cw20_base::msg::ExecuteMsg::BurnFrom {
+        owner: owner.to_string(),
+        amount,
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: contract.to_string(),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn get_cw20_transfer_to_msg(
+    recipient: &Addr,
+    token_addr: &Addr,
+    token_amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    // create transfer cw20 msg
+    let transfer_cw20_msg = Cw20ExecuteMsg::Transfer {
+        recipient: recipient.into(),
+        amount: token_amount,
+    };
+    let exec_cw20_transfer = WasmMsg::Execute {
+        contract_addr: token_addr.into(),
+        msg: to_binary(&transfer_cw20_msg)?,
+        funds: vec![],
+    };
+    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
+    Ok(cw20_transfer_cosmos_msg)
+}
+
+fn get_bank_transfer_to_msg(recipient: &Addr, denom: &str, native_amount: Uint128) -> CosmosMsg {
+    let transfer_bank_msg = cosmwasm_std::BankMsg::Send {
+        to_address: recipient.into(),
+        amount: vec![Coin {
+            denom: denom.to_string(),
+            amount: native_amount,
+        }],
+    };
+
+    let transfer_bank_cosmos_msg: CosmosMsg = transfer_bank_msg.into();
+    transfer_bank_cosmos_msg
+}
+
+fn get_fee_transfer_msg(
+    sender: &Addr,
+    recipient: &Addr,
+    fee_denom: &Denom,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match fee_denom {
+        Denom::Cw20(addr) => get_cw20_transfer_from_msg(sender, recipient, addr, amount),
+        Denom::Native(denom) => Ok(get_bank_transfer_to_msg(recipient, denom, amount)),
+    }
+}
+
+pub fn get_input_price(
+    input_amount: Uint128,
+    input_reserve: Uint128,
+    input_weight: Decimal,
+    output_reserve: Uint128,
+    output_weight: Decimal,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+) -> StdResult<Uint128> {
+    let input_amount_with_fee = input_amount.multiply_ratio(
+        fee_percent_denominator - fee_percent_numerator,
+        fee_percent_denominator,
+    );
+    get_out_given_in(
+        input_reserve,
+        input_weight,
+        output_reserve,
+        output_weight,
+        input_amount_with_fee,
+    )
+}
+
+pub fn get_protocol_fee_amount(
+    input_amount: Uint128,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+) -> StdResult<Uint128> {
+    if fee_percent_numerator.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    input_amount
+        .checked_mul(fee_percent_numerator)
+        .map_err(StdError::overflow)?
+        .checked_div(fee_percent_denominator)
+        .map_err(StdError::divide_by_zero)
+}
+
+fn get_amount_for_denom(coins: &[Coin], denom: &str) -> Coin {
+    let amount: Uint128 = coins
+        .iter()
+        .filter(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .sum();
+    Coin {
+        amount,
+        denom: denom.to_string(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap(
+    deps: DepsMut,
+    info: &MessageInfo,
+    input_amount: Uint128,
+    env: Env,
+    input_token_enum: TokenSelect,
+    recipient: String,
+    min_token: Uint128,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_expiration(&expiration, &env.block)?;
+
+    let (input_token_item, input_weight, output_token_item, output_weight) =
+        select_tokens(deps.as_ref(), &input_token_enum)?;
+    let input_token = input_token_item.load(deps.storage)?;
+    let output_token = output_token_item.load(deps.storage)?;
+
+    // validate input_amount if native input token
+    validate_input_amount(&info.funds, input_amount, &input_token.denom)?;
+    check_max_in_ratio(input_amount, input_token.reserve)?;
+
+    let fees = FEES.load(deps.storage)?;
+
+    let token_bought = get_input_price(
+        input_amount,
+        input_token.reserve,
+        input_weight,
+        output_token.reserve,
+        output_weight,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+
+    if min_token > token_bought {
+        return Err(ContractError::SwapMinError {
+            min: min_token,
+            available: token_bought,
+        });
+    }
+    // Calculate fees
+    let protocol_fee_amount = get_protocol_fee_amount(
+        input_amount,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )?;
+    let input_amount_minus_protocol_fee = input_amount - protocol_fee_amount;
+
+    let mut msgs = match input_token.denom.clone() {
+        Denom::Cw20(addr) => vec![get_cw20_transfer_from_msg(
+            &info.sender,
+            &env.contract.address,
+            &addr,
+            input_amount_minus_protocol_fee,
+        )?],
+        Denom::Native(_) => vec![],
+    };
+
+    // Send protocol fee to protocol fee recipient
+    for dev_wallet in fees.dev_wallet_lists {
+        let fee_amount = protocol_fee_amount * dev_wallet.ratio;
+        if fee_amount > Uint128::zero() {
+            msgs.push(get_fee_transfer_msg(
+                &info.sender,
+                &deps.api.addr_validate(&dev_wallet.address)?,
+                &input_token.denom,
+                fee_amount,
+            )?)
+        }
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    // Create transfer to message
+    msgs.push(match output_token.denom {
+        Denom::Cw20(addr) => get_cw20_transfer_to_msg(&recipient, &addr, token_bought)?,
+        Denom::Native(denom) => get_bank_transfer_to_msg(&recipient, &denom, token_bought),
+    });
+
+    input_token_item.update(
+        deps.storage,
+        |mut input_token| -> Result<_, ContractError> {
+            input_token.reserve = input_token
+                .reserve
+                .checked_add(input_amount_minus_protocol_fee)
+                .map_err(StdError::overflow)?;
+            Ok(input_token)
+        },
+    )?;
+
+    output_token_item.update(
+        deps.storage,
+        |mut output_token| -> Result<_, ContractError> {
+            output_token.reserve = output_token
+                .reserve
+                .checked_sub(token_bought)
+                .map_err(StdError::overflow)?;
+            Ok(output_token)
+        },
+    )?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "swap"),
+        attr("native_sold", input_amount),
+        attr("token_bought", token_bought),
+        attr("protocol_fee_amount", protocol_fee_amount),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Info {} => to_binary(&query_info(deps)?),
+        QueryMsg::Token1ForToken2Price { token1_amount } => {
+            to_binary(&query_token1_for_token2_price(deps, token1_amount)?)
+        }
+        QueryMsg::Token2ForToken1Price { token2_amount } => {
+            to_binary(&query_token2_for_token1_price(deps, token2_amount)?)
+        }
+        QueryMsg::SpotPrice {} => to_binary(&query_spot_price(deps)?),
+        QueryMsg::Fee {} => to_binary(&query_fee(deps)?),
+    }
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let weights = WEIGHTS.load(deps.storage)?;
+    let lp_token_address = LP_TOKEN.load(deps.storage)?;
+
+    Ok(InfoResponse {
+        token1_reserve: token1.reserve,
+        token1_denom: token1.denom,
+        token1_weight: weights.token1_weight,
+        token2_reserve: token2.reserve,
+        token2_denom: token2.denom,
+        token2_weight: weights.token2_weight,
+        lp_token_supply: get_lp_token_supply(deps, &lp_token_address)?,
+        lp_token_address: lp_token_address.into_string(),
+    })
+}
+
+fn simulate_swap(
+    deps: Deps,
+    input_token_enum: TokenSelect,
+    input_amount: Uint128,
+) -> StdResult<Uint128> {
+    let (input_token_item, input_weight, output_token_item, output_weight) =
+        select_tokens(deps, &input_token_enum)?;
+    let input_token = input_token_item.load(deps.storage)?;
+    let output_token = output_token_item.load(deps.storage)?;
+
+    let fees = FEES.load(deps.storage)?;
+    get_input_price(
+        input_amount,
+        input_token.reserve,
+        input_weight,
+        output_token.reserve,
+        output_weight,
+        fees.fee_percent_numerator,
+        fees.fee_percent_denominator,
+    )
+}
+
+pub fn query_token1_for_token2_price(
+    deps: Deps,
+    token1_amount: Uint128,
+) -> StdResult<Token1ForToken2PriceResponse> {
+    let token2_amount = simulate_swap(deps, TokenSelect::Token1, token1_amount)?;
+    Ok(Token1ForToken2PriceResponse { token2_amount })
+}
+
+pub fn query_token2_for_token1_price(
+    deps: Deps,
+    token2_amount: Uint128,
+) -> StdResult<Token2ForToken1PriceResponse> {
+    let token1_amount = simulate_swap(deps, TokenSelect::Token2, token2_amount)?;
+    Ok(Token2ForToken1PriceResponse { token1_amount })
+}
+
+pub fn query_spot_price(deps: Deps) -> StdResult<SpotPriceResponse> {
+    let token1 = TOKEN1.load(deps.storage)?;
+    let token2 = TOKEN2.load(deps.storage)?;
+    let weights = WEIGHTS.load(deps.storage)?;
+
+    let price = get_spot_price(
+        token2.reserve,
+        weights.token2_weight,
+        token1.reserve,
+        weights.token1_weight,
+    )?;
+    Ok(SpotPriceResponse { price })
+}
+
+pub fn query_fee(deps: Deps) -> StdResult<FeeResponse> {
+    let fees = FEES.load(deps.storage)?;
+    let owner = OWNER.load(deps.storage)?.map(|o| o.into_string());
+
+    let num = fees.fee_percent_numerator.u128();
+    let den = fees.fee_percent_denominator.u128();
+    let total_fee_percent = Decimal::from_ratio(num, den);
+
+    Ok(FeeResponse {
+        owner,
+        total_fee_percent,
+        dev_wallet_lists: fees.dev_wallet_lists,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != INSTANTIATE_LP_TOKEN_REPLY_ID {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    };
+    let res = parse_reply_instantiate_data(msg);
+    match res {
+        Ok(res) => {
+            // Validate contract address
+            let cw20_addr = deps.api.addr_validate(&res.contract_address)?;
+
+            // Save gov token
+            LP_TOKEN.save(deps.storage, &cw20_addr)?;
+
+            Ok(Response::new())
+        }
+        Err(_) => Err(ContractError::InstantiateLpTokenError {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.contract,
+        });
+    }
+
+    if version.version != CONTRACT_VERSION {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.version,
+        });
+    }
+
+    Ok(Response::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_liquidity_amount() {
+        let liquidity =
+            get_lp_token_amount_to_mint(Uint128::new(100), Uint128::zero(), Uint128::zero())
+                .unwrap();
+        assert_eq!(liquidity, Uint128::new(100));
+
+        let liquidity =
+            get_lp_token_amount_to_mint(Uint128::new(100), Uint128::new(50), Uint128::new(25))
+                .unwrap();
+        assert_eq!(liquidity, Uint128::new(200));
+    }
+
+    #[test]
+    fn test_get_token_amount() {
+        let liquidity = get_token2_amount_required(
+            Uint128::new(100),
+            Uint128::new(50),
+            Uint128::zero(),
+            Uint128::zero(),
+            Uint128::zero(),
+        )
+        .unwrap();
+        assert_eq!(liquidity, Uint128::new(100));
+
+        let liquidity = get_token2_amount_required(
+            Uint128::new(200),
+            Uint128::new(50),
+            Uint128::new(50),
+            Uint128::new(100),
+            Uint128::new(25),
+        )
+        .unwrap();
+        assert_eq!(liquidity, Uint128::new(201));
+    }
+}
@@ -0,0 +1,408 @@
+#![cfg(test)]
+
+use std::borrow::BorrowMut;
+
+use cosmwasm_std::{coins, Addr, Coin, Decimal, Decimal256, Empty, Uint128};
+use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Denom};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use std::str::FromStr;
+
+use crate::contract::{get_input_price, get_protocol_fee_amount};
+use crate::error::ContractError;
+use crate::msg::{
+    ExecuteMsg, InfoResponse, InstantiateMsg, QueryMsg, SpotPriceResponse,
+    Token2ForToken1PriceResponse, TokenSelect, WalletInfo,
+};
+
+const NATIVE_TOKEN_DENOM: &str = "juno";
+
+fn mock_app() -> App {
+    App::default()
+}
+
+pub fn contract_amm() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply)
+    .with_migrate(crate::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn get_info(router: &App, contract_addr: &Addr) -> InfoResponse {
+    router
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Info {})
+        .unwrap()
+}
+
+fn instantiate_msg(router: &mut App, token1_denom: Denom, owner: &Addr) -> InstantiateMsg {
+    let cw20_id = router.store_code(contract_cw20());
+    InstantiateMsg {
+        token1_denom,
+        token2_denom: Denom::Native(NATIVE_TOKEN_DENOM.into()),
+        token1_weight: Decimal::from_str("0.8").unwrap(),
+        token2_weight: Decimal::from_str("0.2").unwrap(),
+        lp_token_code_id: cw20_id,
+        owner: Some(owner.to_string()),
+        fee_percent_numerator: Uint128::new(3),
+        fee_percent_denominator: Uint128::new(1000),
+        lp_token_name: "lp_token".to_string(),
+        lp_token_symbol: "lpsymbol".to_string(),
+        dev_wallet_lists: vec![WalletInfo {
+            address: "dev".to_string(),
+            ratio: Decimal::one(),
+        }],
+    }
+}
+
+// CreateCW20 create new cw20 with given initial balance belonging to owner
+fn create_cw20(
+    router: &mut App,
+    owner: &Addr,
+    name: String,
+    symbol: String,
+    balance: Uint128,
+) -> Cw20Contract {
+    // set up cw20 contract with some tokens
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name,
+        symbol,
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: balance,
+        }],
+        mint: None,
+        marketing: None,
+    };
+    let addr = router
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "CASH", None)
+        .unwrap();
+    Cw20Contract(addr)
+}
+
+fn bank_balance(router: &mut App, addr: &Addr, denom: String) -> Coin {
+    router
+        .wrap()
+        .query_balance(addr.to_string(), denom)
+        .unwrap()
+}
+
+// Sets up an 80/20 HOPERS/JUNO pool with 400_000 HOPERS and 100_000 JUNO
+fn setup() -> (App, Addr, Cw20Contract, Addr) {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(10_000_000, NATIVE_TOKEN_DENOM);
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let cw20_token = create_cw20(
+        &mut router,
+        &owner,
+        "hopers".to_string(),
+        "HOPERS".to_string(),
+        Uint128::new(10_000_000),
+    );
+
+    let amm_id = router.store_code(contract_amm());
+    let msg = instantiate_msg(&mut router, Denom::Cw20(cw20_token.addr()), &owner);
+    let amm_addr = router
+        .instantiate_contract(amm_id, owner.clone(), &msg, &[], "amm", None)
+        .unwrap();
+
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(10_000_000),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), cw20_token.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(400_000),
+        min_liquidity: Uint128::new(400_000),
+        max_token2: Uint128::new(100_000),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &coins(100_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    (router, owner, cw20_token, amm_addr)
+}
+
+#[test]
+fn test_instantiate() {
+    let (mut router, owner, cw20_token, amm_addr) = setup();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_weight, Decimal::from_str("0.8").unwrap());
+    assert_eq!(info.token2_weight, Decimal::from_str("0.2").unwrap());
+    assert_eq!(info.lp_token_address, "contract2".to_string());
+
+    let amm_id = router.store_code(contract_amm());
+    let mut msg = instantiate_msg(&mut router, Denom::Cw20(cw20_token.addr()), &owner);
+    msg.token2_weight = Decimal::from_str("0.3").unwrap();
+    let err = router
+        .instantiate_contract(amm_id, owner.clone(), &msg, &[], "amm", None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::InvalidWeights {
+            min_weight: Decimal::from_str("0.01").unwrap()
+        },
+        err
+    );
+
+    let mut msg = instantiate_msg(&mut router, Denom::Cw20(cw20_token.addr()), &owner);
+    msg.token1_weight = Decimal::one();
+    msg.token2_weight = Decimal::zero();
+    let err = router
+        .instantiate_contract(amm_id, owner, &msg, &[], "amm", None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::InvalidWeights {
+            min_weight: Decimal::from_str("0.01").unwrap()
+        },
+        err
+    );
+}
+
+#[test]
+fn add_and_remove_liquidity() {
+    let (mut router, owner, cw20_token, amm_addr) = setup();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(400_000));
+    assert_eq!(info.token2_reserve, Uint128::new(100_000));
+    assert_eq!(info.lp_token_supply, Uint128::new(400_000));
+
+    // 80% of the value is held in HOPERS, so both sides are worth the same at price 1
+    let spot_price: SpotPriceResponse = router
+        .wrap()
+        .query_wasm_smart(&amm_addr, &QueryMsg::SpotPrice {})
+        .unwrap();
+    assert_eq!(spot_price.price, Decimal256::one());
+
+    // proportional deposits keep the ratio of the reserves
+    let add_liquidity_msg = ExecuteMsg::AddLiquidity {
+        token1_amount: Uint128::new(40_000),
+        min_liquidity: Uint128::new(40_000),
+        max_token2: Uint128::new(20_000),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_liquidity_msg,
+            &coins(20_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(440_000));
+    assert_eq!(info.token2_reserve, Uint128::new(110_001));
+    assert_eq!(info.lp_token_supply, Uint128::new(440_000));
+
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    let allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: amm_addr.to_string(),
+        amount: Uint128::new(220_000),
+        expires: None,
+    };
+    router
+        .execute_contract(owner.clone(), lp_token.addr(), &allowance_msg, &[])
+        .unwrap();
+
+    let remove_liquidity_msg = ExecuteMsg::RemoveLiquidity {
+        amount: Uint128::new(220_000),
+        min_token1: Uint128::new(220_000),
+        min_token2: Uint128::new(55_000),
+        expiration: None,
+    };
+    router
+        .execute_contract(owner.clone(), amm_addr.clone(), &remove_liquidity_msg, &[])
+        .unwrap();
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(220_000));
+    assert_eq!(info.token2_reserve, Uint128::new(55_001));
+    assert_eq!(info.lp_token_supply, Uint128::new(220_000));
+    assert_eq!(
+        cw20_token.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(9_780_000)
+    );
+    assert_eq!(
+        bank_balance(&mut router, &owner, NATIVE_TOKEN_DENOM.to_string()).amount,
+        Uint128::new(9_944_999)
+    );
+}
+
+#[test]
+fn swap_with_weights() {
+    let (mut router, owner, cw20_token, amm_addr) = setup();
+
+    let price: Token2ForToken1PriceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &amm_addr,
+            &QueryMsg::Token2ForToken1Price {
+                token2_amount: Uint128::new(10_000),
+            },
+        )
+        .unwrap();
+    let expected = get_input_price(
+        Uint128::new(10_000),
+        Uint128::new(100_000),
+        Decimal::from_str("0.2").unwrap(),
+        Uint128::new(400_000),
+        Decimal::from_str("0.8").unwrap(),
+        Uint128::new(3),
+        Uint128::new(1000),
+    )
+    .unwrap();
+    assert_eq!(price.token1_amount, expected);
+    // 400_000 * (1 - (100_000 / 109_970)^0.25) is far below the 50/50 output of ~36_260
+    assert_eq!(price.token1_amount, Uint128::new(9_391));
+
+    let swap_msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token2,
+        input_amount: Uint128::new(10_000),
+        min_output: price.token1_amount,
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &swap_msg,
+            &coins(10_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    let protocol_fee =
+        get_protocol_fee_amount(Uint128::new(10_000), Uint128::new(3), Uint128::new(1000)).unwrap();
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(400_000) - expected);
+    assert_eq!(info.token2_reserve, Uint128::new(110_000) - protocol_fee);
+    assert_eq!(
+        cw20_token.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(9_600_000) + expected
+    );
+    assert_eq!(
+        bank_balance(
+            &mut router,
+            &Addr::unchecked("dev"),
+            NATIVE_TOKEN_DENOM.to_string()
+        )
+        .amount,
+        protocol_fee
+    );
+
+    // inputs larger than half of the reserve are rejected
+    let swap_msg = ExecuteMsg::Swap {
+        input_token: TokenSelect::Token2,
+        input_amount: Uint128::new(60_000),
+        min_output: Uint128::zero(),
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(
+            owner,
+            amm_addr,
+            &swap_msg,
+            &coins(60_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        ContractError::MaxInRatio {
+            max_in_ratio: Decimal::from_str("0.5").unwrap()
+        },
+        err
+    );
+}
+
+#[test]
+fn single_sided_liquidity() {
+    let (mut router, owner, _cw20_token, amm_addr) = setup();
+
+    let add_msg = ExecuteMsg::AddSingleSidedLiquidity {
+        input_token: TokenSelect::Token2,
+        input_amount: Uint128::new(10_000),
+        min_liquidity: Uint128::new(1),
+        expiration: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            amm_addr.clone(),
+            &add_msg,
+            &coins(10_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap();
+
+    // the fee is only charged on the 80% implicitly swapped to HOPERS
+    let dev_balance = bank_balance(
+        &mut router,
+        &Addr::unchecked("dev"),
+        NATIVE_TOKEN_DENOM.to_string(),
+    );
+    assert_eq!(dev_balance.amount, Uint128::new(24));
+
+    let info = get_info(&router, &amm_addr);
+    assert_eq!(info.token1_reserve, Uint128::new(400_000));
+    assert_eq!(info.token2_reserve, Uint128::new(109_976));
+    // 400_000 * ((109_976 / 100_000)^0.2 - 1), slightly less than the 8_000 a
+    // proportional deposit of the same value would mint
+    assert_eq!(info.lp_token_supply, Uint128::new(407_680));
+
+    let lp_token = Cw20Contract(Addr::unchecked(info.lp_token_address));
+    assert_eq!(
+        lp_token.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(407_680)
+    );
+
+    let add_msg = ExecuteMsg::AddSingleSidedLiquidity {
+        input_token: TokenSelect::Token2,
+        input_amount: Uint128::new(10_000),
+        min_liquidity: Uint128::new(8_000),
+        expiration: None,
+    };
+    let err = router
+        .execute_contract(
+            owner,
+            amm_addr,
+            &add_msg,
+            &coins(10_000, NATIVE_TOKEN_DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::MinLiquidityError { .. }));
+}
@@ -0,0 +1,6 @@
+pub mod contract;
+pub mod error;
+mod integration_test;
+pub mod math;
+pub mod msg;
+pub mod state;
@@ -0,0 +1,259 @@
+use cosmwasm_std::{Decimal, Decimal256, StdError, StdResult, Uint128, Uint256};
+use std::convert::TryFrom;
+
+// Limits of the series used for the fractional part of an exponent
+const POW_MAX_ITERATIONS: u128 = 150;
+const POW_PRECISION: u128 = 100;
+
+fn pow_precision() -> Decimal256 {
+    Decimal256::from_atomics(POW_PRECISION, 18).unwrap()
+}
+
+pub fn to_decimal256(value: Decimal) -> Decimal256 {
+    Decimal256::new(Uint256::from(value.atomics()))
+}
+
+fn amount_to_decimal(amount: Uint128) -> Decimal256 {
+    Decimal256::from_ratio(amount, 1u128)
+}
+
+fn decimal_to_amount(value: Decimal256) -> StdResult<Uint128> {
+    let amount = value.atomics() / Decimal256::one().atomics();
+    Ok(Uint128::try_from(amount)?)
+}
+
+/// `base^exp` for a `base` in (0, 2). The integer part of the exponent is applied
+/// exactly, the fractional part with the binomial series of `(1 + x)^exp`.
+pub fn pow(base: Decimal256, exp: Decimal256) -> StdResult<Decimal256> {
+    let whole = exp.floor();
+    let remain = exp - whole;
+    let whole_exp = u32::try_from(decimal_to_amount(whole)?.u128())
+        .map_err(|_| StdError::generic_err("Exponent too large"))?;
+
+    let whole_pow = base.checked_pow(whole_exp).map_err(StdError::overflow)?;
+    if remain.is_zero() {
+        return Ok(whole_pow);
+    }
+
+    let partial_pow = pow_approx(base, remain)?;
+    whole_pow
+        .checked_mul(partial_pow)
+        .map_err(StdError::overflow)
+}
+
+fn pow_approx(base: Decimal256, exp: Decimal256) -> StdResult<Decimal256> {
+    let one = Decimal256::one();
+    if base.is_zero() || base >= Decimal256::from_ratio(2u128, 1u128) {
+        return Err(StdError::generic_err("Base out of bounds"));
+    }
+    let (x, x_negative) = if base >= one {
+        (base - one, false)
+    } else {
+        (one - base, true)
+    };
+
+    // Each term is the previous one multiplied by (exp - (k - 1)) * x / k
+    let mut term = one;
+    let mut sum = one;
+    let mut negative = false;
+    for k in 1..=POW_MAX_ITERATIONS {
+        let big_k = Decimal256::from_ratio(k, 1u128);
+        let k_minus_one = big_k - one;
+        let (c, c_negative) = if exp >= k_minus_one {
+            (exp - k_minus_one, false)
+        } else {
+            (k_minus_one - exp, true)
+        };
+        term = term * c * x / big_k;
+        if term.is_zero() {
+            break;
+        }
+
+        if x_negative {
+            negative = !negative;
+        }
+        if c_negative {
+            negative = !negative;
+        }
+        sum = if negative {
+            sum.checked_sub(term).map_err(StdError::overflow)?
+        } else {
+            sum + term
+        };
+
+        if term < pow_precision() {
+            break;
+        }
+    }
+    Ok(sum)
+}
+
+/// Price of the output token denominated in the input token, without fees
+pub fn get_spot_price(
+    balance_in: Uint128,
+    weight_in: Decimal,
+    balance_out: Uint128,
+    weight_out: Decimal,
+) -> StdResult<Decimal256> {
+    if balance_in.is_zero() || balance_out.is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+    let numerator = amount_to_decimal(balance_in) / to_decimal256(weight_in);
+    let denominator = amount_to_decimal(balance_out) / to_decimal256(weight_out);
+    Ok(numerator / denominator)
+}
+
+/// Output amount of a swap keeping `balance_in^weight_in * balance_out^weight_out` constant
+pub fn get_out_given_in(
+    balance_in: Uint128,
+    weight_in: Decimal,
+    balance_out: Uint128,
+    weight_out: Decimal,
+    amount_in: Uint128,
+) -> StdResult<Uint128> {
+    if balance_in.is_zero() || balance_out.is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+    let base = Decimal256::from_ratio(balance_in, balance_in + amount_in);
+    let exp = to_decimal256(weight_in) / to_decimal256(weight_out);
+    let ratio = pow(base, exp)?;
+    decimal_to_amount(amount_to_decimal(balance_out) * (Decimal256::one() - ratio))
+}
+
+/// LP tokens minted for depositing a single token, the deposit is valued by how much it
+/// grows the weighted invariant
+pub fn get_lp_for_single_token_in(
+    balance_in: Uint128,
+    weight_in: Decimal,
+    lp_supply: Uint128,
+    amount_in: Uint128,
+) -> StdResult<Uint128> {
+    if balance_in.is_zero() || lp_supply.is_zero() {
+        return Err(StdError::generic_err("No liquidity"));
+    }
+    let new_balance_ratio = Decimal256::from_ratio(balance_in + amount_in, balance_in);
+    let pool_ratio = pow(new_balance_ratio, to_decimal256(weight_in))?;
+    if pool_ratio <= Decimal256::one() {
+        return Ok(Uint128::zero());
+    }
+    decimal_to_amount(amount_to_decimal(lp_supply) * (pool_ratio - Decimal256::one()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn assert_close(actual: Decimal256, expected: &str) {
+        let expected = Decimal256::from_str(expected).unwrap();
+        let diff = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(
+            diff < Decimal256::from_str("0.000000001").unwrap(),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        let base = Decimal256::from_str("1.5").unwrap();
+        assert_eq!(
+            pow(base, Decimal256::from_str("2").unwrap()).unwrap(),
+            Decimal256::from_str("2.25").unwrap()
+        );
+        assert_close(
+            pow(base, Decimal256::from_str("0.5").unwrap()).unwrap(),
+            "1.224744871391589049",
+        );
+        assert_close(
+            pow(
+                Decimal256::from_str("0.8").unwrap(),
+                Decimal256::from_str("0.25").unwrap(),
+            )
+            .unwrap(),
+            "0.945741609003176",
+        );
+        assert_close(
+            pow(
+                Decimal256::from_str("0.9").unwrap(),
+                Decimal256::from_str("4").unwrap(),
+            )
+            .unwrap(),
+            "0.6561",
+        );
+
+        let err = pow(
+            Decimal256::from_str("2.5").unwrap(),
+            Decimal256::from_str("0.5").unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("Base out of bounds"));
+    }
+
+    #[test]
+    fn test_spot_price() {
+        // 80/20 pool with equal values on both sides
+        let price = get_spot_price(
+            Uint128::new(4_000),
+            Decimal::from_str("0.8").unwrap(),
+            Uint128::new(1_000),
+            Decimal::from_str("0.2").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(price, Decimal256::one());
+    }
+
+    #[test]
+    fn test_out_given_in() {
+        // equal weights match the constant product formula
+        let out = get_out_given_in(
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.5").unwrap(),
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.5").unwrap(),
+            Uint128::new(100_000),
+        )
+        .unwrap();
+        assert_eq!(out, Uint128::new(90_909));
+
+        // 1_000_000 * (1 - (1_000_000 / 1_100_000)^4)
+        let out = get_out_given_in(
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.8").unwrap(),
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.2").unwrap(),
+            Uint128::new(100_000),
+        )
+        .unwrap();
+        assert_eq!(out, Uint128::new(316_986));
+
+        // 1_000_000 * (1 - (1_000_000 / 1_100_000)^0.25)
+        let out = get_out_given_in(
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.2").unwrap(),
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.8").unwrap(),
+            Uint128::new(100_000),
+        )
+        .unwrap();
+        assert_eq!(out, Uint128::new(23_545));
+    }
+
+    #[test]
+    fn test_lp_for_single_token_in() {
+        // 1_000 * ((1_100_000 / 1_000_000)^0.8 - 1)
+        let lp = get_lp_for_single_token_in(
+            Uint128::new(1_000_000),
+            Decimal::from_str("0.8").unwrap(),
+            Uint128::new(1_000),
+            Uint128::new(100_000),
+        )
+        .unwrap();
+        assert_eq!(lp, Uint128::new(79));
+    }
+}
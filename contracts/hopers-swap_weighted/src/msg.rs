@@ -0,0 +1,133 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::QueryResponses;
+
+use cosmwasm_std::{Decimal, Decimal256, Uint128};
+
+use cw20::{Denom, Expiration};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub token1_denom: Denom,
+    pub token2_denom: Denom,
+    /// Weights of the two tokens, e.g. 0.8 and 0.2 for an 80/20 pool
+    pub token1_weight: Decimal,
+    pub token2_weight: Decimal,
+    pub lp_token_code_id: u64,
+    pub owner: Option<String>,
+    // NOTE: Fees percents are out of 100 e.g., 1 = 1%
+    pub fee_percent_numerator: Uint128,
+    pub fee_percent_denominator: Uint128,
+    pub lp_token_name: String,
+    pub lp_token_symbol: String,
+    pub dev_wallet_lists: Vec<WalletInfo>,
+}
+
+#[cw_serde]
+pub enum TokenSelect {
+    Token1,
+    Token2,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Deposits both tokens in the ratio of the current reserves
+    AddLiquidity {
+        token1_amount: Uint128,
+        min_liquidity: Uint128,
+        max_token2: Uint128,
+        expiration: Option<Expiration>,
+    },
+    /// Deposits only one of the tokens. Part of the deposit is implicitly swapped
+    /// for the other token, so the swap fee is charged on that part.
+    AddSingleSidedLiquidity {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        min_liquidity: Uint128,
+        expiration: Option<Expiration>,
+    },
+    RemoveLiquidity {
+        amount: Uint128,
+        min_token1: Uint128,
+        min_token2: Uint128,
+        expiration: Option<Expiration>,
+    },
+    Swap {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        min_output: Uint128,
+        expiration: Option<Expiration>,
+    },
+    SwapAndSendTo {
+        input_token: TokenSelect,
+        input_amount: Uint128,
+        recipient: String,
+        min_token: Uint128,
+        expiration: Option<Expiration>,
+    },
+    UpdateConfig {
+        owner: Option<String>,
+        fee_percent_numerator: Uint128,
+        fee_percent_denominator: Uint128,
+        dev_wallet_lists: Vec<WalletInfo>,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(InfoResponse)]
+    Info {},
+    #[returns(Token1ForToken2PriceResponse)]
+    Token1ForToken2Price { token1_amount: Uint128 },
+    #[returns(Token2ForToken1PriceResponse)]
+    Token2ForToken1Price { token2_amount: Uint128 },
+    /// Weighted price of token1 denominated in token2, without fees
+    #[returns(SpotPriceResponse)]
+    SpotPrice {},
+    #[returns(FeeResponse)]
+    Fee {},
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub token1_reserve: Uint128,
+    pub token1_denom: Denom,
+    pub token1_weight: Decimal,
+    pub token2_reserve: Uint128,
+    pub token2_denom: Denom,
+    pub token2_weight: Decimal,
+    pub lp_token_supply: Uint128,
+    pub lp_token_address: String,
+}
+
+#[cw_serde]
+pub struct FeeResponse {
+    pub owner: Option<String>,
+    pub total_fee_percent: Decimal,
+    pub dev_wallet_lists: Vec<WalletInfo>,
+}
+
+#[cw_serde]
+pub struct Token1ForToken2PriceResponse {
+    pub token2_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Token2ForToken1PriceResponse {
+    pub token1_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct SpotPriceResponse {
+    pub price: Decimal256,
+}
+
+#[cw_serde]
+#[derive(Eq)]
+pub struct WalletInfo {
+    pub address: String,
+    pub ratio: Decimal,
+}
@@ -0,0 +1,77 @@
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, Response, StdResult, Timestamp};
+
+use crate::msg::{AllowlistConfigResponse, IsAllowlistedResponse};
+use crate::state::{AllowlistConfig, ALLOWLIST, ALLOWLIST_CONFIG};
+
+// The pools check the owner before calling the updates, the errors of a failed
+// check are their own
+
+pub fn is_allowlisted(deps: Deps, env: &Env, address: &Addr) -> StdResult<bool> {
+    let config = ALLOWLIST_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if !config.enabled {
+        return Ok(true);
+    }
+    if let Some(public_at) = config.public_at {
+        if env.block.time >= public_at {
+            return Ok(true);
+        }
+    }
+    Ok(ALLOWLIST.may_load(deps.storage, address)?.is_some())
+}
+
+pub fn update_allowlist_config(
+    deps: DepsMut,
+    enabled: bool,
+    public_at: Option<Timestamp>,
+) -> StdResult<Response> {
+    ALLOWLIST_CONFIG.save(deps.storage, &AllowlistConfig { enabled, public_at })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_allowlist_config"),
+        attr("enabled", enabled.to_string()),
+        attr(
+            "public_at",
+            public_at.map_or_else(|| "none".to_string(), |time| time.to_string()),
+        ),
+    ]))
+}
+
+pub fn update_allowlist(
+    deps: DepsMut,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> StdResult<Response> {
+    for address in add.iter() {
+        let address = deps.api.addr_validate(address)?;
+        ALLOWLIST.save(deps.storage, &address, &true)?;
+    }
+    for address in remove.iter() {
+        let address = deps.api.addr_validate(address)?;
+        ALLOWLIST.remove(deps.storage, &address);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_allowlist"),
+        attr("added", add.len().to_string()),
+        attr("removed", remove.len().to_string()),
+    ]))
+}
+
+pub fn query_allowlist_config(deps: Deps) -> StdResult<AllowlistConfigResponse> {
+    let config = ALLOWLIST_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    Ok(AllowlistConfigResponse {
+        enabled: config.enabled,
+        public_at: config.public_at,
+    })
+}
+
+pub fn query_is_allowlisted(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<IsAllowlistedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(IsAllowlistedResponse {
+        allowed: is_allowlisted(deps, &env, &address)?,
+    })
+}
@@ -0,0 +1,9 @@
+pub mod allowlist;
+pub mod msg;
+pub mod state;
+
+pub use crate::allowlist::{
+    is_allowlisted, query_allowlist_config, query_is_allowlisted, update_allowlist,
+    update_allowlist_config,
+};
+pub use crate::msg::{AllowlistConfigResponse, IsAllowlistedResponse};
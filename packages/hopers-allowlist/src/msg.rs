@@ -0,0 +1,14 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Timestamp;
+
+#[cw_serde]
+pub struct AllowlistConfigResponse {
+    pub enabled: bool,
+    pub public_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct IsAllowlistedResponse {
+    /// Whether the address can currently trade and provide liquidity
+    pub allowed: bool,
+}
@@ -0,0 +1,16 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Timestamp};
+use cw_storage_plus::{Item, Map};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct AllowlistConfig {
+    /// Only allowlisted addresses can trade or provide liquidity
+    pub enabled: bool,
+    /// The pool becomes public from this time on
+    pub public_at: Option<Timestamp>,
+}
+
+pub const ALLOWLIST_CONFIG: Item<AllowlistConfig> = Item::new("allowlist_config");
+pub const ALLOWLIST: Map<&Addr, bool> = Map::new("allowlist");
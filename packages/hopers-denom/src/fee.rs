@@ -0,0 +1,16 @@
+use cosmwasm_std::{StdError, StdResult, Uint128};
+
+pub fn get_protocol_fee_amount(
+    input_amount: Uint128,
+    fee_percent_numerator: Uint128,
+    fee_percent_denominator: Uint128,
+) -> StdResult<Uint128> {
+    if fee_percent_numerator.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    input_amount
+        .checked_mul(fee_percent_numerator)
+        .map_err(StdError::overflow)?
+        .checked_div(fee_percent_denominator)
+        .map_err(StdError::divide_by_zero)
+}
@@ -0,0 +1,8 @@
+pub mod fee;
+pub mod transfer;
+
+pub use crate::fee::get_protocol_fee_amount;
+pub use crate::transfer::{
+    denom_to_string, get_amount_for_denom, get_bank_transfer_to_msg, get_cw20_transfer_from_msg,
+    get_cw20_transfer_to_msg, get_fee_transfer_msg, get_transfer_to_msg,
+};